@@ -0,0 +1,1041 @@
+//! Synchronous mirror of the fetchers in `client` and of `FortisClient`, built on the blocking `RpcClient`.
+//!
+//! Request building, filters, decoding, PDA derivation, instruction building and balance
+//! diffing are shared with the async API as sync helpers; only the RPC calls differ.
+use std::ops::RangeBounds;
+
+use solana_client::rpc_client::RpcClient;
+use solana_client::rpc_config::CommitmentConfig;
+use solana_message::{AddressLookupTableAccount, Hash};
+use solana_sdk::{instruction::Instruction, pubkey::Pubkey, signature::Signature};
+use solana_signer::Signer;
+
+use crate::client::{
+    add_multisigs, decode_account, decode_accounts, decode_address_lookup_tables,
+    empty_proposal_bundles, fill_proposal_bundles, lookup_table_keys, member_slot_filters,
+    multisig_info, oldest_in_page, program_accounts_config, proposal_bundle_keys,
+    proposal_index_bounds, MultisigInfo, MultisigSearch, ProposalBundle, MAX_MULTIPLE_ACCOUNTS,
+};
+use crate::compute_budget::{
+    compute_budget_instructions, prioritization_fee_accounts, recent_fee_percentile,
+    simulated_limit, simulation_config, simulation_transaction, ComputeBudgetConfig,
+    ComputeUnitLimit, ComputeUnitPrice,
+};
+use crate::fortis_client::{
+    approve_instruction, close_instruction, compile_transaction, create_multisig_instruction,
+    execute_instruction, propose_instruction, sendable_instructions, with_advance_nonce,
+    CreatedMultisig, CreatedProposal,
+};
+use crate::lint::{proposal_context, Finding, Linter};
+use crate::lookup_table::{
+    decode_lookup_table, plan_lookup_table, plan_signers, LookupTablePlan, ACTIVATION_POLL_INTERVAL,
+};
+use crate::nonce::{
+    create_nonce_account_instructions, decode_nonce, DurableNonce, NonceInfo, NONCE_ACCOUNT_SIZE,
+};
+use crate::offline::PartiallySignedTransaction;
+use crate::pda::{
+    get_multisig_pda, get_proposal_pda, get_transaction_pda, get_vault_pda, FORTIS_PROGRAM_ID,
+    TREASURY,
+};
+#[cfg(feature = "policy")]
+use crate::policy::{executed_since, vault_spends, Spend, HISTORY_PAGE_SIZE};
+use crate::proposal_plan::{plan_proposals, ProposalPlan};
+use crate::simulation::{
+    vault_simulation_transaction, BalanceDiff, BalanceDiffRequest, VaultSimulation,
+};
+use crate::state::{
+    FortisAccount, Multisig, MultisigCreateArgs, Proposal, VaultTransaction,
+    VaultTransactionMessage,
+};
+use crate::ClientResult;
+
+/// Gets a `Multisig` account from the chain
+pub fn get_multisig(
+    rpc_client: &RpcClient,
+    multisig_key: &Pubkey,
+    program_id: Option<Pubkey>,
+) -> ClientResult<Multisig> {
+    get_fortis_account(rpc_client, multisig_key, program_id)
+}
+pub fn get_transaction(
+    rpc_client: &RpcClient,
+    transaction_key: &Pubkey,
+    program_id: Option<Pubkey>,
+) -> ClientResult<VaultTransaction> {
+    get_fortis_account(rpc_client, transaction_key, program_id)
+}
+pub fn get_proposal(
+    rpc_client: &RpcClient,
+    proposal_key: &Pubkey,
+    program_id: Option<Pubkey>,
+) -> ClientResult<Proposal> {
+    get_fortis_account(rpc_client, proposal_key, program_id)
+}
+fn get_fortis_account<T: FortisAccount>(
+    rpc_client: &RpcClient,
+    key: &Pubkey,
+    program_id: Option<Pubkey>,
+) -> ClientResult<T> {
+    let account = rpc_client.get_account(key)?;
+
+    decode_account(key, &account, &program_id.unwrap_or(FORTIS_PROGRAM_ID))
+}
+/// Gets several `Multisig` accounts from the chain, `None` for accounts that do not exist.
+pub fn get_multisigs(
+    rpc_client: &RpcClient,
+    multisig_keys: &[Pubkey],
+    program_id: Option<Pubkey>,
+) -> ClientResult<Vec<Option<Multisig>>> {
+    get_fortis_accounts(rpc_client, multisig_keys, program_id)
+}
+/// Gets several `Proposal` accounts from the chain, `None` for accounts that do not exist.
+pub fn get_proposals(
+    rpc_client: &RpcClient,
+    proposal_keys: &[Pubkey],
+    program_id: Option<Pubkey>,
+) -> ClientResult<Vec<Option<Proposal>>> {
+    get_fortis_accounts(rpc_client, proposal_keys, program_id)
+}
+/// Gets several `VaultTransaction` accounts from the chain, `None` for accounts that do not exist.
+pub fn get_transactions(
+    rpc_client: &RpcClient,
+    transaction_keys: &[Pubkey],
+    program_id: Option<Pubkey>,
+) -> ClientResult<Vec<Option<VaultTransaction>>> {
+    get_fortis_accounts(rpc_client, transaction_keys, program_id)
+}
+fn get_fortis_accounts<T: FortisAccount>(
+    rpc_client: &RpcClient,
+    keys: &[Pubkey],
+    program_id: Option<Pubkey>,
+) -> ClientResult<Vec<Option<T>>> {
+    let program_id = program_id.unwrap_or(FORTIS_PROGRAM_ID);
+    let mut decoded = Vec::with_capacity(keys.len());
+
+    for chunk in keys.chunks(MAX_MULTIPLE_ACCOUNTS) {
+        let accounts = rpc_client.get_multiple_accounts(chunk)?;
+        decoded.extend(decode_accounts(chunk, accounts, &program_id)?);
+    }
+
+    Ok(decoded)
+}
+/// Builds the `proposal_execute` instruction for the proposal at `transaction_index`,
+/// fetching the transaction account and every address lookup table its message references.
+///
+/// Returns the instruction along with the lookup tables, which the outer transaction
+/// should be compiled with.
+pub fn proposal_execute_auto(
+    rpc_client: &RpcClient,
+    multisig_key: &Pubkey,
+    transaction_index: u64,
+    member: &Pubkey,
+    program_id: Option<Pubkey>,
+) -> ClientResult<(Instruction, Vec<AddressLookupTableAccount>)> {
+    let program_key = program_id.unwrap_or(FORTIS_PROGRAM_ID);
+    let transaction_key =
+        get_transaction_pda(multisig_key, transaction_index, Some(&program_key)).0;
+    let vault_transaction = get_transaction(rpc_client, &transaction_key, program_id)?;
+
+    let address_lookup_table_accounts =
+        get_address_lookup_tables(rpc_client, &vault_transaction.message)?;
+
+    let instruction = execute_instruction(
+        &program_key,
+        multisig_key,
+        member,
+        &vault_transaction,
+        &address_lookup_table_accounts,
+    )?;
+
+    Ok((instruction, address_lookup_table_accounts))
+}
+/// Fetches the address lookup tables referenced by `message`, checking that each one
+/// is still active and holds every index the message uses.
+pub fn get_address_lookup_tables(
+    rpc_client: &RpcClient,
+    message: &VaultTransactionMessage,
+) -> ClientResult<Vec<AddressLookupTableAccount>> {
+    let keys = lookup_table_keys(message);
+
+    let mut accounts = Vec::with_capacity(keys.len());
+    for chunk in keys.chunks(MAX_MULTIPLE_ACCOUNTS) {
+        accounts.extend(rpc_client.get_multiple_accounts(chunk)?);
+    }
+
+    decode_address_lookup_tables(message, accounts)
+}
+/// Plans, creates and activates a lookup table for the accounts of `instructions`
+/// missing from `existing`, then returns `existing` followed by the new table.
+pub fn prepare_lookup_tables(
+    rpc_client: &RpcClient,
+    payer: &dyn Signer,
+    authority: &dyn Signer,
+    vault: &Pubkey,
+    instructions: &[Instruction],
+    existing: &[AddressLookupTableAccount],
+) -> ClientResult<Vec<AddressLookupTableAccount>> {
+    let recent_slot = rpc_client.get_slot_with_commitment(CommitmentConfig::finalized())?;
+    let mut tables = existing.to_vec();
+
+    if let Some(plan) = plan_lookup_table(
+        &authority.pubkey(),
+        &payer.pubkey(),
+        recent_slot,
+        vault,
+        instructions,
+        existing,
+    )? {
+        tables.push(create_lookup_table(rpc_client, &plan, payer, authority)?);
+    }
+
+    Ok(tables)
+}
+/// Sends the create and extend transactions of `plan`, then waits until the
+/// table is active, i.e. the current slot is past its last extension.
+pub fn create_lookup_table(
+    rpc_client: &RpcClient,
+    plan: &LookupTablePlan,
+    payer: &dyn Signer,
+    authority: &dyn Signer,
+) -> ClientResult<AddressLookupTableAccount> {
+    // The table authority only has to sign the extensions.
+    let extend_signers = plan_signers(payer, authority);
+    let transactions = std::iter::once((&plan.create_instruction, vec![payer])).chain(
+        plan.extend_instructions
+            .iter()
+            .map(|ix| (ix, extend_signers.clone())),
+    );
+    for (instruction, signers) in transactions {
+        let blockhash = rpc_client.get_latest_blockhash()?;
+        let transaction = compile_transaction(
+            &payer.pubkey(),
+            std::slice::from_ref(instruction),
+            &signers,
+            &[],
+            blockhash,
+        )?;
+        rpc_client.send_and_confirm_transaction(&transaction)?;
+    }
+
+    let account = rpc_client
+        .get_account_with_commitment(&plan.address, rpc_client.commitment())?
+        .value;
+    let (table, meta) = decode_lookup_table(&plan.address, account)?;
+
+    while rpc_client.get_slot()? <= meta.last_extended_slot {
+        std::thread::sleep(ACTIVATION_POLL_INTERVAL);
+    }
+
+    Ok(table)
+}
+/// Resolves `config` for a transaction paid by `payer` carrying `instructions`,
+/// returning the `ComputeBudget` instructions to prepend to them.
+///
+/// `durable_nonce` is the nonce the transaction advances, if any: its `advance_nonce_account`
+/// instruction is counted for the fees and simulated along, as in the transaction sent.
+pub fn resolve_compute_budget(
+    rpc_client: &RpcClient,
+    config: &ComputeBudgetConfig,
+    payer: &Pubkey,
+    instructions: &[Instruction],
+    address_lookup_table_accounts: &[AddressLookupTableAccount],
+    durable_nonce: Option<&DurableNonce>,
+) -> ClientResult<Vec<Instruction>> {
+    let sent = with_advance_nonce(durable_nonce, instructions);
+    let unit_price = match config.unit_price {
+        None => None,
+        Some(ComputeUnitPrice::Fixed(price)) => Some(price),
+        Some(ComputeUnitPrice::Percentile(percentile)) => {
+            let fees =
+                rpc_client.get_recent_prioritization_fees(&prioritization_fee_accounts(&sent))?;
+            Some(recent_fee_percentile(&fees, percentile))
+        }
+    };
+
+    let unit_limit = match config.unit_limit {
+        None => None,
+        Some(ComputeUnitLimit::Fixed(units)) => Some(units),
+        Some(ComputeUnitLimit::Simulated { margin_percent }) => {
+            let transaction = simulation_transaction(
+                payer,
+                instructions,
+                address_lookup_table_accounts,
+                unit_price,
+                durable_nonce,
+            )?;
+            let result = rpc_client
+                .simulate_transaction_with_config(
+                    &transaction,
+                    simulation_config(rpc_client.commitment()),
+                )?
+                .value;
+            Some(simulated_limit(result, margin_percent)?)
+        }
+    };
+
+    Ok(compute_budget_instructions(unit_limit, unit_price))
+}
+/// Creates a rent-exempt nonce account whose authority is `authority`, paid by `payer`.
+pub fn create_durable_nonce(
+    rpc_client: &RpcClient,
+    payer: &dyn Signer,
+    nonce_account: &dyn Signer,
+    authority: &Pubkey,
+) -> ClientResult<(DurableNonce, Signature)> {
+    let lamports = rpc_client.get_minimum_balance_for_rent_exemption(NONCE_ACCOUNT_SIZE)?;
+    let instructions = create_nonce_account_instructions(
+        &payer.pubkey(),
+        &nonce_account.pubkey(),
+        authority,
+        lamports,
+    );
+
+    let blockhash = rpc_client.get_latest_blockhash()?;
+    let transaction = compile_transaction(
+        &payer.pubkey(),
+        &instructions,
+        &[payer, nonce_account],
+        &[],
+        blockhash,
+    )?;
+    let signature = rpc_client.send_and_confirm_transaction(&transaction)?;
+
+    Ok((
+        DurableNonce {
+            account: nonce_account.pubkey(),
+            authority: *authority,
+        },
+        signature,
+    ))
+}
+/// Fetches the current value of a nonce account.
+pub fn get_nonce(rpc_client: &RpcClient, nonce_account: &Pubkey) -> ClientResult<NonceInfo> {
+    let account = rpc_client
+        .get_account_with_commitment(nonce_account, rpc_client.commitment())?
+        .value;
+    decode_nonce(nonce_account, account)
+}
+/// Simulates `message` as executed by the vault of `multisig_key`, fetching its lookup tables.
+pub fn simulate_vault_message(
+    rpc_client: &RpcClient,
+    multisig_key: &Pubkey,
+    message: &VaultTransactionMessage,
+    program_id: Option<Pubkey>,
+) -> ClientResult<VaultSimulation> {
+    let address_lookup_table_accounts = get_address_lookup_tables(rpc_client, message)?;
+    let transaction = vault_simulation_transaction(
+        multisig_key,
+        message,
+        &address_lookup_table_accounts,
+        program_id,
+    )?;
+
+    let result = rpc_client
+        .simulate_transaction_with_config(&transaction, simulation_config(rpc_client.commitment()))?
+        .value;
+    Ok(VaultSimulation::from_result(result))
+}
+/// Simulates a vault transaction already stored on chain.
+pub fn simulate_vault_transaction(
+    rpc_client: &RpcClient,
+    vault_transaction: &VaultTransaction,
+    program_id: Option<Pubkey>,
+) -> ClientResult<VaultSimulation> {
+    simulate_vault_message(
+        rpc_client,
+        &vault_transaction.multisig,
+        &vault_transaction.message,
+        program_id,
+    )
+}
+/// Fetches the transaction of the proposal at `transaction_index` and simulates it.
+pub fn simulate_proposal(
+    rpc_client: &RpcClient,
+    multisig_key: &Pubkey,
+    transaction_index: u64,
+    program_id: Option<Pubkey>,
+) -> ClientResult<VaultSimulation> {
+    let transaction_key = get_transaction_pda(
+        multisig_key,
+        transaction_index,
+        Some(&program_id.unwrap_or(FORTIS_PROGRAM_ID)),
+    )
+    .0;
+    let vault_transaction = get_transaction(rpc_client, &transaction_key, program_id)?;
+
+    simulate_vault_transaction(rpc_client, &vault_transaction, program_id)
+}
+/// Simulates `message` and reports the balance changes of the vault and of its writable accounts.
+pub fn simulate_balance_diff(
+    rpc_client: &RpcClient,
+    multisig_key: &Pubkey,
+    message: &VaultTransactionMessage,
+    program_id: Option<Pubkey>,
+) -> ClientResult<BalanceDiff> {
+    let address_lookup_table_accounts = get_address_lookup_tables(rpc_client, message)?;
+    let request = BalanceDiffRequest::new(
+        multisig_key,
+        message,
+        &address_lookup_table_accounts,
+        program_id,
+    )?;
+
+    let mut pre_accounts = Vec::with_capacity(request.addresses.len());
+    let mut pre_slot = 0;
+    for chunk in request.addresses.chunks(MAX_MULTIPLE_ACCOUNTS) {
+        let response =
+            rpc_client.get_multiple_accounts_with_commitment(chunk, rpc_client.commitment())?;
+        pre_slot = pre_slot.max(response.context.slot);
+        pre_accounts.extend(response.value);
+    }
+    let response = rpc_client.simulate_transaction_with_config(
+        &request.transaction,
+        request.simulation_config(rpc_client.commitment(), pre_slot),
+    )?;
+    let simulated = request.simulated((pre_slot, pre_accounts), response);
+
+    let mut mint_accounts = Vec::with_capacity(simulated.mints.len());
+    for chunk in simulated.mints.chunks(MAX_MULTIPLE_ACCOUNTS) {
+        mint_accounts.extend(rpc_client.get_multiple_accounts(chunk)?);
+    }
+
+    Ok(simulated.diff(&mint_accounts))
+}
+/// Lints the message of the proposal at `transaction_index`, fetching the multisig,
+/// the transaction, its lookup tables and the vault balance.
+pub fn lint_proposal(
+    rpc_client: &RpcClient,
+    linter: &Linter,
+    multisig_key: &Pubkey,
+    transaction_index: u64,
+    program_id: Option<Pubkey>,
+) -> ClientResult<Vec<Finding>> {
+    let multisig = get_multisig(rpc_client, multisig_key, program_id)?;
+    let transaction_key = get_transaction_pda(
+        multisig_key,
+        transaction_index,
+        Some(&program_id.unwrap_or(FORTIS_PROGRAM_ID)),
+    )
+    .0;
+    let vault_transaction = get_transaction(rpc_client, &transaction_key, program_id)?;
+    let address_lookup_table_accounts =
+        get_address_lookup_tables(rpc_client, &vault_transaction.message)?;
+
+    let context = proposal_context(multisig_key, &multisig, &vault_transaction, program_id);
+    let vault_lamports = rpc_client
+        .get_account_with_commitment(&context.vault, rpc_client.commitment())?
+        .value
+        .map_or(0, |account| account.lamports);
+    let rent_exempt_minimum = rpc_client.get_minimum_balance_for_rent_exemption(0)?;
+
+    linter.lint(
+        &vault_transaction.message,
+        &address_lookup_table_accounts,
+        &context.with_vault_balance(vault_lamports, rent_exempt_minimum),
+    )
+}
+/// Spends of the proposals executed since `since` (unix timestamp), newest first.
+///
+/// This is a best-effort reconstruction: closing executed proposals to reclaim their rent
+/// erases them from it, and the last update of a proposal stands for its execution time.
+/// Keep a record of the spends at execution when the daily caps must hold.
+///
+/// Proposals are scanned from the newest until a page holds none updated since `since`.
+#[cfg(feature = "policy")]
+pub fn spending_history(
+    rpc_client: &RpcClient,
+    multisig_key: &Pubkey,
+    since: i64,
+    program_id: Option<Pubkey>,
+) -> ClientResult<Vec<Spend>> {
+    use crate::explain::Explainer;
+
+    let vault = get_vault_pda(multisig_key, Some(&program_id.unwrap_or(FORTIS_PROGRAM_ID))).0;
+    let explainer = Explainer::new();
+    let mut spends = Vec::new();
+
+    for page in list_proposals_pages(rpc_client, multisig_key, HISTORY_PAGE_SIZE, program_id) {
+        let Some(executed) = executed_since(page?, since) else {
+            break;
+        };
+        for transaction in executed {
+            let address_lookup_table_accounts =
+                get_address_lookup_tables(rpc_client, &transaction.message)?;
+            let instructions =
+                explainer.explain(&transaction.message, &address_lookup_table_accounts)?;
+            spends.extend(vault_spends(&vault, &instructions));
+        }
+    }
+    Ok(spends)
+}
+/// Lists the proposals of a multisig whose transaction index falls within `range`, oldest first.
+///
+/// The range is clamped to `1..=multisig.transaction_index`.
+pub fn list_proposals(
+    rpc_client: &RpcClient,
+    multisig_key: &Pubkey,
+    range: impl RangeBounds<u64>,
+    program_id: Option<Pubkey>,
+) -> ClientResult<Vec<ProposalBundle>> {
+    let multisig = get_multisig(rpc_client, multisig_key, program_id)?;
+
+    match proposal_index_bounds(&range, multisig.transaction_index) {
+        Some((first, last)) => {
+            get_proposal_bundles(rpc_client, multisig_key, first, last, program_id)
+        }
+        None => Ok(Vec::new()),
+    }
+}
+/// Iterates over the proposals of a multisig in pages of `page_size`, newest first.
+pub fn list_proposals_pages<'a>(
+    rpc_client: &'a RpcClient,
+    multisig_key: &'a Pubkey,
+    page_size: usize,
+    program_id: Option<Pubkey>,
+) -> impl Iterator<Item = ClientResult<Vec<ProposalBundle>>> + 'a {
+    // The newest index not yet yielded, `None` until the multisig is fetched.
+    let mut newest: Option<u64> = None;
+
+    std::iter::from_fn(move || {
+        let current = match newest {
+            Some(current) => current,
+            None => match get_multisig(rpc_client, multisig_key, program_id) {
+                Ok(multisig) => multisig.transaction_index,
+                Err(err) => {
+                    newest = Some(0);
+                    return Some(Err(err));
+                }
+            },
+        };
+        if current == 0 {
+            return None;
+        }
+
+        let oldest = oldest_in_page(current, page_size);
+        newest = Some(oldest - 1);
+        Some(
+            get_proposal_bundles(rpc_client, multisig_key, oldest, current, program_id).map(
+                |mut page| {
+                    page.reverse();
+                    page
+                },
+            ),
+        )
+    })
+}
+fn get_proposal_bundles(
+    rpc_client: &RpcClient,
+    multisig_key: &Pubkey,
+    first: u64,
+    last: u64,
+    program_id: Option<Pubkey>,
+) -> ClientResult<Vec<ProposalBundle>> {
+    let bundles = empty_proposal_bundles(
+        multisig_key,
+        first,
+        last,
+        &program_id.unwrap_or(FORTIS_PROGRAM_ID),
+    );
+    let (proposal_keys, transaction_keys) = proposal_bundle_keys(&bundles);
+
+    let proposals = get_proposals(rpc_client, &proposal_keys, program_id)?;
+    let transactions = get_transactions(rpc_client, &transaction_keys, program_id)?;
+
+    Ok(fill_proposal_bundles(bundles, proposals, transactions))
+}
+/// Finds every multisig `member` belongs to among its first `max_members` members.
+pub fn find_multisigs_for_member(
+    rpc_client: &RpcClient,
+    member: &Pubkey,
+    max_members: usize,
+    program_id: Option<Pubkey>,
+) -> ClientResult<MultisigSearch> {
+    let program_id = program_id.unwrap_or(FORTIS_PROGRAM_ID);
+
+    let mut search = MultisigSearch::default();
+    for slot in 0..max_members {
+        let accounts = rpc_client.get_program_ui_accounts_with_config(
+            &program_id,
+            program_accounts_config(member_slot_filters(member, slot)),
+        )?;
+        add_multisigs(&mut search, accounts, member, &program_id)?;
+    }
+    Ok(search)
+}
+/// Gets the multisig seeded by `create_key`, `None` if it does not exist.
+pub fn find_multisigs_by_create_key(
+    rpc_client: &RpcClient,
+    create_key: &Pubkey,
+    program_id: Option<Pubkey>,
+) -> ClientResult<Option<MultisigInfo>> {
+    let program_id = program_id.unwrap_or(FORTIS_PROGRAM_ID);
+    let address = get_multisig_pda(create_key, Some(&program_id)).0;
+    let multisig = get_multisigs(rpc_client, &[address], Some(program_id))?;
+
+    Ok(multisig
+        .into_iter()
+        .flatten()
+        .next()
+        .map(|multisig| multisig_info(address, multisig, &program_id)))
+}
+
+/// Blocking counterpart of `crate::FortisClient`.
+pub struct FortisClient {
+    rpc_client: RpcClient,
+    program_id: Pubkey,
+    treasury: Pubkey,
+    commitment: CommitmentConfig,
+    durable_nonce: Option<DurableNonce>,
+}
+
+impl FortisClient {
+    /// Creates a client for `rpc_url` using the default program id and treasury.
+    pub fn new(rpc_url: String, commitment: CommitmentConfig) -> Self {
+        Self::from_rpc_client(RpcClient::new_with_commitment(rpc_url, commitment))
+    }
+
+    /// Creates a client on top of an existing `RpcClient`, using its commitment.
+    pub fn from_rpc_client(rpc_client: RpcClient) -> Self {
+        let commitment = rpc_client.commitment();
+        Self {
+            rpc_client,
+            program_id: FORTIS_PROGRAM_ID,
+            treasury: TREASURY,
+            commitment,
+            durable_nonce: None,
+        }
+    }
+
+    pub fn with_program_id(mut self, program_id: Pubkey) -> Self {
+        self.program_id = program_id;
+        self
+    }
+
+    /// Makes every transaction built by this client use `durable_nonce` instead of a recent
+    /// blockhash. Its authority must be among the signers of each transaction sent.
+    pub fn with_durable_nonce(mut self, durable_nonce: DurableNonce) -> Self {
+        self.durable_nonce = Some(durable_nonce);
+        self
+    }
+
+    pub fn with_treasury(mut self, treasury: Pubkey) -> Self {
+        self.treasury = treasury;
+        self
+    }
+
+    pub fn rpc_client(&self) -> &RpcClient {
+        &self.rpc_client
+    }
+
+    pub fn program_id(&self) -> Pubkey {
+        self.program_id
+    }
+
+    pub fn treasury(&self) -> Pubkey {
+        self.treasury
+    }
+
+    pub fn commitment(&self) -> CommitmentConfig {
+        self.commitment
+    }
+
+    pub fn durable_nonce(&self) -> Option<DurableNonce> {
+        self.durable_nonce
+    }
+
+    pub fn get_multisig(&self, multisig_key: &Pubkey) -> ClientResult<Multisig> {
+        get_multisig(&self.rpc_client, multisig_key, Some(self.program_id))
+    }
+
+    pub fn get_proposal(
+        &self,
+        multisig_key: &Pubkey,
+        transaction_index: u64,
+    ) -> ClientResult<Proposal> {
+        let proposal_key =
+            get_proposal_pda(multisig_key, transaction_index, Some(&self.program_id)).0;
+        get_proposal(&self.rpc_client, &proposal_key, Some(self.program_id))
+    }
+
+    pub fn get_transaction(
+        &self,
+        multisig_key: &Pubkey,
+        transaction_index: u64,
+    ) -> ClientResult<VaultTransaction> {
+        let transaction_key =
+            get_transaction_pda(multisig_key, transaction_index, Some(&self.program_id)).0;
+        get_transaction(&self.rpc_client, &transaction_key, Some(self.program_id))
+    }
+
+    /// Simulates the transaction of the proposal at `transaction_index` as the vault would run it.
+    pub fn simulate_proposal(
+        &self,
+        multisig_key: &Pubkey,
+        transaction_index: u64,
+    ) -> ClientResult<VaultSimulation> {
+        simulate_proposal(
+            &self.rpc_client,
+            multisig_key,
+            transaction_index,
+            Some(self.program_id),
+        )
+    }
+
+    /// Creates a new multisig seeded by `create_key`, paid for by `creator`.
+    pub fn create_multisig(
+        &self,
+        creator: &dyn Signer,
+        create_key: &dyn Signer,
+        args: MultisigCreateArgs,
+    ) -> ClientResult<CreatedMultisig> {
+        let (instruction, multisig, vault) = create_multisig_instruction(
+            &self.program_id,
+            &self.treasury,
+            &creator.pubkey(),
+            &create_key.pubkey(),
+            args,
+        );
+
+        let signature = self.send(creator, &[instruction], &[creator, create_key], &[])?;
+
+        Ok(CreatedMultisig {
+            signature,
+            multisig,
+            vault,
+        })
+    }
+
+    /// Proposes `instructions` to be executed by the multisig vault at the next transaction index.
+    ///
+    /// The instructions may reference the first `num_ephemeral_signers` ephemeral signers of the
+    /// transaction, see `pda::ephemeral_signers`.
+    pub fn propose(
+        &self,
+        multisig_key: &Pubkey,
+        creator: &dyn Signer,
+        instructions: &[Instruction],
+        address_lookup_table_accounts: &[AddressLookupTableAccount],
+        num_ephemeral_signers: u8,
+        voting_deadline: i64,
+    ) -> ClientResult<CreatedProposal> {
+        let multisig = self.get_multisig(multisig_key)?;
+        let transaction_index = multisig.transaction_index + 1;
+
+        let (instruction, proposal, transaction) = propose_instruction(
+            &self.program_id,
+            multisig_key,
+            transaction_index,
+            &creator.pubkey(),
+            instructions,
+            address_lookup_table_accounts,
+            num_ephemeral_signers,
+            voting_deadline,
+        )?;
+
+        let signature = self.send(creator, &[instruction], &[creator], &[])?;
+
+        Ok(CreatedProposal {
+            signature,
+            transaction_index,
+            proposal,
+            transaction,
+        })
+    }
+
+    /// Splits `instructions` into proposals that fit in a packet, starting at the next
+    /// transaction index. See `proposal_plan::plan_proposals`.
+    pub fn plan_proposals(
+        &self,
+        multisig_key: &Pubkey,
+        creator: &Pubkey,
+        instructions: Vec<Instruction>,
+        address_lookup_table_accounts: &[AddressLookupTableAccount],
+    ) -> ClientResult<Vec<ProposalPlan>> {
+        let multisig = self.get_multisig(multisig_key)?;
+
+        plan_proposals(
+            multisig_key,
+            creator,
+            multisig.transaction_index + 1,
+            instructions,
+            address_lookup_table_accounts,
+            Some(self.program_id),
+        )
+    }
+
+    /// Proposes one chunk of `plan_proposals`. Plans must be proposed in order.
+    pub fn propose_plan(
+        &self,
+        multisig_key: &Pubkey,
+        creator: &dyn Signer,
+        plan: &ProposalPlan,
+        voting_deadline: i64,
+    ) -> ClientResult<Signature> {
+        let instruction = plan.proposal_create(
+            multisig_key,
+            &creator.pubkey(),
+            voting_deadline,
+            Some(self.program_id),
+        )?;
+
+        self.send(creator, &[instruction], &[creator], &[])
+    }
+
+    /// Approves the proposal at `transaction_index` on behalf of `member`.
+    pub fn approve(
+        &self,
+        multisig_key: &Pubkey,
+        transaction_index: u64,
+        member: &dyn Signer,
+    ) -> ClientResult<Signature> {
+        let instruction = approve_instruction(
+            &self.program_id,
+            multisig_key,
+            transaction_index,
+            &member.pubkey(),
+        );
+
+        self.send(member, &[instruction], &[member], &[])
+    }
+
+    /// Executes the approved proposal at `transaction_index` on behalf of `member`.
+    ///
+    /// The address lookup tables referenced by the stored message are fetched automatically.
+    pub fn execute(
+        &self,
+        multisig_key: &Pubkey,
+        transaction_index: u64,
+        member: &dyn Signer,
+    ) -> ClientResult<Signature> {
+        self.execute_with_compute_budget(
+            multisig_key,
+            transaction_index,
+            member,
+            &ComputeBudgetConfig::default(),
+        )
+    }
+
+    /// Like `execute`, prepending the `ComputeBudget` instructions resolved from `compute_budget`.
+    ///
+    /// Percentile prices are computed from the fees paid on the writable accounts of the execution.
+    pub fn execute_with_compute_budget(
+        &self,
+        multisig_key: &Pubkey,
+        transaction_index: u64,
+        member: &dyn Signer,
+        compute_budget: &ComputeBudgetConfig,
+    ) -> ClientResult<Signature> {
+        let vault_transaction = self.get_transaction(multisig_key, transaction_index)?;
+        let address_lookup_table_accounts =
+            get_address_lookup_tables(&self.rpc_client, &vault_transaction.message)?;
+
+        let instruction = execute_instruction(
+            &self.program_id,
+            multisig_key,
+            &member.pubkey(),
+            &vault_transaction,
+            &address_lookup_table_accounts,
+        )?;
+
+        let mut instructions = resolve_compute_budget(
+            &self.rpc_client,
+            compute_budget,
+            &member.pubkey(),
+            std::slice::from_ref(&instruction),
+            &address_lookup_table_accounts,
+            self.durable_nonce.as_ref(),
+        )?;
+        instructions.push(instruction);
+
+        self.send(
+            member,
+            &instructions,
+            &[member],
+            &address_lookup_table_accounts,
+        )
+    }
+
+    /// Closes the proposal and transaction accounts at `transaction_index`,
+    /// returning their rent to `rent_collector`.
+    pub fn close(
+        &self,
+        multisig_key: &Pubkey,
+        transaction_index: u64,
+        rent_collector: &dyn Signer,
+    ) -> ClientResult<Signature> {
+        let instruction = close_instruction(
+            &self.program_id,
+            multisig_key,
+            transaction_index,
+            &rent_collector.pubkey(),
+        );
+
+        self.send(rent_collector, &[instruction], &[rent_collector], &[])
+    }
+
+    /// Builds an unsigned transaction paid by `payer` for offline signing, on the latest
+    /// blockhash or on the durable nonce of the client.
+    pub fn prepare(
+        &self,
+        payer: &Pubkey,
+        instructions: &[Instruction],
+        address_lookup_table_accounts: &[AddressLookupTableAccount],
+    ) -> ClientResult<PartiallySignedTransaction> {
+        let instructions = with_advance_nonce(self.durable_nonce.as_ref(), instructions);
+        let blockhash = self.blockhash()?;
+        PartiallySignedTransaction::new(
+            payer,
+            &instructions,
+            address_lookup_table_accounts,
+            blockhash,
+        )
+    }
+
+    /// Offline counterpart of `create_multisig`, returning the unsigned transaction with the
+    /// multisig and vault PDAs. Both `creator` and `create_key` must sign it.
+    pub fn prepare_create_multisig(
+        &self,
+        creator: &Pubkey,
+        create_key: &Pubkey,
+        args: MultisigCreateArgs,
+    ) -> ClientResult<(PartiallySignedTransaction, Pubkey, Pubkey)> {
+        let (instruction, multisig, vault) = create_multisig_instruction(
+            &self.program_id,
+            &self.treasury,
+            creator,
+            create_key,
+            args,
+        );
+
+        let transaction = self.prepare(creator, &[instruction], &[])?;
+        Ok((transaction, multisig, vault))
+    }
+
+    /// Offline counterpart of `propose`, returning the unsigned transaction and its transaction index.
+    pub fn prepare_propose(
+        &self,
+        multisig_key: &Pubkey,
+        creator: &Pubkey,
+        instructions: &[Instruction],
+        address_lookup_table_accounts: &[AddressLookupTableAccount],
+        num_ephemeral_signers: u8,
+        voting_deadline: i64,
+    ) -> ClientResult<(PartiallySignedTransaction, u64)> {
+        let multisig = self.get_multisig(multisig_key)?;
+        let transaction_index = multisig.transaction_index + 1;
+
+        let (instruction, _, _) = propose_instruction(
+            &self.program_id,
+            multisig_key,
+            transaction_index,
+            creator,
+            instructions,
+            address_lookup_table_accounts,
+            num_ephemeral_signers,
+            voting_deadline,
+        )?;
+
+        let transaction = self.prepare(creator, &[instruction], &[])?;
+        Ok((transaction, transaction_index))
+    }
+
+    /// Offline counterpart of `approve`.
+    pub fn prepare_approve(
+        &self,
+        multisig_key: &Pubkey,
+        transaction_index: u64,
+        member: &Pubkey,
+    ) -> ClientResult<PartiallySignedTransaction> {
+        let instruction =
+            approve_instruction(&self.program_id, multisig_key, transaction_index, member);
+
+        self.prepare(member, &[instruction], &[])
+    }
+
+    /// Offline counterpart of `execute`.
+    pub fn prepare_execute(
+        &self,
+        multisig_key: &Pubkey,
+        transaction_index: u64,
+        member: &Pubkey,
+    ) -> ClientResult<PartiallySignedTransaction> {
+        let vault_transaction = self.get_transaction(multisig_key, transaction_index)?;
+        let address_lookup_table_accounts =
+            get_address_lookup_tables(&self.rpc_client, &vault_transaction.message)?;
+
+        let instruction = execute_instruction(
+            &self.program_id,
+            multisig_key,
+            member,
+            &vault_transaction,
+            &address_lookup_table_accounts,
+        )?;
+
+        self.prepare(member, &[instruction], &address_lookup_table_accounts)
+    }
+
+    /// Offline counterpart of `close`.
+    pub fn prepare_close(
+        &self,
+        multisig_key: &Pubkey,
+        transaction_index: u64,
+        rent_collector: &Pubkey,
+    ) -> ClientResult<PartiallySignedTransaction> {
+        let instruction = close_instruction(
+            &self.program_id,
+            multisig_key,
+            transaction_index,
+            rent_collector,
+        );
+
+        self.prepare(rent_collector, &[instruction], &[])
+    }
+
+    /// Sends a transaction signed offline once every required signature is present.
+    pub fn submit(&self, transaction: PartiallySignedTransaction) -> ClientResult<Signature> {
+        let transaction = transaction.into_transaction()?;
+
+        Ok(self.rpc_client.send_and_confirm_transaction(&transaction)?)
+    }
+
+    /// The current nonce when a durable nonce is set, the latest blockhash otherwise.
+    fn blockhash(&self) -> ClientResult<Hash> {
+        match &self.durable_nonce {
+            Some(durable_nonce) => {
+                let info = get_nonce(&self.rpc_client, &durable_nonce.account)?;
+                durable_nonce.blockhash(&info)
+            }
+            None => Ok(self.rpc_client.get_latest_blockhash()?),
+        }
+    }
+
+    /// Signs `instructions` with a fresh blockhash or the durable nonce, sends them and waits for confirmation.
+    ///
+    /// Fails with `TransactionTooLarge` without sending when the transaction exceeds the packet size.
+    fn send(
+        &self,
+        payer: &dyn Signer,
+        instructions: &[Instruction],
+        signers: &[&dyn Signer],
+        address_lookup_table_accounts: &[AddressLookupTableAccount],
+    ) -> ClientResult<Signature> {
+        let instructions = sendable_instructions(
+            &payer.pubkey(),
+            self.durable_nonce.as_ref(),
+            instructions,
+            address_lookup_table_accounts,
+        )?;
+
+        let blockhash = self.blockhash()?;
+        let transaction = compile_transaction(
+            &payer.pubkey(),
+            &instructions,
+            signers,
+            address_lookup_table_accounts,
+            blockhash,
+        )?;
+
+        Ok(self.rpc_client.send_and_confirm_transaction(&transaction)?)
+    }
+}
@@ -1,56 +1,450 @@
+use std::ops::{Bound, RangeBounds};
 use std::vec;
 
+use futures::Stream;
 use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_client::rpc_config::{
+    RpcAccountInfoConfig, RpcProgramAccountsConfig, UiAccountEncoding,
+};
+use solana_client::rpc_filter::{Memcmp, RpcFilterType};
+use solana_client::rpc_response::UiAccount;
 
 use crate::state::{
-    Error, Multisig, MultisigCreateAccounts, MultisigCreateArgs, Proposal,
-    ProposalAccountsCloseAccounts, ProposalApproveAccounts, ProposalApproveArgs,
-    ProposalCreateAccounts, ProposalCreateArgs, ProposalExecuteAccounts, VaultTransaction,
-    VaultTransactionMessage,
+    AccountType, Error, FortisAccount, Multisig, MultisigCreateAccounts, MultisigCreateArgs,
+    Proposal, ProposalAccountsCloseAccounts, ProposalApproveAccounts, ProposalApproveArgs,
+    ProposalCreateAccounts, ProposalCreateArgs, ProposalExecuteAccounts, ProposallExecuteArgs,
+    VaultTransaction, VaultTransactionMessage,
 };
 use solana_message::AddressLookupTableAccount;
 
 use crate::error::ClientError;
-use crate::pda::{get_vault_pda, FORTIS_PROGRAM_ID};
+use crate::fortis_client::execute_instruction;
+use crate::instruction::FortisInstruction;
+use crate::lookup_table::decode_lookup_table;
+use crate::pda::{
+    ephemeral_signers, get_ephemeral_signer_pda, get_multisig_pda, get_proposal_pda,
+    get_transaction_pda, get_vault_pda, FORTIS_PROGRAM_ID,
+};
 use crate::ClientResult;
-use solana_sdk::{instruction::Instruction, pubkey::Pubkey};
+use solana_sdk::{account::Account, instruction::Instruction, pubkey::Pubkey};
 pub const MULTISIG_HEADER_SIZE: usize = 75;
 pub const PROPOSAL_HEADER_SIZE: usize = 58;
+pub const VAULT_TRANSACTION_HEADER_SIZE: usize = 74;
+/// Maximum number of keys accepted by a single `getMultipleAccounts` request.
+pub const MAX_MULTIPLE_ACCOUNTS: usize = 100;
 /// Gets a `Multisig` account from the chain
-pub async fn get_multisig(rpc_client: &RpcClient, multisig_key: &Pubkey) -> ClientResult<Multisig> {
+pub async fn get_multisig(
+    rpc_client: &RpcClient,
+    multisig_key: &Pubkey,
+    program_id: Option<Pubkey>,
+) -> ClientResult<Multisig> {
     let multisig_account = rpc_client.get_account(multisig_key).await?;
-    let data = multisig_account.data.as_slice();
-
-    let multisig: Multisig =
-        borsh::from_slice(&data).map_err(|_| ClientError::DeserializationError)?;
 
-    Ok(multisig)
+    decode_account(
+        multisig_key,
+        &multisig_account,
+        &program_id.unwrap_or(FORTIS_PROGRAM_ID),
+    )
 }
 pub async fn get_transaction(
     rpc_client: &RpcClient,
     transaction_key: &Pubkey,
+    program_id: Option<Pubkey>,
 ) -> ClientResult<VaultTransaction> {
     let tx_account = rpc_client.get_account(transaction_key).await?;
-    let data = tx_account.data.as_slice();
-
-    let tx: VaultTransaction =
-        borsh::from_slice(&data).map_err(|_| ClientError::DeserializationError)?;
 
-    Ok(tx)
+    decode_account(
+        transaction_key,
+        &tx_account,
+        &program_id.unwrap_or(FORTIS_PROGRAM_ID),
+    )
 }
-pub async fn get_proposal(rpc_client: &RpcClient, proposal_key: &Pubkey) -> ClientResult<Proposal> {
+pub async fn get_proposal(
+    rpc_client: &RpcClient,
+    proposal_key: &Pubkey,
+    program_id: Option<Pubkey>,
+) -> ClientResult<Proposal> {
     let proposal_account = rpc_client.get_account(proposal_key).await?;
-    let data = proposal_account.data.as_slice();
-    let approvers_len = u32::from_le_bytes(
-        data[PROPOSAL_HEADER_SIZE..PROPOSAL_HEADER_SIZE + 4]
-            .try_into()
-            .expect("slice length should be 4"),
+
+    decode_account(
+        proposal_key,
+        &proposal_account,
+        &program_id.unwrap_or(FORTIS_PROGRAM_ID),
+    )
+}
+/// Decodes a fetched Fortis account after checking that it is owned by `program_id`
+/// and that `address` is the PDA the decoded account must live at.
+///
+/// Another Fortis account type at `address` fails with `WrongAccountType`, whether or not
+/// its data happens to decode as a `T`; other undecodable data keeps its decode error.
+pub fn decode_account<T: FortisAccount>(
+    address: &Pubkey,
+    account: &Account,
+    program_id: &Pubkey,
+) -> ClientResult<T> {
+    if account.owner != *program_id {
+        return Err(ClientError::WrongAccountOwner {
+            address: *address,
+            expected: *program_id,
+            actual: account.owner,
+        });
+    }
+
+    let decoded = T::try_from_account_data(&account.data).map_err(|err| {
+        match account_type_at(address, &account.data, program_id) {
+            Some(actual) if actual != T::ACCOUNT_TYPE => ClientError::WrongAccountType {
+                address: *address,
+                expected: T::ACCOUNT_TYPE,
+            },
+            _ => err,
+        }
+    })?;
+    if decoded.derive_address(program_id).as_ref() != Some(address) {
+        return Err(ClientError::WrongAccountType {
+            address: *address,
+            expected: T::ACCOUNT_TYPE,
+        });
+    }
+
+    Ok(decoded)
+}
+/// The type of the Fortis account `data` holds, if it decodes as one living at `address`.
+fn account_type_at(address: &Pubkey, data: &[u8], program_id: &Pubkey) -> Option<AccountType> {
+    fn lives_at<T: FortisAccount>(address: &Pubkey, data: &[u8], program_id: &Pubkey) -> bool {
+        T::try_from_account_data(data)
+            .is_ok_and(|account| account.derive_address(program_id).as_ref() == Some(address))
+    }
+
+    if lives_at::<Multisig>(address, data, program_id) {
+        Some(AccountType::Multisig)
+    } else if lives_at::<Proposal>(address, data, program_id) {
+        Some(AccountType::Proposal)
+    } else if lives_at::<VaultTransaction>(address, data, program_id) {
+        Some(AccountType::VaultTransaction)
+    } else {
+        None
+    }
+}
+/// Gets several `Multisig` accounts from the chain, `None` for accounts that do not exist.
+pub async fn get_multisigs(
+    rpc_client: &RpcClient,
+    multisig_keys: &[Pubkey],
+    program_id: Option<Pubkey>,
+) -> ClientResult<Vec<Option<Multisig>>> {
+    get_fortis_accounts(rpc_client, multisig_keys, program_id).await
+}
+/// Gets several `Proposal` accounts from the chain, `None` for accounts that do not exist.
+pub async fn get_proposals(
+    rpc_client: &RpcClient,
+    proposal_keys: &[Pubkey],
+    program_id: Option<Pubkey>,
+) -> ClientResult<Vec<Option<Proposal>>> {
+    get_fortis_accounts(rpc_client, proposal_keys, program_id).await
+}
+/// Gets several `VaultTransaction` accounts from the chain, `None` for accounts that do not exist.
+pub async fn get_transactions(
+    rpc_client: &RpcClient,
+    transaction_keys: &[Pubkey],
+    program_id: Option<Pubkey>,
+) -> ClientResult<Vec<Option<VaultTransaction>>> {
+    get_fortis_accounts(rpc_client, transaction_keys, program_id).await
+}
+/// Fetches `keys` in chunks of `MAX_MULTIPLE_ACCOUNTS` and decodes every existing account.
+/// The result is aligned with `keys`.
+async fn get_fortis_accounts<T: FortisAccount>(
+    rpc_client: &RpcClient,
+    keys: &[Pubkey],
+    program_id: Option<Pubkey>,
+) -> ClientResult<Vec<Option<T>>> {
+    let program_id = program_id.unwrap_or(FORTIS_PROGRAM_ID);
+    let mut decoded = Vec::with_capacity(keys.len());
+
+    for chunk in keys.chunks(MAX_MULTIPLE_ACCOUNTS) {
+        let accounts = rpc_client.get_multiple_accounts(chunk).await?;
+        decoded.extend(decode_accounts(chunk, accounts, &program_id)?);
+    }
+
+    Ok(decoded)
+}
+/// Decodes the result of a `getMultipleAccounts` request for `keys`.
+pub(crate) fn decode_accounts<T: FortisAccount>(
+    keys: &[Pubkey],
+    accounts: Vec<Option<Account>>,
+    program_id: &Pubkey,
+) -> ClientResult<Vec<Option<T>>> {
+    keys.iter()
+        .zip(accounts)
+        .map(|(key, account)| {
+            account
+                .map(|account| decode_account(key, &account, program_id))
+                .transpose()
+        })
+        .collect()
+}
+/// The proposal and transaction accounts stored at one transaction index of a multisig.
+pub struct ProposalBundle {
+    /// Transaction index within the multisig.
+    pub index: u64,
+    pub proposal_key: Pubkey,
+    pub transaction_key: Pubkey,
+    /// `None` if the proposal account was closed.
+    pub proposal: Option<Proposal>,
+    /// `None` if the transaction account was closed.
+    pub transaction: Option<VaultTransaction>,
+}
+impl ProposalBundle {
+    /// Returns true if the accounts at this index were already closed.
+    pub fn is_closed(&self) -> bool {
+        self.proposal.is_none() && self.transaction.is_none()
+    }
+}
+/// Lists the proposals of a multisig whose transaction index falls within `range`, oldest first.
+///
+/// The range is clamped to `1..=multisig.transaction_index`.
+pub async fn list_proposals(
+    rpc_client: &RpcClient,
+    multisig_key: &Pubkey,
+    range: impl RangeBounds<u64>,
+    program_id: Option<Pubkey>,
+) -> ClientResult<Vec<ProposalBundle>> {
+    let multisig = get_multisig(rpc_client, multisig_key, program_id).await?;
+
+    match proposal_index_bounds(&range, multisig.transaction_index) {
+        Some((first, last)) => {
+            get_proposal_bundles(rpc_client, multisig_key, first, last, program_id).await
+        }
+        None => Ok(Vec::new()),
+    }
+}
+/// Streams the proposals of a multisig in pages of `page_size`, newest first.
+pub fn list_proposals_stream<'a>(
+    rpc_client: &'a RpcClient,
+    multisig_key: &'a Pubkey,
+    page_size: usize,
+    program_id: Option<Pubkey>,
+) -> impl Stream<Item = ClientResult<Vec<ProposalBundle>>> + 'a {
+    // The state is the newest index not yet yielded, `None` until the multisig is fetched.
+    futures::stream::try_unfold(None, move |newest: Option<u64>| async move {
+        let newest = match newest {
+            Some(newest) => newest,
+            None => {
+                get_multisig(rpc_client, multisig_key, program_id)
+                    .await?
+                    .transaction_index
+            }
+        };
+        if newest == 0 {
+            return Ok(None);
+        }
+
+        let oldest = oldest_in_page(newest, page_size);
+        let mut page =
+            get_proposal_bundles(rpc_client, multisig_key, oldest, newest, program_id).await?;
+        page.reverse();
+
+        Ok(Some((page, Some(oldest - 1))))
+    })
+}
+/// Fetches the proposal and transaction accounts for indices `first..=last`.
+async fn get_proposal_bundles(
+    rpc_client: &RpcClient,
+    multisig_key: &Pubkey,
+    first: u64,
+    last: u64,
+    program_id: Option<Pubkey>,
+) -> ClientResult<Vec<ProposalBundle>> {
+    let bundles = empty_proposal_bundles(
+        multisig_key,
+        first,
+        last,
+        &program_id.unwrap_or(FORTIS_PROGRAM_ID),
     );
-    let proposal: Proposal =
-        borsh::from_slice(&data[..(PROPOSAL_HEADER_SIZE + 4 + (approvers_len as usize * 32))])
-            .map_err(|_| ClientError::DeserializationError)?;
+    let (proposal_keys, transaction_keys) = proposal_bundle_keys(&bundles);
+
+    let proposals = get_proposals(rpc_client, &proposal_keys, program_id).await?;
+    let transactions = get_transactions(rpc_client, &transaction_keys, program_id).await?;
+
+    Ok(fill_proposal_bundles(bundles, proposals, transactions))
+}
+/// Clamps `range` to the existing transaction indices `1..=transaction_index`.
+/// Returns `None` if nothing is left.
+pub(crate) fn proposal_index_bounds(
+    range: &impl RangeBounds<u64>,
+    transaction_index: u64,
+) -> Option<(u64, u64)> {
+    let first = match range.start_bound() {
+        Bound::Included(&start) => start,
+        Bound::Excluded(&start) => start.saturating_add(1),
+        Bound::Unbounded => 1,
+    }
+    .max(1);
+    let last = match range.end_bound() {
+        Bound::Included(&end) => end,
+        Bound::Excluded(&end) => end.saturating_sub(1),
+        Bound::Unbounded => transaction_index,
+    }
+    .min(transaction_index);
+
+    (first <= last).then_some((first, last))
+}
+/// Returns the oldest index of the page of `page_size` proposals ending at `newest`.
+pub(crate) fn oldest_in_page(newest: u64, page_size: usize) -> u64 {
+    let page_size = page_size.max(1) as u64;
+    newest.saturating_sub(page_size - 1).max(1)
+}
+/// Derives the proposal and transaction PDAs for indices `first..=last`, with no accounts yet.
+pub(crate) fn empty_proposal_bundles(
+    multisig_key: &Pubkey,
+    first: u64,
+    last: u64,
+    program_id: &Pubkey,
+) -> Vec<ProposalBundle> {
+    (first..=last)
+        .map(|index| ProposalBundle {
+            index,
+            proposal_key: get_proposal_pda(multisig_key, index, Some(program_id)).0,
+            transaction_key: get_transaction_pda(multisig_key, index, Some(program_id)).0,
+            proposal: None,
+            transaction: None,
+        })
+        .collect()
+}
+/// The proposal keys and the transaction keys of `bundles`, in order.
+pub(crate) fn proposal_bundle_keys(bundles: &[ProposalBundle]) -> (Vec<Pubkey>, Vec<Pubkey>) {
+    bundles
+        .iter()
+        .map(|bundle| (bundle.proposal_key, bundle.transaction_key))
+        .unzip()
+}
+/// Sets the accounts fetched for the keys of `proposal_bundle_keys`.
+pub(crate) fn fill_proposal_bundles(
+    mut bundles: Vec<ProposalBundle>,
+    proposals: Vec<Option<Proposal>>,
+    transactions: Vec<Option<VaultTransaction>>,
+) -> Vec<ProposalBundle> {
+    for (bundle, (proposal, transaction)) in bundles
+        .iter_mut()
+        .zip(proposals.into_iter().zip(transactions))
+    {
+        bundle.proposal = proposal;
+        bundle.transaction = transaction;
+    }
+    bundles
+}
+/// A `Multisig` found on chain along with its address and vault PDA.
+pub struct MultisigInfo {
+    pub address: Pubkey,
+    pub vault: Pubkey,
+    pub multisig: Multisig,
+}
+/// Result of a program account scan for multisigs.
+#[derive(Default)]
+pub struct MultisigSearch {
+    pub multisigs: Vec<MultisigInfo>,
+    /// Accounts matching the member filter that did not decode as a multisig, with the reason.
+    /// Vault transactions holding the member at a matching offset end up here.
+    pub skipped: Vec<(Pubkey, ClientError)>,
+}
+/// Finds every multisig `member` belongs to among its first `max_members` members.
+///
+/// `members` is a variable-length vec, so the member can sit at any of its slots: one
+/// `getProgramAccounts` request is sent per slot, with a `memcmp` filter matching the member
+/// at that slot's offset. Proposal approvals never line up with those offsets. Multisigs
+/// listing the member past `max_members` are not found.
+pub async fn find_multisigs_for_member(
+    rpc_client: &RpcClient,
+    member: &Pubkey,
+    max_members: usize,
+    program_id: Option<Pubkey>,
+) -> ClientResult<MultisigSearch> {
+    let program_id = program_id.unwrap_or(FORTIS_PROGRAM_ID);
 
-    Ok(proposal)
+    let mut search = MultisigSearch::default();
+    for slot in 0..max_members {
+        let accounts = rpc_client
+            .get_program_ui_accounts_with_config(
+                &program_id,
+                program_accounts_config(member_slot_filters(member, slot)),
+            )
+            .await?;
+        add_multisigs(&mut search, accounts, member, &program_id)?;
+    }
+    Ok(search)
+}
+/// Gets the multisig seeded by `create_key`, `None` if it does not exist.
+pub async fn find_multisigs_by_create_key(
+    rpc_client: &RpcClient,
+    create_key: &Pubkey,
+    program_id: Option<Pubkey>,
+) -> ClientResult<Option<MultisigInfo>> {
+    let program_id = program_id.unwrap_or(FORTIS_PROGRAM_ID);
+    let address = get_multisig_pda(create_key, Some(&program_id)).0;
+    let multisig = get_multisigs(rpc_client, &[address], Some(program_id)).await?;
+
+    Ok(multisig
+        .into_iter()
+        .flatten()
+        .next()
+        .map(|multisig| multisig_info(address, multisig, &program_id)))
+}
+/// Matches `member` at the `slot`-th entry of `Multisig::members`, after its `u32` length.
+pub(crate) fn member_slot_filters(member: &Pubkey, slot: usize) -> Vec<RpcFilterType> {
+    let offset = MULTISIG_HEADER_SIZE + 4 + slot * 32;
+    vec![RpcFilterType::Memcmp(Memcmp::new_base58_encoded(
+        offset,
+        member.as_ref(),
+    ))]
+}
+pub(crate) fn program_accounts_config(filters: Vec<RpcFilterType>) -> RpcProgramAccountsConfig {
+    RpcProgramAccountsConfig {
+        filters: Some(filters),
+        account_config: RpcAccountInfoConfig {
+            encoding: Some(UiAccountEncoding::Base64),
+            ..RpcAccountInfoConfig::default()
+        },
+        ..RpcProgramAccountsConfig::default()
+    }
+}
+/// Decodes the scanned `accounts` into `search`, keeping the multisigs `member` belongs to.
+/// Accounts already found by the scan of another slot are ignored.
+pub(crate) fn add_multisigs(
+    search: &mut MultisigSearch,
+    accounts: Vec<(Pubkey, UiAccount)>,
+    member: &Pubkey,
+    program_id: &Pubkey,
+) -> ClientResult<()> {
+    for (address, ui_account) in accounts {
+        let seen = search.multisigs.iter().any(|info| info.address == address)
+            || search
+                .skipped
+                .iter()
+                .any(|(skipped, _)| *skipped == address);
+        if seen {
+            continue;
+        }
+        let account = ui_account
+            .decode()
+            .ok_or(ClientError::IncompleteRpcAccount(address))?;
+        match decode_account::<Multisig>(&address, &account, program_id) {
+            Ok(multisig) if multisig.members.contains(member) => search
+                .multisigs
+                .push(multisig_info(address, multisig, program_id)),
+            Ok(_) => {}
+            Err(err) => search.skipped.push((address, err)),
+        }
+    }
+    Ok(())
+}
+pub(crate) fn multisig_info(
+    address: Pubkey,
+    multisig: Multisig,
+    program_id: &Pubkey,
+) -> MultisigInfo {
+    MultisigInfo {
+        address,
+        vault: get_vault_pda(&address, Some(program_id)).0,
+        multisig,
+    }
 }
 /*
 /// Gets a `Proposal` account from the chain
@@ -90,45 +484,83 @@ pub fn multisig_create(
     args: MultisigCreateArgs,
     program_id: Option<Pubkey>,
 ) -> Instruction {
-    let mut data = vec![0];
-    data.extend_from_slice(&args.to_vec());
     Instruction {
         accounts: accounts.to_account_metas(),
-        data: data,
+        data: FortisInstruction::MultisigCreate(args).to_vec(),
         program_id: program_id.unwrap_or(FORTIS_PROGRAM_ID),
     }
 }
 
+/// Builds the `proposal_create` instruction.
+///
+/// Only the `num_ephemeral_signers` declared ephemeral signers are derived, and it fails with
+/// `UnusedEphemeralSigner` if `message` does not reference one of them. Other signers are
+/// left to the caller; `lint` reports those that are neither the vault nor declared.
 pub fn proposal_create(
     accounts: ProposalCreateAccounts,
     num_ephemeral_signers: u8,
     message: &VaultTransactionMessage,
     voting_deadline: i64,
     program_id: Option<Pubkey>,
-) -> Instruction {
+) -> ClientResult<Instruction> {
+    let program_key = program_id.unwrap_or(FORTIS_PROGRAM_ID);
+    let signers = &message.account_keys[..usize::from(message.num_signers)];
+    for index in 0..num_ephemeral_signers {
+        let signer = get_ephemeral_signer_pda(&accounts.trasaction, index, Some(&program_key)).0;
+        if !signers.contains(&signer) {
+            return Err(ClientError::UnusedEphemeralSigner { index, signer });
+        }
+    }
+
     let args = ProposalCreateArgs {
         ephemeral_signers: num_ephemeral_signers,
         voting_deadline,
-        transaction_message: borsh::to_vec(message).unwrap(),
+        transaction_message: borsh::to_vec(message)
+            .map_err(|err| ClientError::SerializationError(err.to_string()))?,
     };
-    let mut data = vec![1];
-    data.extend_from_slice(&borsh::to_vec(&args).unwrap());
-    Instruction {
+    Ok(Instruction {
         accounts: accounts.to_account_metas(),
-        data,
+        data: FortisInstruction::ProposalCreate(args).to_vec(),
         program_id: program_id.unwrap_or(FORTIS_PROGRAM_ID),
+    })
+}
+/// Number of ephemeral signers referenced by `message`, to be proposed at `transaction_index`.
+///
+/// Every signer of the message other than the vault must be one of the ephemeral signers
+/// of that transaction, or it fails with `UnknownSigner`. Since they are used without gaps,
+/// `n` such signers can only be the first `n`, so no other index is derived.
+pub fn count_ephemeral_signers(
+    message: &VaultTransactionMessage,
+    multisig: &Pubkey,
+    transaction_index: u64,
+    program_id: Option<Pubkey>,
+) -> ClientResult<u8> {
+    let program_key = program_id.unwrap_or(FORTIS_PROGRAM_ID);
+    let vault = get_vault_pda(multisig, Some(&program_key)).0;
+    let signers: Vec<&Pubkey> = message
+        .account_keys
+        .iter()
+        .take(usize::from(message.num_signers))
+        .filter(|key| **key != vault)
+        .collect();
+    // `num_signers` is a `u8`, and the vault is one of them.
+    let count = signers.len() as u8;
+
+    let derived = ephemeral_signers(multisig, transaction_index, count, Some(&program_key));
+    match signers.into_iter().find(|signer| !derived.contains(signer)) {
+        Some(signer) => Err(ClientError::UnknownSigner(*signer)),
+        None => Ok(count),
     }
 }
+
 pub fn proposal_approve(
     accounts: ProposalApproveAccounts,
     args: ProposalApproveArgs,
     program_id: Option<Pubkey>,
 ) -> Instruction {
-    let mut data = vec![2];
-    data.extend_from_slice(&borsh::to_vec(&args).unwrap());
     Instruction {
         accounts: accounts.to_account_metas(),
-        data: data,
+        data: FortisInstruction::ProposalApprove(args).to_vec(),
         program_id: program_id.unwrap_or(FORTIS_PROGRAM_ID),
     }
 }
@@ -139,8 +571,23 @@ pub async fn proposal_execute(
     address_lookup_table_accounts: &[AddressLookupTableAccount],
     program_id: Option<Pubkey>,
 ) -> ClientResult<Instruction> {
-    let vault_transaction: VaultTransaction = borsh::from_slice(transaction_account_data).unwrap();
+    let vault_transaction = VaultTransaction::try_from_account_data(transaction_account_data)?;
 
+    proposal_execute_from_transaction(
+        &vault_transaction,
+        accounts,
+        address_lookup_table_accounts,
+        program_id,
+    )
+}
+
+/// Same as `proposal_execute`, for a `VaultTransaction` that was already decoded.
+pub fn proposal_execute_from_transaction(
+    vault_transaction: &VaultTransaction,
+    accounts: ProposalExecuteAccounts,
+    address_lookup_table_accounts: &[AddressLookupTableAccount],
+    program_id: Option<Pubkey>,
+) -> ClientResult<Instruction> {
     let program_id = program_id.unwrap_or(FORTIS_PROGRAM_ID);
 
     let vault_pda = get_vault_pda(&accounts.multisig, Some(&program_id)).0;
@@ -150,35 +597,130 @@ pub async fn proposal_execute(
         .get_accounts_for_execute(
             &vault_pda,
             &accounts.transaction,
-            &address_lookup_table_accounts,
+            address_lookup_table_accounts,
             vault_transaction.ephemeral_signer_bumps.len() as u8,
             &program_id,
         )
-        .map_err(|err| match err {
-            Error::InvalidAddressLookupTableAccount => {
-                ClientError::InvalidAddressLookupTableAccount
-            }
-            Error::InvalidTransactionMessage => ClientError::InvalidTransactionMessage,
-        })?;
+        .map_err(message_error)?;
 
     let mut accounts = accounts.to_account_metas();
     // Append the accounts required for executing the inner instructions.
-    accounts.extend(accounts_for_execute.into_iter());
+    accounts.extend(accounts_for_execute);
 
     Ok(Instruction {
         accounts,
-        data: vec![3],
+        data: FortisInstruction::ProposalExecute(ProposallExecuteArgs {}).to_vec(),
         program_id,
     })
 }
 
+/// Maps the errors of `VaultTransactionMessage` helpers to `ClientError`.
+pub(crate) fn message_error(err: Error) -> ClientError {
+    match err {
+        Error::InvalidAddressLookupTableAccount => ClientError::InvalidAddressLookupTableAccount,
+        Error::InvalidTransactionMessage => ClientError::InvalidTransactionMessage,
+        Error::InvalidProposalStatus(_) => ClientError::DeserializationError,
+    }
+}
+
+/// Builds the `proposal_execute` instruction for the proposal at `transaction_index`,
+/// fetching the transaction account and every address lookup table its message references.
+///
+/// Returns the instruction along with the lookup tables, which the outer transaction
+/// should be compiled with.
+pub async fn proposal_execute_auto(
+    rpc_client: &RpcClient,
+    multisig_key: &Pubkey,
+    transaction_index: u64,
+    member: &Pubkey,
+    program_id: Option<Pubkey>,
+) -> ClientResult<(Instruction, Vec<AddressLookupTableAccount>)> {
+    let program_key = program_id.unwrap_or(FORTIS_PROGRAM_ID);
+    let transaction_key =
+        get_transaction_pda(multisig_key, transaction_index, Some(&program_key)).0;
+    let vault_transaction = get_transaction(rpc_client, &transaction_key, program_id).await?;
+
+    let address_lookup_table_accounts =
+        get_address_lookup_tables(rpc_client, &vault_transaction.message).await?;
+
+    let instruction = execute_instruction(
+        &program_key,
+        multisig_key,
+        member,
+        &vault_transaction,
+        &address_lookup_table_accounts,
+    )?;
+
+    Ok((instruction, address_lookup_table_accounts))
+}
+
+/// Fetches the address lookup tables referenced by `message`, checking that each one
+/// is still active and holds every index the message uses.
+pub async fn get_address_lookup_tables(
+    rpc_client: &RpcClient,
+    message: &VaultTransactionMessage,
+) -> ClientResult<Vec<AddressLookupTableAccount>> {
+    let keys = lookup_table_keys(message);
+
+    let mut accounts = Vec::with_capacity(keys.len());
+    for chunk in keys.chunks(MAX_MULTIPLE_ACCOUNTS) {
+        accounts.extend(rpc_client.get_multiple_accounts(chunk).await?);
+    }
+
+    decode_address_lookup_tables(message, accounts)
+}
+
+/// Addresses of the lookup tables referenced by `message`, in order.
+pub(crate) fn lookup_table_keys(message: &VaultTransactionMessage) -> Vec<Pubkey> {
+    message
+        .address_table_lookups
+        .iter()
+        .map(|lookup| lookup.account_key)
+        .collect()
+}
+
+/// Decodes the lookup table accounts fetched for `message.address_table_lookups`, in order.
+pub(crate) fn decode_address_lookup_tables(
+    message: &VaultTransactionMessage,
+    accounts: Vec<Option<Account>>,
+) -> ClientResult<Vec<AddressLookupTableAccount>> {
+    message
+        .address_table_lookups
+        .iter()
+        .zip(accounts)
+        .map(|(lookup, account)| {
+            let key = lookup.account_key;
+            let (table, meta) = decode_lookup_table(&key, account)?;
+            if meta.deactivation_slot != u64::MAX {
+                return Err(ClientError::LookupTableDeactivated(key));
+            }
+
+            let len = table.addresses.len();
+            if let Some(&index) = lookup
+                .writable_indexes
+                .iter()
+                .chain(&lookup.readonly_indexes)
+                .find(|&&index| usize::from(index) >= len)
+            {
+                return Err(ClientError::LookupTableIndexOutOfRange {
+                    table: key,
+                    index,
+                    len,
+                });
+            }
+
+            Ok(table)
+        })
+        .collect()
+}
+
 pub fn proposal_accounts_close(
     accounts: ProposalAccountsCloseAccounts,
     program_id: Option<Pubkey>,
 ) -> Instruction {
     Instruction {
         accounts: accounts.to_account_metas(),
-        data: vec![4],
+        data: FortisInstruction::ProposalAccountsClose.to_vec(),
         program_id: program_id.unwrap_or(FORTIS_PROGRAM_ID),
     }
 }
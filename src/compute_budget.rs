@@ -0,0 +1,211 @@
+//! Compute unit limit and priority fee for the transactions sent by `FortisClient`.
+//!
+//! A `ComputeBudgetConfig` is resolved into the `ComputeBudget` instructions prepended to
+//! the transaction: the unit limit is either fixed or measured by simulation, the unit
+//! price either fixed or taken from `getRecentPrioritizationFees`.
+use std::collections::HashSet;
+
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_client::rpc_config::{CommitmentConfig, RpcSimulateTransactionConfig};
+use solana_client::rpc_response::{RpcPrioritizationFee, RpcSimulateTransactionResult};
+use solana_compute_budget_interface::ComputeBudgetInstruction;
+use solana_message::{AddressLookupTableAccount, Hash};
+use solana_sdk::{instruction::Instruction, pubkey::Pubkey, signature::Signature};
+use solana_transaction::versioned::VersionedTransaction;
+
+use crate::error::ClientError;
+use crate::fortis_client::{compile_message, with_advance_nonce};
+use crate::nonce::DurableNonce;
+use crate::ClientResult;
+
+/// Highest compute unit limit a transaction can request.
+pub const MAX_COMPUTE_UNIT_LIMIT: u32 = 1_400_000;
+
+/// Maximum number of accounts accepted by `getRecentPrioritizationFees`.
+pub const MAX_PRIORITIZATION_FEE_ACCOUNTS: usize = 128;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ComputeUnitLimit {
+    Fixed(u32),
+    /// Units consumed by a simulation, increased by `margin_percent`.
+    Simulated {
+        margin_percent: u32,
+    },
+}
+
+/// Price of a compute unit, in micro-lamports.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ComputeUnitPrice {
+    Fixed(u64),
+    /// Percentile, from 0 to 100, of the recent prioritization fees paid
+    /// to write the accounts of the transaction.
+    Percentile(u8),
+}
+
+/// Compute budget of a transaction. Leaving both fields to `None` uses the runtime defaults.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ComputeBudgetConfig {
+    pub unit_limit: Option<ComputeUnitLimit>,
+    pub unit_price: Option<ComputeUnitPrice>,
+}
+
+impl ComputeBudgetConfig {
+    pub fn is_empty(&self) -> bool {
+        self.unit_limit.is_none() && self.unit_price.is_none()
+    }
+}
+
+/// Builds the `ComputeBudget` instructions to prepend to a transaction.
+pub fn compute_budget_instructions(
+    unit_limit: Option<u32>,
+    unit_price: Option<u64>,
+) -> Vec<Instruction> {
+    unit_limit
+        .map(ComputeBudgetInstruction::set_compute_unit_limit)
+        .into_iter()
+        .chain(unit_price.map(ComputeBudgetInstruction::set_compute_unit_price))
+        .collect()
+}
+
+/// Resolves `config` for a transaction paid by `payer` carrying `instructions`,
+/// returning the `ComputeBudget` instructions to prepend to them.
+///
+/// `durable_nonce` is the nonce the transaction advances, if any: its `advance_nonce_account`
+/// instruction is counted for the fees and simulated along, as in the transaction sent.
+pub async fn resolve_compute_budget(
+    rpc_client: &RpcClient,
+    config: &ComputeBudgetConfig,
+    payer: &Pubkey,
+    instructions: &[Instruction],
+    address_lookup_table_accounts: &[AddressLookupTableAccount],
+    durable_nonce: Option<&DurableNonce>,
+) -> ClientResult<Vec<Instruction>> {
+    let sent = with_advance_nonce(durable_nonce, instructions);
+    let unit_price = match config.unit_price {
+        None => None,
+        Some(ComputeUnitPrice::Fixed(price)) => Some(price),
+        Some(ComputeUnitPrice::Percentile(percentile)) => {
+            let fees = rpc_client
+                .get_recent_prioritization_fees(&prioritization_fee_accounts(&sent))
+                .await?;
+            Some(recent_fee_percentile(&fees, percentile))
+        }
+    };
+
+    let unit_limit = match config.unit_limit {
+        None => None,
+        Some(ComputeUnitLimit::Fixed(units)) => Some(units),
+        Some(ComputeUnitLimit::Simulated { margin_percent }) => {
+            let transaction = simulation_transaction(
+                payer,
+                instructions,
+                address_lookup_table_accounts,
+                unit_price,
+                durable_nonce,
+            )?;
+            let result = rpc_client
+                .simulate_transaction_with_config(
+                    &transaction,
+                    simulation_config(rpc_client.commitment()),
+                )
+                .await?
+                .value;
+            Some(simulated_limit(result, margin_percent)?)
+        }
+    };
+
+    Ok(compute_budget_instructions(unit_limit, unit_price))
+}
+
+/// The writable accounts of `instructions`, whose locks drive the prioritization fees,
+/// capped at `MAX_PRIORITIZATION_FEE_ACCOUNTS`.
+pub fn prioritization_fee_accounts(instructions: &[Instruction]) -> Vec<Pubkey> {
+    let mut seen = HashSet::new();
+    instructions
+        .iter()
+        .flat_map(|ix| &ix.accounts)
+        .filter(|meta| meta.is_writable && seen.insert(meta.pubkey))
+        .map(|meta| meta.pubkey)
+        .take(MAX_PRIORITIZATION_FEE_ACCOUNTS)
+        .collect()
+}
+
+/// Nearest-rank `percentile` of `fees`, 0 when there are none.
+pub fn fee_percentile(fees: &[u64], percentile: u8) -> u64 {
+    if fees.is_empty() {
+        return 0;
+    }
+
+    let mut fees = fees.to_vec();
+    fees.sort_unstable();
+    let rank = (usize::from(percentile.min(100)) * fees.len()).div_ceil(100);
+    fees[rank.saturating_sub(1)]
+}
+
+/// `fee_percentile` of the fees returned by `getRecentPrioritizationFees`.
+pub(crate) fn recent_fee_percentile(fees: &[RpcPrioritizationFee], percentile: u8) -> u64 {
+    let fees: Vec<u64> = fees.iter().map(|fee| fee.prioritization_fee).collect();
+    fee_percentile(&fees, percentile)
+}
+
+/// `units` increased by `margin_percent`, capped at `MAX_COMPUTE_UNIT_LIMIT`.
+pub fn limit_with_margin(units: u64, margin_percent: u32) -> u32 {
+    let units = units.saturating_mul(100 + u64::from(margin_percent)) / 100;
+    units.min(u64::from(MAX_COMPUTE_UNIT_LIMIT)) as u32
+}
+
+/// Unsigned transaction used to measure the units consumed by `instructions`,
+/// with the highest limit so the simulation is not cut short. It is laid out like
+/// the transaction `FortisClient` sends, advancing `durable_nonce` first.
+pub(crate) fn simulation_transaction(
+    payer: &Pubkey,
+    instructions: &[Instruction],
+    address_lookup_table_accounts: &[AddressLookupTableAccount],
+    unit_price: Option<u64>,
+    durable_nonce: Option<&DurableNonce>,
+) -> ClientResult<VersionedTransaction> {
+    let mut budgeted = compute_budget_instructions(Some(MAX_COMPUTE_UNIT_LIMIT), unit_price);
+    budgeted.extend_from_slice(instructions);
+    let simulated = with_advance_nonce(durable_nonce, &budgeted);
+
+    let message = compile_message(
+        payer,
+        &simulated,
+        address_lookup_table_accounts,
+        Hash::default(),
+    )?;
+    let num_signatures = usize::from(message.header().num_required_signatures);
+
+    Ok(VersionedTransaction {
+        signatures: vec![Signature::default(); num_signatures],
+        message,
+    })
+}
+
+/// Simulation without signature verification, on a fresh blockhash.
+pub(crate) fn simulation_config(commitment: CommitmentConfig) -> RpcSimulateTransactionConfig {
+    RpcSimulateTransactionConfig {
+        sig_verify: false,
+        replace_recent_blockhash: true,
+        commitment: Some(commitment),
+        ..RpcSimulateTransactionConfig::default()
+    }
+}
+
+/// The units consumed by the simulation of `simulation_transaction`, increased by `margin_percent`.
+pub(crate) fn simulated_limit(
+    result: RpcSimulateTransactionResult,
+    margin_percent: u32,
+) -> ClientResult<u32> {
+    Ok(limit_with_margin(simulated_units(result)?, margin_percent))
+}
+
+fn simulated_units(result: RpcSimulateTransactionResult) -> ClientResult<u64> {
+    if let Some(err) = result.err {
+        return Err(ClientError::SimulationFailed(err.to_string()));
+    }
+
+    result.units_consumed.ok_or_else(|| {
+        ClientError::SimulationFailed("no consumed units in the simulation result".to_string())
+    })
+}
@@ -0,0 +1,193 @@
+//! JSON interchange format for proposed vault transactions, behind the `serde` feature.
+//!
+//! An envelope carries everything a reviewer needs to inspect a vault transaction, or a
+//! proposer needs to submit it: the multisig, the transaction index, the compiled message
+//! and the lookup tables it loads accounts from. Version 1 looks like:
+//!
+//! ```json
+//! {
+//!   "version": 1,
+//!   "program_id": "<base58>",
+//!   "multisig": "<base58>",
+//!   "transaction_index": 7,
+//!   "ephemeral_signers": 0,
+//!   "message": {
+//!     "num_signers": 1,
+//!     "num_writable_signers": 1,
+//!     "num_writable_non_signers": 1,
+//!     "account_keys": ["<base58>", "..."],
+//!     "address_table_lookups": [
+//!       { "account_key": "<base58>", "writable_indexes": [0], "readonly_indexes": [] }
+//!     ],
+//!     "instructions": [
+//!       { "program_id_index": 2, "accounts": [0, 1], "data": "<base64>" }
+//!     ]
+//!   },
+//!   "address_lookup_tables": [
+//!     { "key": "<base58>", "addresses": ["<base58>", "..."] }
+//!   ]
+//! }
+//! ```
+//!
+//! Pubkeys are base58 strings and instruction data is base64; account index lists stay
+//! plain number arrays. `from_json` rejects versions it does not know, and lookup tables
+//! that do not match the lookups of the message one for one.
+use serde::{Deserialize, Serialize};
+use solana_message::AddressLookupTableAccount;
+use solana_sdk::pubkey::Pubkey;
+
+use crate::error::ClientError;
+use crate::pda::{get_proposal_pda, get_transaction_pda, get_vault_pda, FORTIS_PROGRAM_ID};
+use crate::state::{VaultTransaction, VaultTransactionMessage};
+use crate::ClientResult;
+
+/// Version written by `VaultTransactionEnvelope::new`.
+pub const ENVELOPE_VERSION: u32 = 1;
+
+/// A vault transaction proposed, or to be proposed, at `transaction_index` of `multisig`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VaultTransactionEnvelope {
+    pub version: u32,
+    #[serde(with = "crate::serde_utils::pubkey")]
+    pub program_id: Pubkey,
+    #[serde(with = "crate::serde_utils::pubkey")]
+    pub multisig: Pubkey,
+    pub transaction_index: u64,
+    pub ephemeral_signers: u8,
+    pub message: VaultTransactionMessage,
+    /// The tables referenced by `message.address_table_lookups`, in the same order.
+    pub address_lookup_tables: Vec<LookupTable>,
+}
+
+/// Serializable copy of an `AddressLookupTableAccount`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LookupTable {
+    #[serde(with = "crate::serde_utils::pubkey")]
+    pub key: Pubkey,
+    #[serde(with = "crate::serde_utils::pubkey_vec")]
+    pub addresses: Vec<Pubkey>,
+}
+
+/// Leading field read before the rest of the envelope, so that unknown
+/// versions are reported as such rather than as malformed documents.
+#[derive(Deserialize)]
+struct VersionHeader {
+    version: u32,
+}
+
+impl VaultTransactionEnvelope {
+    pub fn new(
+        multisig: Pubkey,
+        transaction_index: u64,
+        ephemeral_signers: u8,
+        message: VaultTransactionMessage,
+        address_lookup_table_accounts: &[AddressLookupTableAccount],
+        program_id: Option<Pubkey>,
+    ) -> Self {
+        Self {
+            version: ENVELOPE_VERSION,
+            program_id: program_id.unwrap_or(FORTIS_PROGRAM_ID),
+            multisig,
+            transaction_index,
+            ephemeral_signers,
+            message,
+            address_lookup_tables: address_lookup_table_accounts
+                .iter()
+                .map(|table| LookupTable {
+                    key: table.key,
+                    addresses: table.addresses.clone(),
+                })
+                .collect(),
+        }
+    }
+
+    /// Wraps an on-chain `VaultTransaction` along with the lookup tables its message uses.
+    pub fn from_vault_transaction(
+        vault_transaction: &VaultTransaction,
+        address_lookup_table_accounts: &[AddressLookupTableAccount],
+        program_id: Option<Pubkey>,
+    ) -> Self {
+        Self::new(
+            vault_transaction.multisig,
+            vault_transaction.index,
+            vault_transaction.ephemeral_signer_bumps.len() as u8,
+            vault_transaction.message.clone(),
+            address_lookup_table_accounts,
+            program_id,
+        )
+    }
+
+    pub fn to_json(&self) -> ClientResult<String> {
+        serde_json::to_string_pretty(self)
+            .map_err(|err| ClientError::InvalidEnvelope(err.to_string()))
+    }
+
+    pub fn from_json(json: &str) -> ClientResult<Self> {
+        let header: VersionHeader = serde_json::from_str(json)
+            .map_err(|err| ClientError::InvalidEnvelope(err.to_string()))?;
+        if header.version != ENVELOPE_VERSION {
+            return Err(ClientError::UnsupportedEnvelopeVersion(header.version));
+        }
+
+        let envelope: Self = serde_json::from_str(json)
+            .map_err(|err| ClientError::InvalidEnvelope(err.to_string()))?;
+        envelope.check_lookup_tables()?;
+        Ok(envelope)
+    }
+
+    /// Checks that `address_lookup_tables` lists the tables of `message.address_table_lookups`,
+    /// in the same order.
+    fn check_lookup_tables(&self) -> ClientResult<()> {
+        let lookups = &self.message.address_table_lookups;
+        if lookups.len() != self.address_lookup_tables.len() {
+            return Err(ClientError::InvalidEnvelope(format!(
+                "the message uses {} lookup tables, {} are given",
+                lookups.len(),
+                self.address_lookup_tables.len()
+            )));
+        }
+        for (position, (lookup, table)) in
+            lookups.iter().zip(&self.address_lookup_tables).enumerate()
+        {
+            if lookup.account_key != table.key {
+                return Err(ClientError::InvalidEnvelope(format!(
+                    "lookup table #{position} is {}, the message uses {}",
+                    table.key, lookup.account_key
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    pub fn address_lookup_table_accounts(&self) -> Vec<AddressLookupTableAccount> {
+        self.address_lookup_tables
+            .iter()
+            .map(|table| AddressLookupTableAccount {
+                key: table.key,
+                addresses: table.addresses.clone(),
+            })
+            .collect()
+    }
+
+    pub fn vault(&self) -> Pubkey {
+        get_vault_pda(&self.multisig, Some(&self.program_id)).0
+    }
+
+    pub fn proposal(&self) -> Pubkey {
+        get_proposal_pda(
+            &self.multisig,
+            self.transaction_index,
+            Some(&self.program_id),
+        )
+        .0
+    }
+
+    pub fn transaction(&self) -> Pubkey {
+        get_transaction_pda(
+            &self.multisig,
+            self.transaction_index,
+            Some(&self.program_id),
+        )
+        .0
+    }
+}
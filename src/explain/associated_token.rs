@@ -0,0 +1,45 @@
+use solana_sdk::{instruction::Instruction, pubkey, pubkey::Pubkey};
+
+use super::{account, DecodedInstruction, InstructionDecoder, Value};
+
+pub const ASSOCIATED_TOKEN_PROGRAM_ID: Pubkey =
+    pubkey!("ATokenGPvbdGVxr1b2hvZbsiqW5xWH25efTNsLJA8knL");
+
+const PROGRAM: &str = "Associated Token Account Program";
+
+/// Decodes the Associated Token Account program instructions, tagged by a single byte.
+pub struct AssociatedTokenDecoder;
+
+impl InstructionDecoder for AssociatedTokenDecoder {
+    fn handles(&self, program_id: &Pubkey) -> bool {
+        *program_id == ASSOCIATED_TOKEN_PROGRAM_ID
+    }
+
+    fn decode(&self, instruction: &Instruction) -> Option<DecodedInstruction> {
+        // An empty payload is the original `Create`.
+        let name = match instruction.data.first() {
+            None | Some(0) => "Create",
+            Some(1) => "CreateIdempotent",
+            Some(2) => {
+                return Some(
+                    DecodedInstruction::new(PROGRAM, "RecoverNested")
+                        .with("nested_account", Value::Pubkey(account(instruction, 0)?))
+                        .with("nested_mint", Value::Pubkey(account(instruction, 1)?))
+                        .with("destination", Value::Pubkey(account(instruction, 2)?))
+                        .with("owner_account", Value::Pubkey(account(instruction, 3)?))
+                        .with("owner_mint", Value::Pubkey(account(instruction, 4)?))
+                        .with("wallet", Value::Pubkey(account(instruction, 5)?)),
+                )
+            }
+            Some(_) => return None,
+        };
+        Some(
+            DecodedInstruction::new(PROGRAM, name)
+                .with("payer", Value::Pubkey(account(instruction, 0)?))
+                .with("account", Value::Pubkey(account(instruction, 1)?))
+                .with("wallet", Value::Pubkey(account(instruction, 2)?))
+                .with("mint", Value::Pubkey(account(instruction, 3)?))
+                .with("token_program", Value::Pubkey(account(instruction, 5)?)),
+        )
+    }
+}
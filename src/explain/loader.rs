@@ -0,0 +1,82 @@
+use solana_sdk::{instruction::Instruction, pubkey, pubkey::Pubkey};
+
+use super::{account, DataReader, DecodedInstruction, InstructionDecoder, Value};
+
+pub const BPF_LOADER_UPGRADEABLE_PROGRAM_ID: Pubkey =
+    pubkey!("BPFLoaderUpgradeab1e11111111111111111111111");
+
+const PROGRAM: &str = "BPF Upgradeable Loader";
+
+/// Decodes the BPF upgradeable loader instructions, bincode encoded with a `u32` tag.
+pub struct UpgradeableLoaderDecoder;
+
+impl InstructionDecoder for UpgradeableLoaderDecoder {
+    fn handles(&self, program_id: &Pubkey) -> bool {
+        *program_id == BPF_LOADER_UPGRADEABLE_PROGRAM_ID
+    }
+
+    fn decode(&self, instruction: &Instruction) -> Option<DecodedInstruction> {
+        let new = |name| DecodedInstruction::new(PROGRAM, name);
+        let account_field = |position| Some(Value::Pubkey(account(instruction, position)?));
+        let optional_account = |position| Value::OptionalPubkey(account(instruction, position));
+        let mut data = DataReader::new(&instruction.data);
+
+        let decoded = match data.u32()? {
+            0 => new("InitializeBuffer")
+                .with("buffer", account_field(0)?)
+                .with("authority", optional_account(1)),
+            1 => {
+                let offset = data.u32()?;
+                let len = data.u64()?;
+                new("Write")
+                    .with("buffer", account_field(0)?)
+                    .with("authority", account_field(1)?)
+                    .with("offset", Value::Number(u64::from(offset)))
+                    .with("bytes", Value::Number(len))
+            }
+            2 => new("DeployWithMaxDataLen")
+                .with("payer", account_field(0)?)
+                .with("program_data", account_field(1)?)
+                .with("program", account_field(2)?)
+                .with("buffer", account_field(3)?)
+                .with("authority", account_field(7)?)
+                .with("max_data_len", Value::Number(data.u64()?)),
+            3 => new("Upgrade")
+                .with("program_data", account_field(0)?)
+                .with("program", account_field(1)?)
+                .with("buffer", account_field(2)?)
+                .with("spill", account_field(3)?)
+                .with("authority", account_field(6)?),
+            4 => new("SetAuthority")
+                .with("account", account_field(0)?)
+                .with("current_authority", account_field(1)?)
+                .with("new_authority", optional_account(2)),
+            5 => new("Close")
+                .with("account", account_field(0)?)
+                .with("recipient", account_field(1)?)
+                .with("authority", optional_account(2))
+                .with("program", optional_account(3)),
+            6 => new("ExtendProgram")
+                .with("program_data", account_field(0)?)
+                .with("program", account_field(1)?)
+                .with("payer", optional_account(3))
+                .with("additional_bytes", Value::Number(u64::from(data.u32()?))),
+            7 => new("SetAuthorityChecked")
+                .with("account", account_field(0)?)
+                .with("current_authority", account_field(1)?)
+                .with("new_authority", account_field(2)?),
+            8 => new("Migrate")
+                .with("program_data", account_field(0)?)
+                .with("program", account_field(1)?)
+                .with("authority", account_field(2)?),
+            9 => new("ExtendProgramChecked")
+                .with("program_data", account_field(0)?)
+                .with("program", account_field(1)?)
+                .with("authority", account_field(2)?)
+                .with("payer", optional_account(4))
+                .with("additional_bytes", Value::Number(u64::from(data.u32()?))),
+            _ => return None,
+        };
+        Some(decoded)
+    }
+}
@@ -0,0 +1,26 @@
+use solana_sdk::{instruction::Instruction, pubkey, pubkey::Pubkey};
+
+use super::{DecodedInstruction, InstructionDecoder, Value};
+
+pub const MEMO_PROGRAM_ID: Pubkey = pubkey!("MemoSq4gqABAXKb96qnH8TysNcWxMyWCqXgDLGmfcHr");
+pub const MEMO_V1_PROGRAM_ID: Pubkey = pubkey!("Memo1UhkJRfHyvLMcVucJwxXeuD728EqVDDwQDxFMNo");
+
+/// Decodes memos, whose data is the UTF-8 text itself.
+pub struct MemoDecoder;
+
+impl InstructionDecoder for MemoDecoder {
+    fn handles(&self, program_id: &Pubkey) -> bool {
+        *program_id == MEMO_PROGRAM_ID || *program_id == MEMO_V1_PROGRAM_ID
+    }
+
+    fn decode(&self, instruction: &Instruction) -> Option<DecodedInstruction> {
+        let memo = std::str::from_utf8(&instruction.data).ok()?;
+        Some(
+            instruction.accounts.iter().fold(
+                DecodedInstruction::new("Memo Program", "Memo")
+                    .with("memo", Value::Text(memo.to_string())),
+                |decoded, signer| decoded.with("signer", Value::Pubkey(signer.pubkey)),
+            ),
+        )
+    }
+}
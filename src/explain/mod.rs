@@ -0,0 +1,334 @@
+//! Human-readable descriptions of the instructions inside a vault transaction.
+//!
+//! Members approve a `VaultTransactionMessage`, a list of compiled instructions that says
+//! little by itself. An `Explainer` decompiles the message and runs each instruction through
+//! the `InstructionDecoder` registered for its program, producing named fields such as
+//! `lamports: 1.5 SOL` or `destination: <pubkey>`.
+//!
+//! Built-in decoders cover the System, SPL Token, Token-2022, Associated Token Account, Memo,
+//! BPF upgradeable loader and Stake programs. Decoders for other programs are added with
+//! `Explainer::with_decoder` and take precedence over the built-in ones.
+use std::fmt;
+
+use solana_message::AddressLookupTableAccount;
+use solana_sdk::{instruction::Instruction, pubkey::Pubkey};
+
+use crate::client::message_error;
+use crate::state::VaultTransactionMessage;
+use crate::ClientResult;
+
+mod associated_token;
+mod loader;
+mod memo;
+mod stake;
+mod system;
+mod token;
+
+pub use associated_token::{AssociatedTokenDecoder, ASSOCIATED_TOKEN_PROGRAM_ID};
+pub use loader::{UpgradeableLoaderDecoder, BPF_LOADER_UPGRADEABLE_PROGRAM_ID};
+pub use memo::{MemoDecoder, MEMO_PROGRAM_ID, MEMO_V1_PROGRAM_ID};
+pub use stake::{StakeDecoder, STAKE_PROGRAM_ID};
+pub use system::{SystemDecoder, SYSTEM_PROGRAM_ID};
+pub use token::{TokenDecoder, TOKEN_2022_PROGRAM_ID, TOKEN_PROGRAM_ID};
+
+/// Number of decimals of SOL amounts.
+pub const SOL_DECIMALS: u8 = 9;
+
+/// Decodes the instructions of the programs it handles.
+pub trait InstructionDecoder: Send + Sync {
+    /// Returns true if this decoder understands instructions of `program_id`.
+    fn handles(&self, program_id: &Pubkey) -> bool;
+
+    /// Decodes `instruction`, `None` if its data or accounts do not match any known instruction.
+    fn decode(&self, instruction: &Instruction) -> Option<DecodedInstruction>;
+}
+
+/// An instruction recognised by a decoder.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DecodedInstruction {
+    /// Name of the program, e.g. `System Program`.
+    pub program: String,
+    /// Name of the instruction, e.g. `Transfer`.
+    pub name: String,
+    /// Arguments and accounts of the instruction, in display order.
+    pub fields: Vec<Field>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Field {
+    pub name: String,
+    pub value: Value,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Value {
+    Pubkey(Pubkey),
+    /// A pubkey that may be unset, such as a removed authority.
+    OptionalPubkey(Option<Pubkey>),
+    Lamports(u64),
+    /// A token amount in base units, with the decimals of the mint when the instruction carries them.
+    TokenAmount {
+        amount: u64,
+        decimals: Option<u8>,
+    },
+    Number(u64),
+    Text(String),
+}
+
+/// One instruction of a vault transaction, decompiled and decoded when possible.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExplainedInstruction {
+    /// Position of the instruction within the message.
+    pub index: usize,
+    pub instruction: Instruction,
+    /// `None` when no decoder handles the program or recognises the data.
+    pub decoded: Option<DecodedInstruction>,
+}
+
+/// Decodes vault transaction instructions with a list of decoders.
+pub struct Explainer {
+    decoders: Vec<Box<dyn InstructionDecoder>>,
+}
+
+impl DecodedInstruction {
+    pub fn new(program: &str, name: &str) -> Self {
+        Self {
+            program: program.to_string(),
+            name: name.to_string(),
+            fields: Vec::new(),
+        }
+    }
+
+    /// Appends a field, for decoders building the description.
+    pub fn with(mut self, name: &str, value: Value) -> Self {
+        self.fields.push(Field {
+            name: name.to_string(),
+            value,
+        });
+        self
+    }
+
+    pub fn field(&self, name: &str) -> Option<&Value> {
+        self.fields
+            .iter()
+            .find(|field| field.name == name)
+            .map(|field| &field.value)
+    }
+
+    /// The pubkey stored in field `name`, `None` if it is missing or unset.
+    pub fn pubkey(&self, name: &str) -> Option<Pubkey> {
+        match self.field(name)? {
+            Value::Pubkey(pubkey) => Some(*pubkey),
+            Value::OptionalPubkey(pubkey) => *pubkey,
+            _ => None,
+        }
+    }
+
+    /// The amount stored in field `name`, in lamports or token base units.
+    pub fn amount(&self, name: &str) -> Option<u64> {
+        match self.field(name)? {
+            Value::Lamports(amount) | Value::Number(amount) => Some(*amount),
+            Value::TokenAmount { amount, .. } => Some(*amount),
+            _ => None,
+        }
+    }
+}
+
+impl Explainer {
+    /// An explainer with every built-in decoder.
+    pub fn new() -> Self {
+        Self::empty()
+            .with_decoder(SystemDecoder)
+            .with_decoder(TokenDecoder)
+            .with_decoder(AssociatedTokenDecoder)
+            .with_decoder(MemoDecoder)
+            .with_decoder(UpgradeableLoaderDecoder)
+            .with_decoder(StakeDecoder)
+    }
+
+    /// An explainer without any decoder.
+    pub fn empty() -> Self {
+        Self {
+            decoders: Vec::new(),
+        }
+    }
+
+    /// Registers `decoder`, tried before the decoders registered earlier.
+    pub fn with_decoder(mut self, decoder: impl InstructionDecoder + 'static) -> Self {
+        self.decoders.insert(0, Box::new(decoder));
+        self
+    }
+
+    /// Decompiles `message` and decodes each of its instructions.
+    pub fn explain(
+        &self,
+        message: &VaultTransactionMessage,
+        address_lookup_table_accounts: &[AddressLookupTableAccount],
+    ) -> ClientResult<Vec<ExplainedInstruction>> {
+        let instructions = message
+            .decompile(address_lookup_table_accounts)
+            .map_err(message_error)?;
+
+        Ok(instructions
+            .into_iter()
+            .enumerate()
+            .map(|(index, instruction)| self.explain_instruction(index, instruction))
+            .collect())
+    }
+
+    pub fn explain_instruction(
+        &self,
+        index: usize,
+        instruction: Instruction,
+    ) -> ExplainedInstruction {
+        let decoded = self
+            .decoders
+            .iter()
+            .filter(|decoder| decoder.handles(&instruction.program_id))
+            .find_map(|decoder| decoder.decode(&instruction));
+
+        ExplainedInstruction {
+            index,
+            instruction,
+            decoded,
+        }
+    }
+}
+
+impl Default for Explainer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Explains `message` with the built-in decoders.
+pub fn explain(
+    message: &VaultTransactionMessage,
+    address_lookup_table_accounts: &[AddressLookupTableAccount],
+) -> ClientResult<Vec<ExplainedInstruction>> {
+    Explainer::new().explain(message, address_lookup_table_accounts)
+}
+
+/// Formats `amount` base units of a `decimals` asset, e.g. `1500000000` lamports as `1.5`.
+pub fn format_amount(amount: u64, decimals: u8) -> String {
+    let divisor = 10u128.pow(u32::from(decimals));
+    let whole = u128::from(amount) / divisor;
+    let fraction = u128::from(amount) % divisor;
+    if fraction == 0 {
+        return whole.to_string();
+    }
+    let fraction = format!("{:0width$}", fraction, width = usize::from(decimals));
+    format!("{}.{}", whole, fraction.trim_end_matches('0'))
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Value::Pubkey(pubkey) | Value::OptionalPubkey(Some(pubkey)) => write!(f, "{pubkey}"),
+            Value::OptionalPubkey(None) => write!(f, "none"),
+            Value::Lamports(lamports) => {
+                write!(f, "{} SOL", format_amount(*lamports, SOL_DECIMALS))
+            }
+            Value::TokenAmount {
+                amount,
+                decimals: Some(decimals),
+            } => write!(f, "{}", format_amount(*amount, *decimals)),
+            Value::TokenAmount {
+                amount,
+                decimals: None,
+            } => write!(f, "{amount} (base units)"),
+            Value::Number(number) => write!(f, "{number}"),
+            Value::Text(text) => write!(f, "{text:?}"),
+        }
+    }
+}
+
+impl fmt::Display for DecodedInstruction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.program, self.name)?;
+        for (position, field) in self.fields.iter().enumerate() {
+            let separator = if position == 0 { " (" } else { ", " };
+            write!(f, "{separator}{}: {}", field.name, field.value)?;
+        }
+        if !self.fields.is_empty() {
+            write!(f, ")")?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for ExplainedInstruction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.decoded {
+            Some(decoded) => write!(f, "#{} {}", self.index, decoded),
+            None => write!(
+                f,
+                "#{} Unknown instruction of {} ({} accounts, {} bytes of data)",
+                self.index,
+                self.instruction.program_id,
+                self.instruction.accounts.len(),
+                self.instruction.data.len()
+            ),
+        }
+    }
+}
+
+/// Little-endian reader over instruction data.
+pub(crate) struct DataReader<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> DataReader<'a> {
+    pub(crate) fn new(data: &'a [u8]) -> Self {
+        Self { data }
+    }
+
+    pub(crate) fn bytes(&mut self, len: usize) -> Option<&'a [u8]> {
+        if self.data.len() < len {
+            return None;
+        }
+        let (bytes, rest) = self.data.split_at(len);
+        self.data = rest;
+        Some(bytes)
+    }
+
+    pub(crate) fn u8(&mut self) -> Option<u8> {
+        Some(self.bytes(1)?[0])
+    }
+
+    pub(crate) fn u32(&mut self) -> Option<u32> {
+        Some(u32::from_le_bytes(self.bytes(4)?.try_into().ok()?))
+    }
+
+    pub(crate) fn u64(&mut self) -> Option<u64> {
+        Some(u64::from_le_bytes(self.bytes(8)?.try_into().ok()?))
+    }
+
+    pub(crate) fn i64(&mut self) -> Option<i64> {
+        Some(i64::from_le_bytes(self.bytes(8)?.try_into().ok()?))
+    }
+
+    pub(crate) fn pubkey(&mut self) -> Option<Pubkey> {
+        Some(Pubkey::new_from_array(self.bytes(32)?.try_into().ok()?))
+    }
+
+    /// A pubkey preceded by a one byte `Some`/`None` tag, as in bincode and `COption`.
+    pub(crate) fn option_pubkey(&mut self) -> Option<Option<Pubkey>> {
+        match self.u8()? {
+            0 => Some(None),
+            1 => Some(Some(self.pubkey()?)),
+            _ => None,
+        }
+    }
+
+    /// A bincode string: a `u64` length followed by UTF-8 bytes.
+    pub(crate) fn string(&mut self) -> Option<String> {
+        let len = usize::try_from(self.u64()?).ok()?;
+        String::from_utf8(self.bytes(len)?.to_vec()).ok()
+    }
+}
+
+/// The pubkey of the account at `position` of `instruction`.
+pub(crate) fn account(instruction: &Instruction, position: usize) -> Option<Pubkey> {
+    instruction.accounts.get(position).map(|meta| meta.pubkey)
+}
@@ -0,0 +1,115 @@
+use solana_sdk::{instruction::Instruction, pubkey, pubkey::Pubkey};
+
+use super::{account, DataReader, DecodedInstruction, InstructionDecoder, Value};
+
+pub const STAKE_PROGRAM_ID: Pubkey = pubkey!("Stake11111111111111111111111111111111111111");
+
+const PROGRAM: &str = "Stake Program";
+
+/// Decodes the Stake program instructions, bincode encoded with a `u32` tag.
+///
+/// Lockup changes and seeded authorizations are named without their arguments.
+pub struct StakeDecoder;
+
+impl InstructionDecoder for StakeDecoder {
+    fn handles(&self, program_id: &Pubkey) -> bool {
+        *program_id == STAKE_PROGRAM_ID
+    }
+
+    fn decode(&self, instruction: &Instruction) -> Option<DecodedInstruction> {
+        let new = |name| DecodedInstruction::new(PROGRAM, name);
+        let account_field = |position| Some(Value::Pubkey(account(instruction, position)?));
+        let mut data = DataReader::new(&instruction.data);
+
+        let decoded = match data.u32()? {
+            0 => {
+                let staker = data.pubkey()?;
+                let withdrawer = data.pubkey()?;
+                let unix_timestamp = data.i64()?;
+                let epoch = data.u64()?;
+                let custodian = data.pubkey()?;
+                let decoded = new("Initialize")
+                    .with("stake_account", account_field(0)?)
+                    .with("staker", Value::Pubkey(staker))
+                    .with("withdrawer", Value::Pubkey(withdrawer));
+                if unix_timestamp == 0 && epoch == 0 && custodian == Pubkey::default() {
+                    decoded
+                } else {
+                    decoded
+                        .with(
+                            "lockup_unix_timestamp",
+                            Value::Text(unix_timestamp.to_string()),
+                        )
+                        .with("lockup_epoch", Value::Number(epoch))
+                        .with("custodian", Value::Pubkey(custodian))
+                }
+            }
+            1 => {
+                let new_authority = data.pubkey()?;
+                new("Authorize")
+                    .with("stake_account", account_field(0)?)
+                    .with("authority", account_field(2)?)
+                    .with("new_authority", Value::Pubkey(new_authority))
+                    .with("authority_type", Value::Text(stake_authorize(data.u32()?)))
+            }
+            2 => new("DelegateStake")
+                .with("stake_account", account_field(0)?)
+                .with("vote_account", account_field(1)?)
+                .with("authority", account_field(5)?),
+            3 => new("Split")
+                .with("stake_account", account_field(0)?)
+                .with("split_stake_account", account_field(1)?)
+                .with("authority", account_field(2)?)
+                .with("lamports", Value::Lamports(data.u64()?)),
+            4 => new("Withdraw")
+                .with("stake_account", account_field(0)?)
+                .with("recipient", account_field(1)?)
+                .with("authority", account_field(4)?)
+                .with("lamports", Value::Lamports(data.u64()?)),
+            5 => new("Deactivate")
+                .with("stake_account", account_field(0)?)
+                .with("authority", account_field(2)?),
+            6 => new("SetLockup").with("stake_account", account_field(0)?),
+            7 => new("Merge")
+                .with("destination", account_field(0)?)
+                .with("source", account_field(1)?)
+                .with("authority", account_field(4)?),
+            8 => new("AuthorizeWithSeed").with("stake_account", account_field(0)?),
+            9 => new("InitializeChecked")
+                .with("stake_account", account_field(0)?)
+                .with("staker", account_field(2)?)
+                .with("withdrawer", account_field(3)?),
+            10 => new("AuthorizeChecked")
+                .with("stake_account", account_field(0)?)
+                .with("authority", account_field(2)?)
+                .with("new_authority", account_field(3)?)
+                .with("authority_type", Value::Text(stake_authorize(data.u32()?))),
+            11 => new("AuthorizeCheckedWithSeed").with("stake_account", account_field(0)?),
+            12 => new("SetLockupChecked").with("stake_account", account_field(0)?),
+            13 => new("GetMinimumDelegation"),
+            14 => new("DeactivateDelinquent")
+                .with("stake_account", account_field(0)?)
+                .with("delinquent_vote_account", account_field(1)?),
+            16 => new("MoveStake")
+                .with("source", account_field(0)?)
+                .with("destination", account_field(1)?)
+                .with("authority", account_field(2)?)
+                .with("lamports", Value::Lamports(data.u64()?)),
+            17 => new("MoveLamports")
+                .with("source", account_field(0)?)
+                .with("destination", account_field(1)?)
+                .with("authority", account_field(2)?)
+                .with("lamports", Value::Lamports(data.u64()?)),
+            _ => return None,
+        };
+        Some(decoded)
+    }
+}
+
+fn stake_authorize(tag: u32) -> String {
+    match tag {
+        0 => "Staker".to_string(),
+        1 => "Withdrawer".to_string(),
+        _ => format!("Unknown({tag})"),
+    }
+}
@@ -0,0 +1,77 @@
+use solana_sdk::{instruction::Instruction, pubkey, pubkey::Pubkey};
+
+use super::{account, DataReader, DecodedInstruction, InstructionDecoder, Value};
+
+pub const SYSTEM_PROGRAM_ID: Pubkey = pubkey!("11111111111111111111111111111111");
+
+const PROGRAM: &str = "System Program";
+
+/// Decodes the System program instructions, bincode encoded with a `u32` tag.
+pub struct SystemDecoder;
+
+impl InstructionDecoder for SystemDecoder {
+    fn handles(&self, program_id: &Pubkey) -> bool {
+        *program_id == SYSTEM_PROGRAM_ID
+    }
+
+    fn decode(&self, instruction: &Instruction) -> Option<DecodedInstruction> {
+        let mut data = DataReader::new(&instruction.data);
+        let decoded = match data.u32()? {
+            0 => DecodedInstruction::new(PROGRAM, "CreateAccount")
+                .with("from", Value::Pubkey(account(instruction, 0)?))
+                .with("new_account", Value::Pubkey(account(instruction, 1)?))
+                .with("lamports", Value::Lamports(data.u64()?))
+                .with("space", Value::Number(data.u64()?))
+                .with("owner", Value::Pubkey(data.pubkey()?)),
+            1 => DecodedInstruction::new(PROGRAM, "Assign")
+                .with("account", Value::Pubkey(account(instruction, 0)?))
+                .with("owner", Value::Pubkey(data.pubkey()?)),
+            2 => DecodedInstruction::new(PROGRAM, "Transfer")
+                .with("from", Value::Pubkey(account(instruction, 0)?))
+                .with("to", Value::Pubkey(account(instruction, 1)?))
+                .with("lamports", Value::Lamports(data.u64()?)),
+            3 => {
+                let base = data.pubkey()?;
+                let seed = data.string()?;
+                DecodedInstruction::new(PROGRAM, "CreateAccountWithSeed")
+                    .with("from", Value::Pubkey(account(instruction, 0)?))
+                    .with("new_account", Value::Pubkey(account(instruction, 1)?))
+                    .with("base", Value::Pubkey(base))
+                    .with("seed", Value::Text(seed))
+                    .with("lamports", Value::Lamports(data.u64()?))
+                    .with("space", Value::Number(data.u64()?))
+                    .with("owner", Value::Pubkey(data.pubkey()?))
+            }
+            4 => DecodedInstruction::new(PROGRAM, "AdvanceNonceAccount")
+                .with("nonce_account", Value::Pubkey(account(instruction, 0)?))
+                .with("authority", Value::Pubkey(account(instruction, 2)?)),
+            5 => DecodedInstruction::new(PROGRAM, "WithdrawNonceAccount")
+                .with("nonce_account", Value::Pubkey(account(instruction, 0)?))
+                .with("to", Value::Pubkey(account(instruction, 1)?))
+                .with("authority", Value::Pubkey(account(instruction, 4)?))
+                .with("lamports", Value::Lamports(data.u64()?)),
+            6 => DecodedInstruction::new(PROGRAM, "InitializeNonceAccount")
+                .with("nonce_account", Value::Pubkey(account(instruction, 0)?))
+                .with("authority", Value::Pubkey(data.pubkey()?)),
+            7 => DecodedInstruction::new(PROGRAM, "AuthorizeNonceAccount")
+                .with("nonce_account", Value::Pubkey(account(instruction, 0)?))
+                .with("authority", Value::Pubkey(account(instruction, 1)?))
+                .with("new_authority", Value::Pubkey(data.pubkey()?)),
+            8 => DecodedInstruction::new(PROGRAM, "Allocate")
+                .with("account", Value::Pubkey(account(instruction, 0)?))
+                .with("space", Value::Number(data.u64()?)),
+            11 => {
+                let lamports = data.u64()?;
+                let from_seed = data.string()?;
+                DecodedInstruction::new(PROGRAM, "TransferWithSeed")
+                    .with("from", Value::Pubkey(account(instruction, 0)?))
+                    .with("base", Value::Pubkey(account(instruction, 1)?))
+                    .with("to", Value::Pubkey(account(instruction, 2)?))
+                    .with("seed", Value::Text(from_seed))
+                    .with("lamports", Value::Lamports(lamports))
+            }
+            _ => return None,
+        };
+        Some(decoded)
+    }
+}
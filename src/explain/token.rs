@@ -0,0 +1,144 @@
+use solana_sdk::{instruction::Instruction, pubkey, pubkey::Pubkey};
+
+use super::{account, DataReader, DecodedInstruction, InstructionDecoder, Value};
+
+pub const TOKEN_PROGRAM_ID: Pubkey = pubkey!("TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA");
+pub const TOKEN_2022_PROGRAM_ID: Pubkey = pubkey!("TokenzQdBNbLqP5VEhdkAS6EPFLC1PHnBqCXEpPxuEb");
+
+/// Decodes the SPL Token and Token-2022 instructions shared by both programs,
+/// tagged by a single byte. Token-2022 extension instructions are left undecoded.
+pub struct TokenDecoder;
+
+impl InstructionDecoder for TokenDecoder {
+    fn handles(&self, program_id: &Pubkey) -> bool {
+        *program_id == TOKEN_PROGRAM_ID || *program_id == TOKEN_2022_PROGRAM_ID
+    }
+
+    fn decode(&self, instruction: &Instruction) -> Option<DecodedInstruction> {
+        let program = if instruction.program_id == TOKEN_2022_PROGRAM_ID {
+            "Token-2022 Program"
+        } else {
+            "Token Program"
+        };
+        let new = |name| DecodedInstruction::new(program, name);
+        let account_field = |position| Some(Value::Pubkey(account(instruction, position)?));
+        let mut data = DataReader::new(&instruction.data);
+
+        let decoded = match data.u8()? {
+            0 => new("InitializeMint")
+                .with("mint", account_field(0)?)
+                .with("decimals", Value::Number(u64::from(data.u8()?)))
+                .with("mint_authority", Value::Pubkey(data.pubkey()?))
+                .with(
+                    "freeze_authority",
+                    Value::OptionalPubkey(data.option_pubkey()?),
+                ),
+            1 => new("InitializeAccount")
+                .with("account", account_field(0)?)
+                .with("mint", account_field(1)?)
+                .with("owner", account_field(2)?),
+            3 => new("Transfer")
+                .with("source", account_field(0)?)
+                .with("destination", account_field(1)?)
+                .with("authority", account_field(2)?)
+                .with("amount", token_amount(data.u64()?, None)),
+            4 => new("Approve")
+                .with("source", account_field(0)?)
+                .with("delegate", account_field(1)?)
+                .with("owner", account_field(2)?)
+                .with("amount", token_amount(data.u64()?, None)),
+            5 => new("Revoke")
+                .with("source", account_field(0)?)
+                .with("owner", account_field(1)?),
+            6 => new("SetAuthority")
+                .with("account", account_field(0)?)
+                .with("current_authority", account_field(1)?)
+                .with("authority_type", Value::Text(authority_type(data.u8()?)))
+                .with(
+                    "new_authority",
+                    Value::OptionalPubkey(data.option_pubkey()?),
+                ),
+            7 => new("MintTo")
+                .with("mint", account_field(0)?)
+                .with("account", account_field(1)?)
+                .with("authority", account_field(2)?)
+                .with("amount", token_amount(data.u64()?, None)),
+            8 => new("Burn")
+                .with("account", account_field(0)?)
+                .with("mint", account_field(1)?)
+                .with("authority", account_field(2)?)
+                .with("amount", token_amount(data.u64()?, None)),
+            9 => new("CloseAccount")
+                .with("account", account_field(0)?)
+                .with("destination", account_field(1)?)
+                .with("authority", account_field(2)?),
+            10 => new("FreezeAccount")
+                .with("account", account_field(0)?)
+                .with("mint", account_field(1)?)
+                .with("authority", account_field(2)?),
+            11 => new("ThawAccount")
+                .with("account", account_field(0)?)
+                .with("mint", account_field(1)?)
+                .with("authority", account_field(2)?),
+            12 => new("TransferChecked")
+                .with("source", account_field(0)?)
+                .with("mint", account_field(1)?)
+                .with("destination", account_field(2)?)
+                .with("authority", account_field(3)?)
+                .with("amount", checked_amount(&mut data)?),
+            13 => new("ApproveChecked")
+                .with("source", account_field(0)?)
+                .with("mint", account_field(1)?)
+                .with("delegate", account_field(2)?)
+                .with("owner", account_field(3)?)
+                .with("amount", checked_amount(&mut data)?),
+            14 => new("MintToChecked")
+                .with("mint", account_field(0)?)
+                .with("account", account_field(1)?)
+                .with("authority", account_field(2)?)
+                .with("amount", checked_amount(&mut data)?),
+            15 => new("BurnChecked")
+                .with("account", account_field(0)?)
+                .with("mint", account_field(1)?)
+                .with("authority", account_field(2)?)
+                .with("amount", checked_amount(&mut data)?),
+            17 => new("SyncNative").with("account", account_field(0)?),
+            _ => return None,
+        };
+        Some(decoded)
+    }
+}
+
+fn token_amount(amount: u64, decimals: Option<u8>) -> Value {
+    Value::TokenAmount { amount, decimals }
+}
+
+/// The `amount: u64, decimals: u8` payload of the `*Checked` instructions.
+fn checked_amount(data: &mut DataReader) -> Option<Value> {
+    let amount = data.u64()?;
+    Some(token_amount(amount, Some(data.u8()?)))
+}
+
+fn authority_type(tag: u8) -> String {
+    match tag {
+        0 => "MintTokens",
+        1 => "FreezeAccount",
+        2 => "AccountOwner",
+        3 => "CloseAccount",
+        4 => "TransferFeeConfig",
+        5 => "WithheldWithdraw",
+        6 => "CloseMint",
+        7 => "InterestRate",
+        8 => "PermanentDelegate",
+        9 => "ConfidentialTransferMint",
+        10 => "TransferHookProgramId",
+        11 => "ConfidentialTransferFeeConfig",
+        12 => "MetadataPointer",
+        13 => "GroupPointer",
+        14 => "GroupMemberPointer",
+        15 => "ScaledUiAmount",
+        16 => "Pause",
+        _ => return format!("Unknown({tag})"),
+    }
+    .to_string()
+}
@@ -0,0 +1,713 @@
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_client::rpc_config::CommitmentConfig;
+use solana_message::{v0, AddressLookupTableAccount, Hash, Message, VersionedMessage};
+use solana_sdk::{instruction::Instruction, pubkey::Pubkey, signature::Signature};
+use solana_signer::Signer;
+use solana_transaction::versioned::VersionedTransaction;
+
+use crate::client::{
+    get_address_lookup_tables, get_multisig, get_proposal, get_transaction, multisig_create,
+    proposal_accounts_close, proposal_approve, proposal_create, proposal_execute_from_transaction,
+};
+use crate::compute_budget::{resolve_compute_budget, ComputeBudgetConfig};
+use crate::nonce::{get_nonce, DurableNonce};
+use crate::offline::PartiallySignedTransaction;
+use crate::pda::{
+    get_multisig_pda, get_proposal_pda, get_transaction_pda, get_vault_pda, FORTIS_PROGRAM_ID,
+    TREASURY,
+};
+use crate::proposal_plan::{plan_proposals, ProposalPlan};
+use crate::simulation::{simulate_proposal, VaultSimulation};
+use crate::state::{
+    Multisig, MultisigCreateAccounts, MultisigCreateArgs, Proposal, ProposalAccountsCloseAccounts,
+    ProposalApproveAccounts, ProposalApproveArgs, ProposalCreateAccounts, ProposalExecuteAccounts,
+    VaultTransaction, VaultTransactionMessage,
+};
+use crate::transaction_size::transaction_size;
+use crate::ClientResult;
+
+/// High-level client for the Fortis program.
+///
+/// Every operation derives the PDAs it needs, fetches the on-chain state it depends on,
+/// then builds, signs, sends and confirms the transaction.
+pub struct FortisClient {
+    rpc_client: RpcClient,
+    program_id: Pubkey,
+    treasury: Pubkey,
+    commitment: CommitmentConfig,
+    durable_nonce: Option<DurableNonce>,
+}
+
+/// Result of `FortisClient::create_multisig`.
+pub struct CreatedMultisig {
+    pub signature: Signature,
+    pub multisig: Pubkey,
+    pub vault: Pubkey,
+}
+
+/// Result of `FortisClient::propose`.
+pub struct CreatedProposal {
+    pub signature: Signature,
+    pub transaction_index: u64,
+    pub proposal: Pubkey,
+    pub transaction: Pubkey,
+}
+
+impl FortisClient {
+    /// Creates a client for `rpc_url` using the default program id and treasury.
+    pub fn new(rpc_url: String, commitment: CommitmentConfig) -> Self {
+        Self::from_rpc_client(RpcClient::new_with_commitment(rpc_url, commitment))
+    }
+
+    /// Creates a client on top of an existing `RpcClient`, using its commitment.
+    pub fn from_rpc_client(rpc_client: RpcClient) -> Self {
+        let commitment = rpc_client.commitment();
+        Self {
+            rpc_client,
+            program_id: FORTIS_PROGRAM_ID,
+            treasury: TREASURY,
+            commitment,
+            durable_nonce: None,
+        }
+    }
+
+    pub fn with_program_id(mut self, program_id: Pubkey) -> Self {
+        self.program_id = program_id;
+        self
+    }
+
+    /// Makes every transaction built by this client use `durable_nonce` instead of a recent
+    /// blockhash. Its authority must be among the signers of each transaction sent.
+    pub fn with_durable_nonce(mut self, durable_nonce: DurableNonce) -> Self {
+        self.durable_nonce = Some(durable_nonce);
+        self
+    }
+
+    pub fn with_treasury(mut self, treasury: Pubkey) -> Self {
+        self.treasury = treasury;
+        self
+    }
+
+    pub fn rpc_client(&self) -> &RpcClient {
+        &self.rpc_client
+    }
+
+    pub fn program_id(&self) -> Pubkey {
+        self.program_id
+    }
+
+    pub fn treasury(&self) -> Pubkey {
+        self.treasury
+    }
+
+    pub fn commitment(&self) -> CommitmentConfig {
+        self.commitment
+    }
+
+    pub fn durable_nonce(&self) -> Option<DurableNonce> {
+        self.durable_nonce
+    }
+
+    pub async fn get_multisig(&self, multisig_key: &Pubkey) -> ClientResult<Multisig> {
+        get_multisig(&self.rpc_client, multisig_key, Some(self.program_id)).await
+    }
+
+    pub async fn get_proposal(
+        &self,
+        multisig_key: &Pubkey,
+        transaction_index: u64,
+    ) -> ClientResult<Proposal> {
+        let proposal_key =
+            get_proposal_pda(multisig_key, transaction_index, Some(&self.program_id)).0;
+        get_proposal(&self.rpc_client, &proposal_key, Some(self.program_id)).await
+    }
+
+    pub async fn get_transaction(
+        &self,
+        multisig_key: &Pubkey,
+        transaction_index: u64,
+    ) -> ClientResult<VaultTransaction> {
+        let transaction_key =
+            get_transaction_pda(multisig_key, transaction_index, Some(&self.program_id)).0;
+        get_transaction(&self.rpc_client, &transaction_key, Some(self.program_id)).await
+    }
+
+    /// Simulates the transaction of the proposal at `transaction_index` as the vault would run it.
+    pub async fn simulate_proposal(
+        &self,
+        multisig_key: &Pubkey,
+        transaction_index: u64,
+    ) -> ClientResult<VaultSimulation> {
+        simulate_proposal(
+            &self.rpc_client,
+            multisig_key,
+            transaction_index,
+            Some(self.program_id),
+        )
+        .await
+    }
+
+    /// Creates a new multisig seeded by `create_key`, paid for by `creator`.
+    pub async fn create_multisig(
+        &self,
+        creator: &dyn Signer,
+        create_key: &dyn Signer,
+        args: MultisigCreateArgs,
+    ) -> ClientResult<CreatedMultisig> {
+        let (instruction, multisig, vault) = create_multisig_instruction(
+            &self.program_id,
+            &self.treasury,
+            &creator.pubkey(),
+            &create_key.pubkey(),
+            args,
+        );
+
+        let signature = self
+            .send(creator, &[instruction], &[creator, create_key], &[])
+            .await?;
+
+        Ok(CreatedMultisig {
+            signature,
+            multisig,
+            vault,
+        })
+    }
+
+    /// Proposes `instructions` to be executed by the multisig vault at the next transaction index.
+    ///
+    /// The instructions may reference the first `num_ephemeral_signers` ephemeral signers of the
+    /// transaction, see `pda::ephemeral_signers`.
+    pub async fn propose(
+        &self,
+        multisig_key: &Pubkey,
+        creator: &dyn Signer,
+        instructions: &[Instruction],
+        address_lookup_table_accounts: &[AddressLookupTableAccount],
+        num_ephemeral_signers: u8,
+        voting_deadline: i64,
+    ) -> ClientResult<CreatedProposal> {
+        let multisig = self.get_multisig(multisig_key).await?;
+        let transaction_index = multisig.transaction_index + 1;
+
+        let (instruction, proposal, transaction) = propose_instruction(
+            &self.program_id,
+            multisig_key,
+            transaction_index,
+            &creator.pubkey(),
+            instructions,
+            address_lookup_table_accounts,
+            num_ephemeral_signers,
+            voting_deadline,
+        )?;
+
+        let signature = self.send(creator, &[instruction], &[creator], &[]).await?;
+
+        Ok(CreatedProposal {
+            signature,
+            transaction_index,
+            proposal,
+            transaction,
+        })
+    }
+
+    /// Splits `instructions` into proposals that fit in a packet, starting at the next
+    /// transaction index. See `proposal_plan::plan_proposals`.
+    pub async fn plan_proposals(
+        &self,
+        multisig_key: &Pubkey,
+        creator: &Pubkey,
+        instructions: Vec<Instruction>,
+        address_lookup_table_accounts: &[AddressLookupTableAccount],
+    ) -> ClientResult<Vec<ProposalPlan>> {
+        let multisig = self.get_multisig(multisig_key).await?;
+
+        plan_proposals(
+            multisig_key,
+            creator,
+            multisig.transaction_index + 1,
+            instructions,
+            address_lookup_table_accounts,
+            Some(self.program_id),
+        )
+    }
+
+    /// Proposes one chunk of `plan_proposals`. Plans must be proposed in order.
+    pub async fn propose_plan(
+        &self,
+        multisig_key: &Pubkey,
+        creator: &dyn Signer,
+        plan: &ProposalPlan,
+        voting_deadline: i64,
+    ) -> ClientResult<Signature> {
+        let instruction = plan.proposal_create(
+            multisig_key,
+            &creator.pubkey(),
+            voting_deadline,
+            Some(self.program_id),
+        )?;
+
+        self.send(creator, &[instruction], &[creator], &[]).await
+    }
+
+    /// Approves the proposal at `transaction_index` on behalf of `member`.
+    pub async fn approve(
+        &self,
+        multisig_key: &Pubkey,
+        transaction_index: u64,
+        member: &dyn Signer,
+    ) -> ClientResult<Signature> {
+        let instruction = approve_instruction(
+            &self.program_id,
+            multisig_key,
+            transaction_index,
+            &member.pubkey(),
+        );
+
+        self.send(member, &[instruction], &[member], &[]).await
+    }
+
+    /// Executes the approved proposal at `transaction_index` on behalf of `member`.
+    ///
+    /// The address lookup tables referenced by the stored message are fetched automatically.
+    pub async fn execute(
+        &self,
+        multisig_key: &Pubkey,
+        transaction_index: u64,
+        member: &dyn Signer,
+    ) -> ClientResult<Signature> {
+        self.execute_with_compute_budget(
+            multisig_key,
+            transaction_index,
+            member,
+            &ComputeBudgetConfig::default(),
+        )
+        .await
+    }
+
+    /// Like `execute`, prepending the `ComputeBudget` instructions resolved from `compute_budget`.
+    ///
+    /// Percentile prices are computed from the fees paid on the writable accounts of the execution.
+    pub async fn execute_with_compute_budget(
+        &self,
+        multisig_key: &Pubkey,
+        transaction_index: u64,
+        member: &dyn Signer,
+        compute_budget: &ComputeBudgetConfig,
+    ) -> ClientResult<Signature> {
+        let vault_transaction = self
+            .get_transaction(multisig_key, transaction_index)
+            .await?;
+        let address_lookup_table_accounts =
+            get_address_lookup_tables(&self.rpc_client, &vault_transaction.message).await?;
+
+        let instruction = execute_instruction(
+            &self.program_id,
+            multisig_key,
+            &member.pubkey(),
+            &vault_transaction,
+            &address_lookup_table_accounts,
+        )?;
+
+        let mut instructions = resolve_compute_budget(
+            &self.rpc_client,
+            compute_budget,
+            &member.pubkey(),
+            std::slice::from_ref(&instruction),
+            &address_lookup_table_accounts,
+            self.durable_nonce.as_ref(),
+        )
+        .await?;
+        instructions.push(instruction);
+
+        self.send(
+            member,
+            &instructions,
+            &[member],
+            &address_lookup_table_accounts,
+        )
+        .await
+    }
+
+    /// Closes the proposal and transaction accounts at `transaction_index`,
+    /// returning their rent to `rent_collector`.
+    pub async fn close(
+        &self,
+        multisig_key: &Pubkey,
+        transaction_index: u64,
+        rent_collector: &dyn Signer,
+    ) -> ClientResult<Signature> {
+        let instruction = close_instruction(
+            &self.program_id,
+            multisig_key,
+            transaction_index,
+            &rent_collector.pubkey(),
+        );
+
+        self.send(rent_collector, &[instruction], &[rent_collector], &[])
+            .await
+    }
+
+    /// Builds an unsigned transaction paid by `payer` for offline signing, on the latest
+    /// blockhash or on the durable nonce of the client.
+    pub async fn prepare(
+        &self,
+        payer: &Pubkey,
+        instructions: &[Instruction],
+        address_lookup_table_accounts: &[AddressLookupTableAccount],
+    ) -> ClientResult<PartiallySignedTransaction> {
+        let instructions = with_advance_nonce(self.durable_nonce.as_ref(), instructions);
+        let blockhash = self.blockhash().await?;
+        PartiallySignedTransaction::new(
+            payer,
+            &instructions,
+            address_lookup_table_accounts,
+            blockhash,
+        )
+    }
+
+    /// Offline counterpart of `create_multisig`, returning the unsigned transaction with the
+    /// multisig and vault PDAs. Both `creator` and `create_key` must sign it.
+    pub async fn prepare_create_multisig(
+        &self,
+        creator: &Pubkey,
+        create_key: &Pubkey,
+        args: MultisigCreateArgs,
+    ) -> ClientResult<(PartiallySignedTransaction, Pubkey, Pubkey)> {
+        let (instruction, multisig, vault) = create_multisig_instruction(
+            &self.program_id,
+            &self.treasury,
+            creator,
+            create_key,
+            args,
+        );
+
+        let transaction = self.prepare(creator, &[instruction], &[]).await?;
+        Ok((transaction, multisig, vault))
+    }
+
+    /// Offline counterpart of `propose`, returning the unsigned transaction and its transaction index.
+    pub async fn prepare_propose(
+        &self,
+        multisig_key: &Pubkey,
+        creator: &Pubkey,
+        instructions: &[Instruction],
+        address_lookup_table_accounts: &[AddressLookupTableAccount],
+        num_ephemeral_signers: u8,
+        voting_deadline: i64,
+    ) -> ClientResult<(PartiallySignedTransaction, u64)> {
+        let multisig = self.get_multisig(multisig_key).await?;
+        let transaction_index = multisig.transaction_index + 1;
+
+        let (instruction, _, _) = propose_instruction(
+            &self.program_id,
+            multisig_key,
+            transaction_index,
+            creator,
+            instructions,
+            address_lookup_table_accounts,
+            num_ephemeral_signers,
+            voting_deadline,
+        )?;
+
+        let transaction = self.prepare(creator, &[instruction], &[]).await?;
+        Ok((transaction, transaction_index))
+    }
+
+    /// Offline counterpart of `approve`.
+    pub async fn prepare_approve(
+        &self,
+        multisig_key: &Pubkey,
+        transaction_index: u64,
+        member: &Pubkey,
+    ) -> ClientResult<PartiallySignedTransaction> {
+        let instruction =
+            approve_instruction(&self.program_id, multisig_key, transaction_index, member);
+
+        self.prepare(member, &[instruction], &[]).await
+    }
+
+    /// Offline counterpart of `execute`.
+    pub async fn prepare_execute(
+        &self,
+        multisig_key: &Pubkey,
+        transaction_index: u64,
+        member: &Pubkey,
+    ) -> ClientResult<PartiallySignedTransaction> {
+        let vault_transaction = self
+            .get_transaction(multisig_key, transaction_index)
+            .await?;
+        let address_lookup_table_accounts =
+            get_address_lookup_tables(&self.rpc_client, &vault_transaction.message).await?;
+
+        let instruction = execute_instruction(
+            &self.program_id,
+            multisig_key,
+            member,
+            &vault_transaction,
+            &address_lookup_table_accounts,
+        )?;
+
+        self.prepare(member, &[instruction], &address_lookup_table_accounts)
+            .await
+    }
+
+    /// Offline counterpart of `close`.
+    pub async fn prepare_close(
+        &self,
+        multisig_key: &Pubkey,
+        transaction_index: u64,
+        rent_collector: &Pubkey,
+    ) -> ClientResult<PartiallySignedTransaction> {
+        let instruction = close_instruction(
+            &self.program_id,
+            multisig_key,
+            transaction_index,
+            rent_collector,
+        );
+
+        self.prepare(rent_collector, &[instruction], &[]).await
+    }
+
+    /// Sends a transaction signed offline once every required signature is present.
+    pub async fn submit(&self, transaction: PartiallySignedTransaction) -> ClientResult<Signature> {
+        let transaction = transaction.into_transaction()?;
+
+        Ok(self
+            .rpc_client
+            .send_and_confirm_transaction(&transaction)
+            .await?)
+    }
+
+    /// The current nonce when a durable nonce is set, the latest blockhash otherwise.
+    async fn blockhash(&self) -> ClientResult<Hash> {
+        match &self.durable_nonce {
+            Some(durable_nonce) => {
+                let info = get_nonce(&self.rpc_client, &durable_nonce.account).await?;
+                durable_nonce.blockhash(&info)
+            }
+            None => Ok(self.rpc_client.get_latest_blockhash().await?),
+        }
+    }
+
+    /// Signs `instructions` with a fresh blockhash or the durable nonce, sends them and waits for confirmation.
+    ///
+    /// Fails with `TransactionTooLarge` without sending when the transaction exceeds the packet size.
+    async fn send(
+        &self,
+        payer: &dyn Signer,
+        instructions: &[Instruction],
+        signers: &[&dyn Signer],
+        address_lookup_table_accounts: &[AddressLookupTableAccount],
+    ) -> ClientResult<Signature> {
+        let instructions = sendable_instructions(
+            &payer.pubkey(),
+            self.durable_nonce.as_ref(),
+            instructions,
+            address_lookup_table_accounts,
+        )?;
+
+        let blockhash = self.blockhash().await?;
+        let transaction = compile_transaction(
+            &payer.pubkey(),
+            &instructions,
+            signers,
+            address_lookup_table_accounts,
+            blockhash,
+        )?;
+
+        Ok(self
+            .rpc_client
+            .send_and_confirm_transaction(&transaction)
+            .await?)
+    }
+}
+
+/// `instructions`, preceded by `advance_nonce_account` when `durable_nonce` is set.
+pub(crate) fn with_advance_nonce(
+    durable_nonce: Option<&DurableNonce>,
+    instructions: &[Instruction],
+) -> Vec<Instruction> {
+    match durable_nonce {
+        Some(durable_nonce) => durable_nonce.with_advance_instruction(instructions),
+        None => instructions.to_vec(),
+    }
+}
+
+/// `with_advance_nonce`, failing with `TransactionTooLarge` before any RPC call when the
+/// transaction paid by `payer` would exceed the packet size.
+pub(crate) fn sendable_instructions(
+    payer: &Pubkey,
+    durable_nonce: Option<&DurableNonce>,
+    instructions: &[Instruction],
+    address_lookup_table_accounts: &[AddressLookupTableAccount],
+) -> ClientResult<Vec<Instruction>> {
+    let instructions = with_advance_nonce(durable_nonce, instructions);
+    transaction_size(payer, &instructions, address_lookup_table_accounts)?.check()?;
+    Ok(instructions)
+}
+
+/// Builds the `multisig_create` instruction, returning it with the multisig and vault PDAs.
+pub(crate) fn create_multisig_instruction(
+    program_id: &Pubkey,
+    treasury: &Pubkey,
+    creator: &Pubkey,
+    create_key: &Pubkey,
+    args: MultisigCreateArgs,
+) -> (Instruction, Pubkey, Pubkey) {
+    let multisig = get_multisig_pda(create_key, Some(program_id)).0;
+    let vault = get_vault_pda(&multisig, Some(program_id)).0;
+
+    let instruction = multisig_create(
+        MultisigCreateAccounts {
+            treasury: *treasury,
+            multisig,
+            create_key: *create_key,
+            creator: *creator,
+            system_program: solana_system_interface::program::ID,
+        },
+        args,
+        Some(*program_id),
+    );
+
+    (instruction, multisig, vault)
+}
+
+/// Compiles `instructions` into a vault message and builds the `proposal_create` instruction,
+/// returning it with the proposal and transaction PDAs.
+///
+/// Fails if one of the `num_ephemeral_signers` declared ephemeral signers is not referenced.
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn propose_instruction(
+    program_id: &Pubkey,
+    multisig: &Pubkey,
+    transaction_index: u64,
+    creator: &Pubkey,
+    instructions: &[Instruction],
+    address_lookup_table_accounts: &[AddressLookupTableAccount],
+    num_ephemeral_signers: u8,
+    voting_deadline: i64,
+) -> ClientResult<(Instruction, Pubkey, Pubkey)> {
+    let vault = get_vault_pda(multisig, Some(program_id)).0;
+    let proposal = get_proposal_pda(multisig, transaction_index, Some(program_id)).0;
+    let transaction = get_transaction_pda(multisig, transaction_index, Some(program_id)).0;
+
+    let message =
+        VaultTransactionMessage::try_compile(&vault, instructions, address_lookup_table_accounts)?;
+
+    let instruction = proposal_create(
+        ProposalCreateAccounts {
+            multisig: *multisig,
+            trasaction: transaction,
+            creator: *creator,
+            proposal,
+            system_program: solana_system_interface::program::ID,
+        },
+        num_ephemeral_signers,
+        &message,
+        voting_deadline,
+        Some(*program_id),
+    )?;
+
+    Ok((instruction, proposal, transaction))
+}
+
+pub(crate) fn approve_instruction(
+    program_id: &Pubkey,
+    multisig: &Pubkey,
+    transaction_index: u64,
+    member: &Pubkey,
+) -> Instruction {
+    proposal_approve(
+        ProposalApproveAccounts {
+            multisig: *multisig,
+            proposal: get_proposal_pda(multisig, transaction_index, Some(program_id)).0,
+            member: *member,
+        },
+        ProposalApproveArgs {},
+        Some(*program_id),
+    )
+}
+
+pub(crate) fn execute_instruction(
+    program_id: &Pubkey,
+    multisig: &Pubkey,
+    member: &Pubkey,
+    vault_transaction: &VaultTransaction,
+    address_lookup_table_accounts: &[AddressLookupTableAccount],
+) -> ClientResult<Instruction> {
+    let transaction_index = vault_transaction.index;
+    let accounts = ProposalExecuteAccounts {
+        multisig: *multisig,
+        proposal: get_proposal_pda(multisig, transaction_index, Some(program_id)).0,
+        transaction: get_transaction_pda(multisig, transaction_index, Some(program_id)).0,
+        member: *member,
+    };
+
+    proposal_execute_from_transaction(
+        vault_transaction,
+        accounts,
+        address_lookup_table_accounts,
+        Some(*program_id),
+    )
+}
+
+pub(crate) fn close_instruction(
+    program_id: &Pubkey,
+    multisig: &Pubkey,
+    transaction_index: u64,
+    rent_collector: &Pubkey,
+) -> Instruction {
+    proposal_accounts_close(
+        ProposalAccountsCloseAccounts {
+            multisig: *multisig,
+            proposal: get_proposal_pda(multisig, transaction_index, Some(program_id)).0,
+            transaction: get_transaction_pda(multisig, transaction_index, Some(program_id)).0,
+            rent_collector: *rent_collector,
+            system_program: solana_system_interface::program::ID,
+        },
+        Some(*program_id),
+    )
+}
+
+/// Compiles and signs a transaction: legacy when no lookup tables are given, v0 otherwise.
+pub(crate) fn compile_transaction(
+    payer: &Pubkey,
+    instructions: &[Instruction],
+    signers: &[&dyn Signer],
+    address_lookup_table_accounts: &[AddressLookupTableAccount],
+    blockhash: Hash,
+) -> ClientResult<VersionedTransaction> {
+    let message = compile_message(
+        payer,
+        instructions,
+        address_lookup_table_accounts,
+        blockhash,
+    )?;
+
+    Ok(VersionedTransaction::try_new(message, signers)?)
+}
+
+/// Compiles the message of a transaction: legacy when no lookup tables are given, v0 otherwise.
+pub(crate) fn compile_message(
+    payer: &Pubkey,
+    instructions: &[Instruction],
+    address_lookup_table_accounts: &[AddressLookupTableAccount],
+    blockhash: Hash,
+) -> ClientResult<VersionedMessage> {
+    let message = if address_lookup_table_accounts.is_empty() {
+        VersionedMessage::Legacy(Message::new_with_blockhash(
+            instructions,
+            Some(payer),
+            &blockhash,
+        ))
+    } else {
+        VersionedMessage::V0(v0::Message::try_compile(
+            payer,
+            instructions,
+            address_lookup_table_accounts,
+            blockhash,
+        )?)
+    };
+
+    Ok(message)
+}
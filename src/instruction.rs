@@ -0,0 +1,75 @@
+use crate::error::ClientError;
+use crate::state::{
+    MultisigCreateArgs, ProposalApproveArgs, ProposalCreateArgs, ProposallExecuteArgs,
+};
+use crate::ClientResult;
+
+/// Instruction discriminators, written as the first byte of the instruction data.
+pub const MULTISIG_CREATE: u8 = 0;
+pub const PROPOSAL_CREATE: u8 = 1;
+pub const PROPOSAL_APPROVE: u8 = 2;
+pub const PROPOSAL_EXECUTE: u8 = 3;
+pub const PROPOSAL_ACCOUNTS_CLOSE: u8 = 4;
+
+/// The instructions of the Fortis program with their typed arguments.
+///
+/// `to_vec` produces the instruction data expected by the program and
+/// `try_from_slice` parses instruction data seen on chain back.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FortisInstruction {
+    MultisigCreate(MultisigCreateArgs),
+    ProposalCreate(ProposalCreateArgs),
+    ProposalApprove(ProposalApproveArgs),
+    ProposalExecute(ProposallExecuteArgs),
+    ProposalAccountsClose,
+}
+
+impl FortisInstruction {
+    pub fn discriminator(&self) -> u8 {
+        match self {
+            FortisInstruction::MultisigCreate(_) => MULTISIG_CREATE,
+            FortisInstruction::ProposalCreate(_) => PROPOSAL_CREATE,
+            FortisInstruction::ProposalApprove(_) => PROPOSAL_APPROVE,
+            FortisInstruction::ProposalExecute(_) => PROPOSAL_EXECUTE,
+            FortisInstruction::ProposalAccountsClose => PROPOSAL_ACCOUNTS_CLOSE,
+        }
+    }
+
+    /// Serializes the instruction into instruction data.
+    pub fn to_vec(&self) -> Vec<u8> {
+        let mut data = vec![self.discriminator()];
+        // Writing into a `Vec` cannot fail.
+        let encoded = match self {
+            FortisInstruction::MultisigCreate(args) => borsh::to_writer(&mut data, args),
+            FortisInstruction::ProposalCreate(args) => borsh::to_writer(&mut data, args),
+            FortisInstruction::ProposalApprove(args) => borsh::to_writer(&mut data, args),
+            FortisInstruction::ProposalExecute(args) => borsh::to_writer(&mut data, args),
+            FortisInstruction::ProposalAccountsClose => Ok(()),
+        };
+        encoded.expect("borsh serialization into a Vec is infallible");
+        data
+    }
+
+    /// Parses instruction data. Trailing bytes after the arguments are rejected.
+    pub fn try_from_slice(data: &[u8]) -> ClientResult<Self> {
+        let (&discriminator, args) = data.split_first().ok_or_else(|| {
+            ClientError::InvalidInstructionData("instruction data is empty".to_string())
+        })?;
+
+        match discriminator {
+            MULTISIG_CREATE => Ok(FortisInstruction::MultisigCreate(decode_args(args)?)),
+            PROPOSAL_CREATE => Ok(FortisInstruction::ProposalCreate(decode_args(args)?)),
+            PROPOSAL_APPROVE => Ok(FortisInstruction::ProposalApprove(decode_args(args)?)),
+            PROPOSAL_EXECUTE => Ok(FortisInstruction::ProposalExecute(decode_args(args)?)),
+            PROPOSAL_ACCOUNTS_CLOSE => {
+                decode_args::<()>(args)?;
+                Ok(FortisInstruction::ProposalAccountsClose)
+            }
+            other => Err(ClientError::UnknownInstruction(other)),
+        }
+    }
+}
+
+fn decode_args<T: borsh::BorshDeserialize>(args: &[u8]) -> ClientResult<T> {
+    borsh::from_slice(args).map_err(|err| ClientError::InvalidInstructionData(err.to_string()))
+}
@@ -1,10 +1,35 @@
 use crate::pda::get_ephemeral_signer_pda;
+pub use fortis_client::FortisClient;
 pub use solana_client;
+#[cfg(feature = "blocking")]
+pub mod blocking;
 pub mod client;
+pub mod compute_budget;
+#[cfg(feature = "serde")]
+pub mod envelope;
+pub mod explain;
+pub mod fortis_client;
+pub mod instruction;
+pub mod lint;
+pub mod lookup_table;
+pub mod nonce;
+pub mod offline;
 pub mod pda;
+#[cfg(feature = "policy")]
+pub mod policy;
+pub mod proposal_plan;
+#[cfg(feature = "serde")]
+mod serde_utils;
+pub mod simulation;
+pub mod transaction_size;
 pub mod vault_transaction;
+pub mod watch;
 
 pub mod error {
+    use crate::state::AccountType;
+    use solana_message::CompileError;
+    use solana_sdk::pubkey::Pubkey;
+    use solana_signer::SignerError;
     use thiserror::Error;
 
     #[derive(Debug, Error)]
@@ -13,6 +38,95 @@ pub mod error {
         Client(#[from] solana_client::client_error::ClientError),
         #[error("Failed to deserialize account data")]
         DeserializationError,
+        #[error("Invalid {account} account data length: expected {expected} bytes, got {actual}")]
+        InvalidAccountDataLength {
+            account: AccountType,
+            expected: usize,
+            actual: usize,
+        },
+        #[error("Failed to decode {account} account at offset {offset}: {message}")]
+        AccountDecodeError {
+            account: AccountType,
+            offset: usize,
+            message: String,
+        },
+        #[error("Failed to serialize instruction data: {0}")]
+        SerializationError(String),
+        #[error("Account {address} is owned by {actual}, expected {expected}")]
+        WrongAccountOwner {
+            address: Pubkey,
+            expected: Pubkey,
+            actual: Pubkey,
+        },
+        #[error("Account {address} is not a {expected} account")]
+        WrongAccountType {
+            address: Pubkey,
+            expected: AccountType,
+        },
+        #[error("Address lookup table {0} not found")]
+        LookupTableNotFound(Pubkey),
+        #[error("Address lookup table {0} could not be decoded")]
+        InvalidLookupTable(Pubkey),
+        #[error("Address lookup table {0} is deactivated")]
+        LookupTableDeactivated(Pubkey),
+        #[error(
+            "Index {index} is out of range for address lookup table {table} with {len} addresses"
+        )]
+        LookupTableIndexOutOfRange {
+            table: Pubkey,
+            index: u8,
+            len: usize,
+        },
+        #[error("{count} addresses do not fit in one address lookup table (max {max})")]
+        TooManyLookupTableAddresses { count: usize, max: usize },
+        #[error("Transaction is {size} bytes, over the {limit} bytes limit")]
+        TransactionTooLarge { size: usize, limit: usize },
+        #[error("Simulation failed: {0}")]
+        SimulationFailed(String),
+        #[error("Invalid vault transaction envelope: {0}")]
+        InvalidEnvelope(String),
+        #[error("Unsupported vault transaction envelope version: {0}")]
+        UnsupportedEnvelopeVersion(u32),
+        #[error("{0} is not a required signer of this transaction")]
+        UnexpectedSigner(Pubkey),
+        #[error("Invalid signature from {0}")]
+        InvalidSignature(Pubkey),
+        #[error("Missing signatures from {0:?}")]
+        MissingSignatures(Vec<Pubkey>),
+        #[error("Partially signed transactions carry different messages")]
+        MessageMismatch,
+        #[error("Failed to decode transaction: {0}")]
+        TransactionDecodeError(String),
+        #[error("Invalid nonce account {address}: {message}")]
+        InvalidNonceAccount { address: Pubkey, message: String },
+        #[error("Nonce account {account} has authority {actual}, expected {expected}")]
+        NonceAuthorityMismatch {
+            account: Pubkey,
+            expected: Pubkey,
+            actual: Pubkey,
+        },
+        #[error("Ephemeral signer {index} ({signer}) is not used by the transaction")]
+        UnusedEphemeralSigner { index: u8, signer: Pubkey },
+        #[error("Signer {0} is neither the vault nor an ephemeral signer of the transaction")]
+        UnknownSigner(Pubkey),
+        #[error(transparent)]
+        Pubsub(#[from] solana_client::pubsub_client::PubsubClientError),
+        #[error("Account subscriptions were closed by the server")]
+        SubscriptionClosed,
+        #[error("Account {0} was closed")]
+        AccountClosed(Pubkey),
+        #[error("Invalid policy: {0}")]
+        InvalidPolicy(String),
+        #[error("RPC returned account {0} without a decodable encoding or its size")]
+        IncompleteRpcAccount(Pubkey),
+        #[error("Unknown instruction discriminator: {0}")]
+        UnknownInstruction(u8),
+        #[error("Invalid instruction data: {0}")]
+        InvalidInstructionData(String),
+        #[error(transparent)]
+        Compile(#[from] CompileError),
+        #[error(transparent)]
+        Signer(#[from] SignerError),
         #[error("Invalid AddressLookupTableAccount")]
         InvalidAddressLookupTableAccount,
         #[error("Invalid TransactionMessage")]
@@ -23,7 +137,13 @@ pub mod error {
 pub type ClientResult<T> = Result<T, error::ClientError>;
 
 pub mod state {
+    use crate::client::{
+        MULTISIG_HEADER_SIZE, PROPOSAL_HEADER_SIZE, VAULT_TRANSACTION_HEADER_SIZE,
+    };
+    use crate::error::ClientError;
+    use crate::pda::{SEED_MULTISIG, SEED_PREFIX, SEED_PROPOSAL, SEED_TRANSACTION};
     use crate::vault_transaction::compiled_keys::CompiledKeys;
+    use crate::ClientResult;
     pub use solana_client;
 
     use solana_message::AddressLookupTableAccount;
@@ -37,7 +157,104 @@ pub mod state {
         InvalidAddressLookupTableAccount,
         #[error("Invalid TransactionMessage")]
         InvalidTransactionMessage,
+        #[error("Invalid proposal status: {0}")]
+        InvalidProposalStatus(u8),
+    }
+
+    /// The kinds of accounts owned by the Fortis program.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+    pub enum AccountType {
+        Multisig,
+        Proposal,
+        VaultTransaction,
+    }
+    impl std::fmt::Display for AccountType {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            match self {
+                AccountType::Multisig => write!(f, "Multisig"),
+                AccountType::Proposal => write!(f, "Proposal"),
+                AccountType::VaultTransaction => write!(f, "VaultTransaction"),
+            }
+        }
+    }
+
+    /// An account written by the Fortis program.
+    ///
+    /// Fortis accounts carry no discriminator, so the account type is checked by
+    /// re-deriving the PDA from the decoded seeds and bump and comparing it to the fetched address.
+    pub trait FortisAccount: Sized {
+        const ACCOUNT_TYPE: AccountType;
+
+        /// Decodes the account from raw account data.
+        fn try_from_account_data(data: &[u8]) -> ClientResult<Self>;
+
+        /// Returns the PDA this account must live at, or `None` if the seeds are not a valid PDA.
+        fn derive_address(&self, program_id: &Pubkey) -> Option<Pubkey>;
     }
+    impl FortisAccount for Multisig {
+        const ACCOUNT_TYPE: AccountType = AccountType::Multisig;
+
+        fn try_from_account_data(data: &[u8]) -> ClientResult<Self> {
+            Multisig::try_from_account_data(data)
+        }
+
+        fn derive_address(&self, program_id: &Pubkey) -> Option<Pubkey> {
+            Pubkey::create_program_address(
+                &[
+                    SEED_PREFIX,
+                    SEED_MULTISIG,
+                    self.create_key.as_ref(),
+                    &[self.bump],
+                ],
+                program_id,
+            )
+            .ok()
+        }
+    }
+    impl FortisAccount for Proposal {
+        const ACCOUNT_TYPE: AccountType = AccountType::Proposal;
+
+        fn try_from_account_data(data: &[u8]) -> ClientResult<Self> {
+            Proposal::try_from_account_data(data)
+        }
+
+        fn derive_address(&self, program_id: &Pubkey) -> Option<Pubkey> {
+            Pubkey::create_program_address(
+                &[
+                    SEED_PREFIX,
+                    self.multisig.as_ref(),
+                    SEED_TRANSACTION,
+                    &self.transaction_index.to_le_bytes(),
+                    SEED_PROPOSAL,
+                    &[self.bump],
+                ],
+                program_id,
+            )
+            .ok()
+        }
+    }
+    impl FortisAccount for VaultTransaction {
+        const ACCOUNT_TYPE: AccountType = AccountType::VaultTransaction;
+
+        fn try_from_account_data(data: &[u8]) -> ClientResult<Self> {
+            VaultTransaction::try_from_account_data(data)
+        }
+
+        fn derive_address(&self, program_id: &Pubkey) -> Option<Pubkey> {
+            Pubkey::create_program_address(
+                &[
+                    SEED_PREFIX,
+                    self.multisig.as_ref(),
+                    SEED_TRANSACTION,
+                    &self.index.to_le_bytes(),
+                    &[self.bump],
+                ],
+                program_id,
+            )
+            .ok()
+        }
+    }
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
     pub struct MultisigCreateAccounts {
         pub treasury: Pubkey,
         pub multisig: Pubkey,
@@ -56,13 +273,16 @@ pub mod state {
             ]
         }
     }
-    //   #[derive(borsh::BorshSerialize, borsh::BorshDeserialize)]
+    #[derive(Debug, Clone, PartialEq, Eq, borsh::BorshSerialize, borsh::BorshDeserialize)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
     pub struct MultisigCreateArgs {
         /// The number of signatures required to execute a transaction.
         pub threshold: u16,
         ///rent collector
+        #[cfg_attr(feature = "serde", serde(with = "crate::serde_utils::option_pubkey"))]
         pub rent_collector: Option<Pubkey>,
         /// The members of the multisig.
+        #[cfg_attr(feature = "serde", serde(with = "crate::serde_utils::pubkey_vec"))]
         pub members: Vec<Pubkey>,
     }
     impl MultisigCreateArgs {
@@ -95,12 +315,15 @@ pub mod state {
             data
         }
     }
-    #[derive(borsh::BorshSerialize, borsh::BorshDeserialize)]
+    #[derive(Debug, Clone, PartialEq, Eq, borsh::BorshSerialize, borsh::BorshDeserialize)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
     pub struct Multisig {
         /// Key that is used to seed the multisig PDA.
+        #[cfg_attr(feature = "serde", serde(with = "crate::serde_utils::pubkey"))]
         pub create_key: Pubkey,
         /// The address where the rent for the accounts related to executed, rejected, or cancelled
         /// transactions can be reclaimed. If set to `None`, the rent reclamation feature is turned off.
+        #[cfg_attr(feature = "serde", serde(with = "crate::serde_utils::pubkey"))]
         pub rent_collector: Pubkey,
         /// Last transaction index. 0 means no transactions have been created.
         pub transaction_index: u64,
@@ -109,8 +332,28 @@ pub mod state {
         /// Bump for the multisig PDA seed.
         pub bump: u8,
         ///members
+        #[cfg_attr(feature = "serde", serde(with = "crate::serde_utils::pubkey_vec"))]
         pub members: Vec<Pubkey>,
     }
+    impl Multisig {
+        /// Decodes a `Multisig` from raw account data without panicking.
+        ///
+        /// The account is sized exactly for its members, so trailing bytes are rejected.
+        pub fn try_from_account_data(data: &[u8]) -> ClientResult<Self> {
+            let members_len = read_vec_len(AccountType::Multisig, data, MULTISIG_HEADER_SIZE)?;
+            let expected = MULTISIG_HEADER_SIZE + 4 + members_len * 32;
+            if data.len() != expected {
+                return Err(ClientError::InvalidAccountDataLength {
+                    account: AccountType::Multisig,
+                    expected,
+                    actual: data.len(),
+                });
+            }
+            let (multisig, _) = decode_prefix(AccountType::Multisig, data)?;
+            Ok(multisig)
+        }
+    }
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
     pub struct ProposalCreateAccounts {
         pub multisig: Pubkey,
         pub trasaction: Pubkey,
@@ -129,15 +372,26 @@ pub mod state {
             ]
         }
     }
-    #[derive(borsh::BorshSerialize, borsh::BorshDeserialize)]
+    #[derive(Debug, Clone, PartialEq, Eq, borsh::BorshSerialize, borsh::BorshDeserialize)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
     pub struct ProposalCreateArgs {
         pub ephemeral_signers: u8,
         pub voting_deadline: i64, //deadline to vote ,else will be conidersed rejected
+        #[cfg_attr(feature = "serde", serde(with = "crate::serde_utils::base64"))]
         pub transaction_message: Vec<u8>,
     }
-    #[derive(borsh::BorshSerialize, borsh::BorshDeserialize)]
+    impl ProposalCreateArgs {
+        /// Decodes the serialized `VaultTransactionMessage` carried by the instruction.
+        pub fn transaction_message(&self) -> ClientResult<VaultTransactionMessage> {
+            borsh::from_slice(&self.transaction_message)
+                .map_err(|err| ClientError::InvalidInstructionData(err.to_string()))
+        }
+    }
+    #[derive(Debug, Clone, PartialEq, Eq, borsh::BorshSerialize, borsh::BorshDeserialize)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
     pub struct Proposal {
         /// The multisig this belongs to.
+        #[cfg_attr(feature = "serde", serde(with = "crate::serde_utils::pubkey"))]
         pub multisig: Pubkey,
         /// Index of the multisig transaction this proposal is associated with.
         pub transaction_index: u64,
@@ -146,15 +400,156 @@ pub mod state {
         //deadline for voting
         pub deadline: i64,
         /// The status of the transaction.
-        pub status: u8,
+        pub status: ProposalStatus,
         /// PDA bump.
         pub bump: u8,
         /// Keys that have approved/signed.
+        #[cfg_attr(feature = "serde", serde(with = "crate::serde_utils::pubkey_vec"))]
         pub approved: Vec<Pubkey>,
     }
+    impl Proposal {
+        /// Decodes a `Proposal` from raw account data without panicking.
+        ///
+        /// The account is allocated with room for every member's approval, so any
+        /// bytes after the `approved` vec are ignored.
+        pub fn try_from_account_data(data: &[u8]) -> ClientResult<Self> {
+            let approved_len = read_vec_len(AccountType::Proposal, data, PROPOSAL_HEADER_SIZE)?;
+            let expected = PROPOSAL_HEADER_SIZE + 4 + approved_len * 32;
+            if data.len() < expected {
+                return Err(ClientError::InvalidAccountDataLength {
+                    account: AccountType::Proposal,
+                    expected,
+                    actual: data.len(),
+                });
+            }
+            let (proposal, _) = decode_prefix(AccountType::Proposal, &data[..expected])?;
+            Ok(proposal)
+        }
+
+        /// Returns true if the voting deadline has passed at `now` (unix timestamp).
+        pub fn is_expired(&self, now: i64) -> bool {
+            now > self.deadline
+        }
+
+        /// Number of approvals from current members of `multisig`.
+        pub fn approval_count(&self, multisig: &Multisig) -> usize {
+            multisig
+                .members
+                .iter()
+                .filter(|member| self.approved.contains(member))
+                .count()
+        }
+
+        /// Number of approvals still missing to reach the multisig threshold.
+        pub fn remaining_approvals(&self, multisig: &Multisig) -> u16 {
+            let approvals = u16::try_from(self.approval_count(multisig)).unwrap_or(u16::MAX);
+            multisig.threshold.saturating_sub(approvals)
+        }
+
+        /// Members of `multisig` that have not approved the proposal yet.
+        pub fn pending_members(&self, multisig: &Multisig) -> Vec<Pubkey> {
+            multisig
+                .members
+                .iter()
+                .filter(|member| !self.approved.contains(member))
+                .copied()
+                .collect()
+        }
+
+        /// Returns true if the proposal can be executed.
+        ///
+        /// Only `Approved` proposals are executable: an `Active` proposal whose approvals
+        /// already meet the threshold still waits for the program to mark it approved.
+        pub fn is_executable(&self) -> bool {
+            self.status == ProposalStatus::Approved
+        }
+
+        /// Returns true if the proposal and transaction accounts can be closed at `now`,
+        /// i.e. the proposal was executed or its deadline passed without approval.
+        ///
+        /// An `Approved` proposal is never reported closable, even past its deadline: like
+        /// `is_executable`, this assumes the deadline only bounds voting and that an approved
+        /// transaction must be executed before its rent can be reclaimed. Closing it is left
+        /// to the program, which rejects the close if it does not allow it.
+        pub fn is_closable(&self, now: i64) -> bool {
+            match self.status {
+                ProposalStatus::Executed => true,
+                ProposalStatus::Active => self.is_expired(now),
+                ProposalStatus::Approved => false,
+            }
+        }
+    }
+
+    /// Lifecycle status of a `Proposal`, stored on chain as a single byte.
+    ///
+    /// ```plaintext
+    /// Active --(threshold reached)--> Approved --(executed)--> Executed
+    ///    |
+    ///    +--(deadline passed)--> closable
+    /// ```
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+    pub enum ProposalStatus {
+        /// Voting is open.
+        Active,
+        /// The threshold was reached; the transaction can be executed.
+        Approved,
+        /// The transaction was executed.
+        Executed,
+    }
+    impl ProposalStatus {
+        /// Returns true if no further transition is possible.
+        pub fn is_final(&self) -> bool {
+            matches!(self, ProposalStatus::Executed)
+        }
+
+        /// Returns true if the program allows moving from `self` to `next`.
+        pub fn can_transition_to(&self, next: ProposalStatus) -> bool {
+            matches!(
+                (self, next),
+                (ProposalStatus::Active, ProposalStatus::Approved)
+                    | (ProposalStatus::Approved, ProposalStatus::Executed)
+            )
+        }
+    }
+    impl TryFrom<u8> for ProposalStatus {
+        type Error = Error;
 
-    #[derive(borsh::BorshSerialize, borsh::BorshDeserialize)]
+        fn try_from(value: u8) -> Result<Self, Self::Error> {
+            match value {
+                0 => Ok(ProposalStatus::Active),
+                1 => Ok(ProposalStatus::Approved),
+                2 => Ok(ProposalStatus::Executed),
+                other => Err(Error::InvalidProposalStatus(other)),
+            }
+        }
+    }
+    impl From<ProposalStatus> for u8 {
+        fn from(status: ProposalStatus) -> Self {
+            match status {
+                ProposalStatus::Active => 0,
+                ProposalStatus::Approved => 1,
+                ProposalStatus::Executed => 2,
+            }
+        }
+    }
+    impl borsh::BorshSerialize for ProposalStatus {
+        fn serialize<W: std::io::Write>(&self, writer: &mut W) -> std::io::Result<()> {
+            u8::from(*self).serialize(writer)
+        }
+    }
+    impl borsh::BorshDeserialize for ProposalStatus {
+        fn deserialize_reader<R: std::io::Read>(reader: &mut R) -> std::io::Result<Self> {
+            let value = u8::deserialize_reader(reader)?;
+            ProposalStatus::try_from(value)
+                .map_err(|err| std::io::Error::new(std::io::ErrorKind::InvalidData, err))
+        }
+    }
+
+    #[derive(Debug, Clone, PartialEq, Eq, borsh::BorshSerialize, borsh::BorshDeserialize)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
     pub struct ProposalApproveArgs {}
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
     pub struct ProposalApproveAccounts {
         pub multisig: Pubkey,
         pub proposal: Pubkey,
@@ -169,8 +564,10 @@ pub mod state {
             ]
         }
     }
-    #[derive(borsh::BorshSerialize, borsh::BorshDeserialize)]
+    #[derive(Debug, Clone, PartialEq, Eq, borsh::BorshSerialize, borsh::BorshDeserialize)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
     pub struct ProposallExecuteArgs {}
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
     pub struct ProposalExecuteAccounts {
         pub multisig: Pubkey,
         pub proposal: Pubkey,
@@ -187,6 +584,7 @@ pub mod state {
             ]
         }
     }
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
     pub struct ProposalAccountsCloseAccounts {
         pub multisig: Pubkey,
         pub proposal: Pubkey,
@@ -206,7 +604,8 @@ pub mod state {
         }
     }
 
-    #[derive(borsh::BorshSerialize, borsh::BorshDeserialize)]
+    #[derive(Debug, Clone, PartialEq, Eq, borsh::BorshSerialize, borsh::BorshDeserialize)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
     pub struct VaultTransactionMessage {
         /// The number of signer pubkeys in the account_keys vec.
         pub num_signers: u8,
@@ -224,6 +623,7 @@ pub mod state {
         ///  |---writable---|  |---readonly---|  |---writable---|  |---readonly---|
         ///  |------------signers-------------|  |----------non-singers-----------|
         /// ```
+        #[cfg_attr(feature = "serde", serde(with = "crate::serde_utils::pubkey_vec"))]
         pub account_keys: Vec<Pubkey>,
         /// List of address table lookups used to load additional accounts
         /// for this transaction.
@@ -300,28 +700,134 @@ pub mod state {
                 num_writable_non_signers: num_static_keys
                     - header.num_required_signatures
                     - header.num_readonly_unsigned_accounts,
-                account_keys: static_keys.into(),
+                account_keys: static_keys,
                 instructions: instructions
                     .into_iter()
                     .map(|ix| CompiledInstruction {
                         program_id_index: ix.program_id_index,
-                        accounts: ix.accounts.into(),
-                        data: ix.data.into(),
+                        accounts: ix.accounts,
+                        data: ix.data,
                     })
-                    .collect::<Vec<CompiledInstruction>>()
-                    .into(),
+                    .collect::<Vec<CompiledInstruction>>(),
                 address_table_lookups: address_table_lookups
                     .into_iter()
                     .map(|lookup| MessageAddressTableLookup {
                         account_key: lookup.account_key,
-                        writable_indexes: lookup.writable_indexes.into(),
-                        readonly_indexes: lookup.readonly_indexes.into(),
+                        writable_indexes: lookup.writable_indexes,
+                        readonly_indexes: lookup.readonly_indexes,
                     })
-                    .collect::<Vec<MessageAddressTableLookup>>()
-                    .into(),
+                    .collect::<Vec<MessageAddressTableLookup>>(),
             })
         }
 
+        /// Decompiles the message back into the `Instruction`s it will run.
+        ///
+        /// Accounts are resolved in message order: static `account_keys`, then the writable and
+        /// then the readonly addresses loaded from `address_lookup_table_accounts`.
+        ///
+        /// This inverts `try_compile` up to per-key flag merging: the message only stores one
+        /// signer/writable flag pair per account, so each `AccountMeta` comes back with the flags
+        /// of its key across all instructions, and the vault always comes back as a writable
+        /// signer since it is the payer. These merged flags are the ones the program executes with.
+        pub fn decompile(
+            &self,
+            address_lookup_table_accounts: &[AddressLookupTableAccount],
+        ) -> Result<Vec<Instruction>, Error> {
+            let account_metas = self.resolve_account_metas(address_lookup_table_accounts)?;
+
+            self.instructions
+                .iter()
+                .map(|ix| {
+                    let program_id = account_metas
+                        .get(usize::from(ix.program_id_index))
+                        .ok_or(Error::InvalidTransactionMessage)?
+                        .pubkey;
+                    let accounts = ix
+                        .accounts
+                        .iter()
+                        .map(|&index| {
+                            account_metas
+                                .get(usize::from(index))
+                                .cloned()
+                                .ok_or(Error::InvalidTransactionMessage)
+                        })
+                        .collect::<Result<Vec<_>, Error>>()?;
+
+                    Ok(Instruction {
+                        program_id,
+                        accounts,
+                        data: ix.data.clone(),
+                    })
+                })
+                .collect()
+        }
+
+        /// Resolves every account referenced by the message, in the order instructions index them,
+        /// with the signer and writable flags restored from the header counts and lookups.
+        pub fn resolve_account_metas(
+            &self,
+            address_lookup_table_accounts: &[AddressLookupTableAccount],
+        ) -> Result<Vec<AccountMeta>, Error> {
+            let num_signers = usize::from(self.num_signers);
+            if num_signers > self.account_keys.len()
+                || self.num_writable_signers > self.num_signers
+                || usize::from(self.num_writable_non_signers)
+                    > self.account_keys.len() - num_signers
+            {
+                return Err(Error::InvalidTransactionMessage);
+            }
+
+            let static_account_metas =
+                self.account_keys
+                    .iter()
+                    .enumerate()
+                    .map(|(index, &pubkey)| AccountMeta {
+                        pubkey,
+                        is_signer: self.is_signer_index(index),
+                        is_writable: self.is_static_writable_index(index),
+                    });
+
+            let lookup_tables = self
+                .address_table_lookups
+                .iter()
+                .map(|lookup| {
+                    address_lookup_table_accounts
+                        .iter()
+                        .find(|alt| alt.key == lookup.account_key)
+                        .map(|alt| (lookup, alt))
+                        .ok_or(Error::InvalidAddressLookupTableAccount)
+                })
+                .collect::<Result<Vec<_>, Error>>()?;
+
+            let load = |indexes: &[u8], addresses: &[Pubkey], is_writable: bool| {
+                indexes
+                    .iter()
+                    .map(|&index| {
+                        let pubkey = *addresses
+                            .get(usize::from(index))
+                            .ok_or(Error::InvalidAddressLookupTableAccount)?;
+                        Ok(AccountMeta {
+                            pubkey,
+                            is_signer: false,
+                            is_writable,
+                        })
+                    })
+                    .collect::<Result<Vec<_>, Error>>()
+            };
+
+            let mut loaded_writable = Vec::new();
+            let mut loaded_readonly = Vec::new();
+            for (lookup, alt) in lookup_tables {
+                loaded_writable.extend(load(&lookup.writable_indexes, &alt.addresses, true)?);
+                loaded_readonly.extend(load(&lookup.readonly_indexes, &alt.addresses, false)?);
+            }
+
+            Ok(static_account_metas
+                .chain(loaded_writable)
+                .chain(loaded_readonly)
+                .collect())
+        }
+
         pub fn get_accounts_for_execute(
             &self,
             vault_pda: &Pubkey,
@@ -331,7 +837,6 @@ pub mod state {
             program_id: &Pubkey,
         ) -> Result<Vec<AccountMeta>, Error> {
             let ephemeral_signer_pdas: Vec<Pubkey> = (0..num_ephemeral_signers)
-                .into_iter()
                 .map(|ephemeral_signer_index| {
                     crate::get_ephemeral_signer_pda(
                         transaction_pda,
@@ -345,7 +850,7 @@ pub mod state {
             // region: -- address_lookup_tables map --
 
             let address_lookup_tables = address_lookup_table_accounts
-                .into_iter()
+                .iter()
                 .map(|alt| (alt.key, alt))
                 .collect::<std::collections::HashMap<_, _>>();
 
@@ -437,23 +942,30 @@ pub mod state {
             .concat())
         }
     }
-    #[derive(borsh::BorshSerialize, borsh::BorshDeserialize)]
+    #[derive(Debug, Clone, PartialEq, Eq, borsh::BorshSerialize, borsh::BorshDeserialize)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
     pub struct MessageAddressTableLookup {
+        #[cfg_attr(feature = "serde", serde(with = "crate::serde_utils::pubkey"))]
         pub account_key: solana_message::Address,
         pub writable_indexes: Vec<u8>,
         pub readonly_indexes: Vec<u8>,
     }
-    #[derive(borsh::BorshSerialize, borsh::BorshDeserialize)]
+    #[derive(Debug, Clone, PartialEq, Eq, borsh::BorshSerialize, borsh::BorshDeserialize)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
     pub struct CompiledInstruction {
         pub program_id_index: u8,
         pub accounts: Vec<u8>,
+        #[cfg_attr(feature = "serde", serde(with = "crate::serde_utils::base64"))]
         pub data: Vec<u8>,
     }
-    #[derive(borsh::BorshSerialize, borsh::BorshDeserialize)]
+    #[derive(Debug, Clone, PartialEq, Eq, borsh::BorshSerialize, borsh::BorshDeserialize)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
     pub struct VaultTransaction {
         /// The multisig this belongs to.
+        #[cfg_attr(feature = "serde", serde(with = "crate::serde_utils::pubkey"))]
         pub multisig: Pubkey,
         /// Member of the Multisig who submitted the transaction.
+        #[cfg_attr(feature = "serde", serde(with = "crate::serde_utils::pubkey"))]
         pub creator: Pubkey,
         /// Index of this transaction within the multisig.
         pub index: u64,
@@ -465,4 +977,56 @@ pub mod state {
         /// data required for executing the transaction.
         pub message: VaultTransactionMessage,
     }
+    impl VaultTransaction {
+        /// Decodes a `VaultTransaction` from raw account data without panicking.
+        ///
+        /// The account is sized exactly for its message, so trailing bytes are rejected.
+        pub fn try_from_account_data(data: &[u8]) -> ClientResult<Self> {
+            if data.len() < VAULT_TRANSACTION_HEADER_SIZE {
+                return Err(ClientError::InvalidAccountDataLength {
+                    account: AccountType::VaultTransaction,
+                    expected: VAULT_TRANSACTION_HEADER_SIZE,
+                    actual: data.len(),
+                });
+            }
+            let (transaction, consumed) = decode_prefix(AccountType::VaultTransaction, data)?;
+            if consumed != data.len() {
+                return Err(ClientError::InvalidAccountDataLength {
+                    account: AccountType::VaultTransaction,
+                    expected: consumed,
+                    actual: data.len(),
+                });
+            }
+            Ok(transaction)
+        }
+    }
+
+    /// Reads the little-endian `u32` length prefix of the vec that follows a fixed-size header.
+    fn read_vec_len(account: AccountType, data: &[u8], header_size: usize) -> ClientResult<usize> {
+        let len_bytes = data.get(header_size..header_size + 4).ok_or(
+            ClientError::InvalidAccountDataLength {
+                account,
+                expected: header_size + 4,
+                actual: data.len(),
+            },
+        )?;
+        let len = u32::from_le_bytes(len_bytes.try_into().expect("slice length should be 4"));
+        Ok(len as usize)
+    }
+
+    /// Borsh-decodes a `T` from the start of `data`, returning it along with the number of bytes consumed.
+    fn decode_prefix<T: borsh::BorshDeserialize>(
+        account: AccountType,
+        data: &[u8],
+    ) -> ClientResult<(T, usize)> {
+        let mut cursor = data;
+        match T::deserialize(&mut cursor) {
+            Ok(value) => Ok((value, data.len() - cursor.len())),
+            Err(err) => Err(ClientError::AccountDecodeError {
+                account,
+                offset: data.len() - cursor.len(),
+                message: err.to_string(),
+            }),
+        }
+    }
 }
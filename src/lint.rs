@@ -0,0 +1,497 @@
+//! Rule-based checks flagging risky vault transactions before they are approved.
+//!
+//! The linter decodes the message with `explain` and reports each match as a `Finding`
+//! with a severity, the instruction it concerns and an explanation. Rules can be disabled
+//! or have their severity changed through `LintConfig`.
+use std::cmp::Reverse;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_message::AddressLookupTableAccount;
+use solana_sdk::pubkey::Pubkey;
+
+use crate::client::{get_address_lookup_tables, get_multisig, get_transaction};
+use crate::explain::{
+    format_amount, ExplainedInstruction, Explainer, BPF_LOADER_UPGRADEABLE_PROGRAM_ID,
+    SOL_DECIMALS, STAKE_PROGRAM_ID, SYSTEM_PROGRAM_ID, TOKEN_2022_PROGRAM_ID, TOKEN_PROGRAM_ID,
+};
+use crate::pda::{ephemeral_signers, get_transaction_pda, get_vault_pda, FORTIS_PROGRAM_ID};
+use crate::state::{Multisig, VaultTransaction, VaultTransactionMessage};
+use crate::ClientResult;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Severity {
+    Info,
+    Warning,
+    Critical,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Rule {
+    /// A token, program, buffer or stake authority is handed to a key other than the vault
+    /// and `LintConfig::trusted_accounts`, or removed.
+    AuthorityChange,
+    /// An account is closed and its lamports sent to neither the vault nor a member.
+    CloseToNonMember,
+    /// SOL transfers would leave the vault with a balance below rent exemption.
+    VaultBelowRentExemption,
+    /// A program missing from `LintConfig::allowed_programs` is invoked.
+    ProgramNotAllowed,
+    /// A `ComputeBudget` instruction, which has no effect inside a vault transaction.
+    ComputeBudgetInstruction,
+    /// A signer that is neither the vault nor an ephemeral signer: execution would fail.
+    UnexpectedSigner,
+}
+
+impl Rule {
+    pub fn default_severity(&self) -> Severity {
+        match self {
+            Rule::AuthorityChange => Severity::Critical,
+            Rule::CloseToNonMember => Severity::Warning,
+            Rule::VaultBelowRentExemption => Severity::Warning,
+            Rule::ProgramNotAllowed => Severity::Warning,
+            Rule::ComputeBudgetInstruction => Severity::Info,
+            Rule::UnexpectedSigner => Severity::Critical,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Finding {
+    pub rule: Rule,
+    pub severity: Severity,
+    /// Index of the instruction within the vault message.
+    pub instruction_index: usize,
+    pub explanation: String,
+}
+
+/// Which rules run and how severe their findings are.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LintConfig {
+    pub disabled: HashSet<Rule>,
+    pub severities: HashMap<Rule, Severity>,
+    /// Programs the vault may invoke. `None` disables `Rule::ProgramNotAllowed`.
+    pub allowed_programs: Option<Vec<Pubkey>>,
+    /// Accounts, besides the vault, that may receive new authorities. Along with the members,
+    /// they may also receive the lamports of closed accounts.
+    pub trusted_accounts: Vec<Pubkey>,
+}
+
+impl LintConfig {
+    pub fn disable(mut self, rule: Rule) -> Self {
+        self.disabled.insert(rule);
+        self
+    }
+
+    pub fn with_severity(mut self, rule: Rule, severity: Severity) -> Self {
+        self.severities.insert(rule, severity);
+        self
+    }
+
+    pub fn with_allowed_programs(mut self, programs: Vec<Pubkey>) -> Self {
+        self.allowed_programs = Some(programs);
+        self
+    }
+
+    pub fn with_trusted_accounts(mut self, accounts: Vec<Pubkey>) -> Self {
+        self.trusted_accounts = accounts;
+        self
+    }
+
+    pub fn severity(&self, rule: Rule) -> Severity {
+        self.severities
+            .get(&rule)
+            .copied()
+            .unwrap_or_else(|| rule.default_severity())
+    }
+}
+
+/// What the linter knows about the multisig executing the message.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LintContext {
+    pub vault: Pubkey,
+    pub members: Vec<Pubkey>,
+    pub ephemeral_signers: Vec<Pubkey>,
+    /// Current vault balance, `None` skips `Rule::VaultBelowRentExemption`.
+    pub vault_lamports: Option<u64>,
+    pub rent_exempt_minimum: u64,
+}
+
+impl LintContext {
+    /// Context of the transaction at `transaction_index` of `multisig`, without the vault balance.
+    pub fn new(
+        multisig_key: &Pubkey,
+        multisig: &Multisig,
+        transaction_index: u64,
+        num_ephemeral_signers: u8,
+        program_id: Option<Pubkey>,
+    ) -> Self {
+        let program_id = program_id.unwrap_or(FORTIS_PROGRAM_ID);
+        Self {
+            vault: get_vault_pda(multisig_key, Some(&program_id)).0,
+            members: multisig.members.clone(),
+            ephemeral_signers: ephemeral_signers(
+                multisig_key,
+                transaction_index,
+                num_ephemeral_signers,
+                Some(&program_id),
+            ),
+            vault_lamports: None,
+            rent_exempt_minimum: 0,
+        }
+    }
+
+    pub fn with_vault_balance(mut self, lamports: u64, rent_exempt_minimum: u64) -> Self {
+        self.vault_lamports = Some(lamports);
+        self.rent_exempt_minimum = rent_exempt_minimum;
+        self
+    }
+}
+
+/// Runs the enabled rules over vault transaction messages.
+pub struct Linter {
+    config: LintConfig,
+    explainer: Explainer,
+}
+
+impl Linter {
+    pub fn new(config: LintConfig) -> Self {
+        Self {
+            config,
+            explainer: Explainer::new(),
+        }
+    }
+
+    /// Uses `explainer`, with decoders for more programs, instead of the built-in one.
+    pub fn with_explainer(mut self, explainer: Explainer) -> Self {
+        self.explainer = explainer;
+        self
+    }
+
+    /// Findings for `message`, most severe first.
+    pub fn lint(
+        &self,
+        message: &VaultTransactionMessage,
+        address_lookup_table_accounts: &[AddressLookupTableAccount],
+        context: &LintContext,
+    ) -> ClientResult<Vec<Finding>> {
+        let instructions = self
+            .explainer
+            .explain(message, address_lookup_table_accounts)?;
+
+        let mut findings = Vec::new();
+        for instruction in &instructions {
+            self.check_signers(instruction, context, &mut findings);
+            self.check_program(instruction, &mut findings);
+            self.check_authority_change(instruction, context, &mut findings);
+            self.check_close(instruction, context, &mut findings);
+        }
+        self.check_rent_exemption(&instructions, context, &mut findings);
+
+        findings.sort_by_key(|finding| Reverse(finding.severity));
+        Ok(findings)
+    }
+
+    fn report(
+        &self,
+        findings: &mut Vec<Finding>,
+        rule: Rule,
+        instruction_index: usize,
+        explanation: String,
+    ) {
+        if self.config.disabled.contains(&rule) {
+            return;
+        }
+        findings.push(Finding {
+            rule,
+            severity: self.config.severity(rule),
+            instruction_index,
+            explanation,
+        });
+    }
+
+    /// Whether `account` may hold an authority given away by the vault.
+    fn is_trusted_authority(&self, account: &Pubkey, context: &LintContext) -> bool {
+        *account == context.vault || self.config.trusted_accounts.contains(account)
+    }
+
+    /// Whether `account` may receive the lamports of an account closed by the vault.
+    fn is_trusted_destination(&self, account: &Pubkey, context: &LintContext) -> bool {
+        self.is_trusted_authority(account, context) || context.members.contains(account)
+    }
+
+    fn check_signers(
+        &self,
+        instruction: &ExplainedInstruction,
+        context: &LintContext,
+        findings: &mut Vec<Finding>,
+    ) {
+        let mut reported = Vec::new();
+        for account in &instruction.instruction.accounts {
+            let signer = account.pubkey;
+            if !account.is_signer
+                || signer == context.vault
+                || context.ephemeral_signers.contains(&signer)
+                || reported.contains(&signer)
+            {
+                continue;
+            }
+            reported.push(signer);
+            self.report(
+                findings,
+                Rule::UnexpectedSigner,
+                instruction.index,
+                format!(
+                    "{signer} must sign, but only the vault and the ephemeral signers \
+                     can sign a vault transaction: execution will fail"
+                ),
+            );
+        }
+    }
+
+    fn check_program(&self, instruction: &ExplainedInstruction, findings: &mut Vec<Finding>) {
+        let program_id = instruction.instruction.program_id;
+        if program_id == solana_compute_budget_interface::ID {
+            self.report(
+                findings,
+                Rule::ComputeBudgetInstruction,
+                instruction.index,
+                "compute budget instructions only apply to the outer transaction and have \
+                 no effect inside a vault transaction"
+                    .to_string(),
+            );
+        }
+        if let Some(allowed) = &self.config.allowed_programs {
+            if !allowed.contains(&program_id) {
+                self.report(
+                    findings,
+                    Rule::ProgramNotAllowed,
+                    instruction.index,
+                    format!("program {program_id} is not in the allowlist"),
+                );
+            }
+        }
+    }
+
+    fn check_authority_change(
+        &self,
+        instruction: &ExplainedInstruction,
+        context: &LintContext,
+        findings: &mut Vec<Finding>,
+    ) {
+        let Some(decoded) = &instruction.decoded else {
+            return;
+        };
+        let program_id = instruction.instruction.program_id;
+        let is_authority_change = match decoded.name.as_str() {
+            "SetAuthority" => {
+                program_id == TOKEN_PROGRAM_ID
+                    || program_id == TOKEN_2022_PROGRAM_ID
+                    || program_id == BPF_LOADER_UPGRADEABLE_PROGRAM_ID
+            }
+            "SetAuthorityChecked" => program_id == BPF_LOADER_UPGRADEABLE_PROGRAM_ID,
+            "Authorize" | "AuthorizeChecked" => program_id == STAKE_PROGRAM_ID,
+            "AuthorizeNonceAccount" => program_id == SYSTEM_PROGRAM_ID,
+            _ => false,
+        };
+        if !is_authority_change {
+            return;
+        }
+
+        let account = decoded
+            .pubkey("account")
+            .or_else(|| decoded.pubkey("stake_account"))
+            .or_else(|| decoded.pubkey("nonce_account"))
+            .map_or_else(|| "an account".to_string(), |account| account.to_string());
+        let explanation = match decoded.pubkey("new_authority") {
+            None => format!(
+                "removes the authority of {account}: this cannot be undone \
+                 (e.g. the program becomes immutable)"
+            ),
+            Some(new_authority) if !self.is_trusted_authority(&new_authority, context) => {
+                format!(
+                    "hands the authority of {account} to {new_authority}, which is neither \
+                     the vault nor a trusted account"
+                )
+            }
+            Some(_) => return,
+        };
+        self.report(
+            findings,
+            Rule::AuthorityChange,
+            instruction.index,
+            explanation,
+        );
+    }
+
+    fn check_close(
+        &self,
+        instruction: &ExplainedInstruction,
+        context: &LintContext,
+        findings: &mut Vec<Finding>,
+    ) {
+        let Some(decoded) = &instruction.decoded else {
+            return;
+        };
+        let program_id = instruction.instruction.program_id;
+        let destination = match decoded.name.as_str() {
+            "CloseAccount"
+                if program_id == TOKEN_PROGRAM_ID || program_id == TOKEN_2022_PROGRAM_ID =>
+            {
+                decoded.pubkey("destination")
+            }
+            "Close" if program_id == BPF_LOADER_UPGRADEABLE_PROGRAM_ID => {
+                decoded.pubkey("recipient")
+            }
+            _ => None,
+        };
+        if let Some(destination) = destination {
+            if !self.is_trusted_destination(&destination, context) {
+                self.report(
+                    findings,
+                    Rule::CloseToNonMember,
+                    instruction.index,
+                    format!(
+                        "closes {} and sends its lamports to {destination}, which is neither \
+                         the vault nor a member",
+                        decoded
+                            .pubkey("account")
+                            .map_or_else(|| "an account".to_string(), |key| key.to_string())
+                    ),
+                );
+            }
+        }
+    }
+
+    fn check_rent_exemption(
+        &self,
+        instructions: &[ExplainedInstruction],
+        context: &LintContext,
+        findings: &mut Vec<Finding>,
+    ) {
+        let Some(balance) = context.vault_lamports else {
+            return;
+        };
+
+        let mut spent: u64 = 0;
+        let mut last_index = None;
+        for instruction in instructions {
+            let Some(decoded) = &instruction.decoded else {
+                continue;
+            };
+            let is_debit = instruction.instruction.program_id == SYSTEM_PROGRAM_ID
+                && matches!(
+                    decoded.name.as_str(),
+                    "Transfer" | "TransferWithSeed" | "CreateAccount" | "CreateAccountWithSeed"
+                );
+            if is_debit && decoded.pubkey("from") == Some(context.vault) {
+                spent = spent.saturating_add(decoded.amount("lamports").unwrap_or(0));
+                last_index = Some(instruction.index);
+            }
+        }
+        let Some(last_index) = last_index.filter(|_| spent != 0) else {
+            return;
+        };
+
+        // Emptying the vault entirely is allowed, leaving dust below the minimum is not.
+        let explanation = match balance.checked_sub(spent) {
+            None => format!(
+                "transfers {} SOL out of a vault holding {} SOL",
+                format_amount(spent, SOL_DECIMALS),
+                format_amount(balance, SOL_DECIMALS)
+            ),
+            Some(remaining) if remaining != 0 && remaining < context.rent_exempt_minimum => {
+                format!(
+                    "leaves the vault with {} SOL, below the rent-exempt minimum of {} SOL",
+                    format_amount(remaining, SOL_DECIMALS),
+                    format_amount(context.rent_exempt_minimum, SOL_DECIMALS)
+                )
+            }
+            Some(_) => return,
+        };
+        self.report(
+            findings,
+            Rule::VaultBelowRentExemption,
+            last_index,
+            explanation,
+        );
+    }
+}
+
+impl Default for Linter {
+    fn default() -> Self {
+        Self::new(LintConfig::default())
+    }
+}
+
+/// Lints the message of the proposal at `transaction_index`, fetching the multisig,
+/// the transaction, its lookup tables and the vault balance.
+pub async fn lint_proposal(
+    rpc_client: &RpcClient,
+    linter: &Linter,
+    multisig_key: &Pubkey,
+    transaction_index: u64,
+    program_id: Option<Pubkey>,
+) -> ClientResult<Vec<Finding>> {
+    let multisig = get_multisig(rpc_client, multisig_key, program_id).await?;
+    let transaction_key = get_transaction_pda(
+        multisig_key,
+        transaction_index,
+        Some(&program_id.unwrap_or(FORTIS_PROGRAM_ID)),
+    )
+    .0;
+    let vault_transaction = get_transaction(rpc_client, &transaction_key, program_id).await?;
+    let address_lookup_table_accounts =
+        get_address_lookup_tables(rpc_client, &vault_transaction.message).await?;
+
+    let context = proposal_context(multisig_key, &multisig, &vault_transaction, program_id);
+    let vault_lamports = rpc_client
+        .get_account_with_commitment(&context.vault, rpc_client.commitment())
+        .await?
+        .value
+        .map_or(0, |account| account.lamports);
+    let rent_exempt_minimum = rpc_client.get_minimum_balance_for_rent_exemption(0).await?;
+
+    linter.lint(
+        &vault_transaction.message,
+        &address_lookup_table_accounts,
+        &context.with_vault_balance(vault_lamports, rent_exempt_minimum),
+    )
+}
+
+pub(crate) fn proposal_context(
+    multisig_key: &Pubkey,
+    multisig: &Multisig,
+    vault_transaction: &VaultTransaction,
+    program_id: Option<Pubkey>,
+) -> LintContext {
+    LintContext::new(
+        multisig_key,
+        multisig,
+        vault_transaction.index,
+        vault_transaction.ephemeral_signer_bumps.len() as u8,
+        program_id,
+    )
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let severity = match self {
+            Severity::Info => "info",
+            Severity::Warning => "warning",
+            Severity::Critical => "critical",
+        };
+        write!(f, "{severity}")
+    }
+}
+
+impl fmt::Display for Finding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "[{}] {:?} at #{}: {}",
+            self.severity, self.rule, self.instruction_index, self.explanation
+        )
+    }
+}
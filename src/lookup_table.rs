@@ -0,0 +1,255 @@
+//! Address lookup tables for vault transactions that touch too many accounts to fit
+//! in the `proposal_create` and `proposal_execute` transactions.
+//!
+//! `plan_lookup_table` collects the accounts of the vault instructions that can be loaded
+//! from a table and builds the create/extend instructions, `create_lookup_table` sends them
+//! and waits until the table can be used. Once the proposal has been executed the rent is
+//! reclaimed with `deactivate_instruction` and, after the cooldown, `close_instruction`.
+use std::collections::HashSet;
+use std::time::Duration;
+
+use solana_address_lookup_table_interface::instruction::{
+    close_lookup_table, create_lookup_table as create_lookup_table_instruction,
+    deactivate_lookup_table, extend_lookup_table,
+};
+use solana_address_lookup_table_interface::state::{
+    AddressLookupTable, LookupTableMeta, LOOKUP_TABLE_MAX_ADDRESSES,
+};
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_client::rpc_config::CommitmentConfig;
+use solana_message::AddressLookupTableAccount;
+use solana_sdk::{account::Account, instruction::Instruction, pubkey::Pubkey};
+use solana_signer::Signer;
+
+use crate::error::ClientError;
+use crate::fortis_client::compile_transaction;
+use crate::ClientResult;
+
+/// Number of addresses appended by a single `extend_lookup_table` instruction,
+/// small enough for the extend transaction to fit in a packet with a separate payer.
+pub const MAX_ADDRESSES_PER_EXTEND: usize = 20;
+
+/// Delay between two slot checks while waiting for a table to become usable.
+pub(crate) const ACTIVATION_POLL_INTERVAL: Duration = Duration::from_millis(400);
+
+/// Instructions creating and filling one address lookup table.
+pub struct LookupTablePlan {
+    /// Address of the table, derived from the authority and the recent slot.
+    pub address: Pubkey,
+    pub authority: Pubkey,
+    /// Addresses stored in the table, in order.
+    pub addresses: Vec<Pubkey>,
+    pub create_instruction: Instruction,
+    /// One instruction per `MAX_ADDRESSES_PER_EXTEND` addresses, each meant for its own transaction.
+    pub extend_instructions: Vec<Instruction>,
+}
+
+impl LookupTablePlan {
+    /// The table as it will be once created and activated.
+    pub fn address_lookup_table_account(&self) -> AddressLookupTableAccount {
+        AddressLookupTableAccount {
+            key: self.address,
+            addresses: self.addresses.clone(),
+        }
+    }
+
+    pub fn deactivate_instruction(&self) -> Instruction {
+        deactivate_instruction(&self.address, &self.authority)
+    }
+
+    pub fn close_instruction(&self, recipient: &Pubkey) -> Instruction {
+        close_instruction(&self.address, &self.authority, recipient)
+    }
+}
+
+/// Returns the accounts of `instructions` that `VaultTransactionMessage::try_compile` can load
+/// from a lookup table, in order of first use.
+///
+/// Signers, including the vault, have to stay static and are left out. Program ids are
+/// included since the vault message passes them as regular accounts.
+pub fn lookup_table_addresses(vault: &Pubkey, instructions: &[Instruction]) -> Vec<Pubkey> {
+    let signers = instructions
+        .iter()
+        .flat_map(|ix| &ix.accounts)
+        .filter(|meta| meta.is_signer)
+        .map(|meta| meta.pubkey)
+        .chain(std::iter::once(*vault))
+        .collect::<HashSet<_>>();
+
+    let mut seen = HashSet::new();
+    instructions
+        .iter()
+        .flat_map(|ix| {
+            std::iter::once(ix.program_id).chain(ix.accounts.iter().map(|meta| meta.pubkey))
+        })
+        .filter(|key| !signers.contains(key) && seen.insert(*key))
+        .collect()
+}
+
+/// Plans a lookup table holding the accounts of `instructions` that are not already
+/// in `existing`. Returns `None` when there is nothing left to store.
+///
+/// `recent_slot` must be a recent finalized slot; it seeds the table address.
+pub fn plan_lookup_table(
+    authority: &Pubkey,
+    payer: &Pubkey,
+    recent_slot: u64,
+    vault: &Pubkey,
+    instructions: &[Instruction],
+    existing: &[AddressLookupTableAccount],
+) -> ClientResult<Option<LookupTablePlan>> {
+    let stored = existing
+        .iter()
+        .flat_map(|table| &table.addresses)
+        .collect::<HashSet<_>>();
+    let addresses = lookup_table_addresses(vault, instructions)
+        .into_iter()
+        .filter(|key| !stored.contains(key))
+        .collect::<Vec<_>>();
+
+    if addresses.is_empty() {
+        return Ok(None);
+    }
+    if addresses.len() > LOOKUP_TABLE_MAX_ADDRESSES {
+        return Err(ClientError::TooManyLookupTableAddresses {
+            count: addresses.len(),
+            max: LOOKUP_TABLE_MAX_ADDRESSES,
+        });
+    }
+
+    let (create_instruction, address) =
+        create_lookup_table_instruction(*authority, *payer, recent_slot);
+    let extend_instructions = addresses
+        .chunks(MAX_ADDRESSES_PER_EXTEND)
+        .map(|chunk| extend_lookup_table(address, *authority, Some(*payer), chunk.to_vec()))
+        .collect();
+
+    Ok(Some(LookupTablePlan {
+        address,
+        authority: *authority,
+        addresses,
+        create_instruction,
+        extend_instructions,
+    }))
+}
+
+/// Deactivates a lookup table. It can be closed once the deactivation slot
+/// is no longer in the `SlotHashes` sysvar, about 513 slots later.
+pub fn deactivate_instruction(table: &Pubkey, authority: &Pubkey) -> Instruction {
+    deactivate_lookup_table(*table, *authority)
+}
+
+/// Closes a deactivated lookup table, returning its rent to `recipient`.
+pub fn close_instruction(table: &Pubkey, authority: &Pubkey, recipient: &Pubkey) -> Instruction {
+    close_lookup_table(*table, *authority, *recipient)
+}
+
+/// Plans, creates and activates a lookup table for the accounts of `instructions`
+/// missing from `existing`, then returns `existing` followed by the new table.
+pub async fn prepare_lookup_tables(
+    rpc_client: &RpcClient,
+    payer: &dyn Signer,
+    authority: &dyn Signer,
+    vault: &Pubkey,
+    instructions: &[Instruction],
+    existing: &[AddressLookupTableAccount],
+) -> ClientResult<Vec<AddressLookupTableAccount>> {
+    let recent_slot = rpc_client
+        .get_slot_with_commitment(CommitmentConfig::finalized())
+        .await?;
+    let mut tables = existing.to_vec();
+
+    if let Some(plan) = plan_lookup_table(
+        &authority.pubkey(),
+        &payer.pubkey(),
+        recent_slot,
+        vault,
+        instructions,
+        existing,
+    )? {
+        tables.push(create_lookup_table(rpc_client, &plan, payer, authority).await?);
+    }
+
+    Ok(tables)
+}
+
+/// Sends the create and extend transactions of `plan`, then waits until the
+/// table is active, i.e. the current slot is past its last extension.
+pub async fn create_lookup_table(
+    rpc_client: &RpcClient,
+    plan: &LookupTablePlan,
+    payer: &dyn Signer,
+    authority: &dyn Signer,
+) -> ClientResult<AddressLookupTableAccount> {
+    // The table authority only has to sign the extensions.
+    let extend_signers = plan_signers(payer, authority);
+    let transactions = std::iter::once((&plan.create_instruction, vec![payer])).chain(
+        plan.extend_instructions
+            .iter()
+            .map(|ix| (ix, extend_signers.clone())),
+    );
+    for (instruction, signers) in transactions {
+        let blockhash = rpc_client.get_latest_blockhash().await?;
+        let transaction = compile_transaction(
+            &payer.pubkey(),
+            std::slice::from_ref(instruction),
+            &signers,
+            &[],
+            blockhash,
+        )?;
+        rpc_client
+            .send_and_confirm_transaction(&transaction)
+            .await?;
+    }
+
+    let account = rpc_client
+        .get_account_with_commitment(&plan.address, rpc_client.commitment())
+        .await?
+        .value;
+    let (table, meta) = decode_lookup_table(&plan.address, account)?;
+
+    while rpc_client.get_slot().await? <= meta.last_extended_slot {
+        tokio::time::sleep(ACTIVATION_POLL_INTERVAL).await;
+    }
+
+    Ok(table)
+}
+
+/// The signers of the plan transactions, without duplicates when the payer is the authority.
+pub(crate) fn plan_signers<'a>(
+    payer: &'a dyn Signer,
+    authority: &'a dyn Signer,
+) -> Vec<&'a dyn Signer> {
+    if payer.pubkey() == authority.pubkey() {
+        vec![payer]
+    } else {
+        vec![payer, authority]
+    }
+}
+
+/// Decodes a fetched lookup table account, checking that it exists and is owned
+/// by the address lookup table program.
+pub(crate) fn decode_lookup_table(
+    key: &Pubkey,
+    account: Option<Account>,
+) -> ClientResult<(AddressLookupTableAccount, LookupTableMeta)> {
+    let account = account.ok_or(ClientError::LookupTableNotFound(*key))?;
+    if account.owner != solana_address_lookup_table_interface::program::ID {
+        return Err(ClientError::WrongAccountOwner {
+            address: *key,
+            expected: solana_address_lookup_table_interface::program::ID,
+            actual: account.owner,
+        });
+    }
+
+    let table = AddressLookupTable::deserialize(&account.data)
+        .map_err(|_| ClientError::InvalidLookupTable(*key))?;
+
+    Ok((
+        AddressLookupTableAccount {
+            key: *key,
+            addresses: table.addresses.to_vec(),
+        },
+        table.meta,
+    ))
+}
@@ -0,0 +1,146 @@
+//! Durable nonce accounts, letting transactions be signed long after they are built.
+//!
+//! A transaction using a durable nonce starts with `advance_nonce_account` and takes the
+//! value stored in the nonce account as its blockhash. It stays valid until the nonce is
+//! advanced, instead of the ~60 seconds a recent blockhash lasts.
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_client::nonce_utils::nonblocking::data_from_account;
+use solana_message::Hash;
+use solana_sdk::{
+    account::Account, instruction::Instruction, pubkey::Pubkey, signature::Signature,
+};
+use solana_signer::Signer;
+use solana_system_interface::instruction::{advance_nonce_account, create_nonce_account};
+
+use crate::error::ClientError;
+use crate::fortis_client::compile_transaction;
+use crate::ClientResult;
+
+/// Size of the data of a nonce account.
+pub const NONCE_ACCOUNT_SIZE: usize = solana_nonce::state::State::size();
+
+/// A nonce account and the authority allowed to advance it, usually a multisig member.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DurableNonce {
+    pub account: Pubkey,
+    pub authority: Pubkey,
+}
+
+/// State of an initialized nonce account.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NonceInfo {
+    pub account: Pubkey,
+    pub authority: Pubkey,
+    /// The value to use as the blockhash of the next transaction.
+    pub blockhash: Hash,
+    pub lamports_per_signature: u64,
+}
+
+impl DurableNonce {
+    pub fn advance_instruction(&self) -> Instruction {
+        advance_nonce_account(&self.account, &self.authority)
+    }
+
+    /// `instructions` preceded by the `advance_nonce_account` instruction,
+    /// which the runtime requires to come first.
+    pub fn with_advance_instruction(&self, instructions: &[Instruction]) -> Vec<Instruction> {
+        let mut with_advance = Vec::with_capacity(instructions.len() + 1);
+        with_advance.push(self.advance_instruction());
+        with_advance.extend_from_slice(instructions);
+        with_advance
+    }
+
+    /// Checks that `info` is the state of this nonce account and matches its authority.
+    pub fn check(&self, info: &NonceInfo) -> ClientResult<()> {
+        if info.account != self.account || info.authority != self.authority {
+            return Err(ClientError::NonceAuthorityMismatch {
+                account: self.account,
+                expected: self.authority,
+                actual: info.authority,
+            });
+        }
+        Ok(())
+    }
+
+    /// The nonce stored in `info`, to use as the blockhash, once `check`ed.
+    pub fn blockhash(&self, info: &NonceInfo) -> ClientResult<Hash> {
+        self.check(info)?;
+        Ok(info.blockhash)
+    }
+}
+
+/// Instructions creating `nonce_account` funded with `lamports` by `payer`
+/// and initializing it with `authority`.
+pub fn create_nonce_account_instructions(
+    payer: &Pubkey,
+    nonce_account: &Pubkey,
+    authority: &Pubkey,
+    lamports: u64,
+) -> Vec<Instruction> {
+    create_nonce_account(payer, nonce_account, authority, lamports)
+}
+
+/// Creates a rent-exempt nonce account whose authority is `authority`, paid by `payer`.
+pub async fn create_durable_nonce(
+    rpc_client: &RpcClient,
+    payer: &dyn Signer,
+    nonce_account: &dyn Signer,
+    authority: &Pubkey,
+) -> ClientResult<(DurableNonce, Signature)> {
+    let lamports = rpc_client
+        .get_minimum_balance_for_rent_exemption(NONCE_ACCOUNT_SIZE)
+        .await?;
+    let instructions = create_nonce_account_instructions(
+        &payer.pubkey(),
+        &nonce_account.pubkey(),
+        authority,
+        lamports,
+    );
+
+    let blockhash = rpc_client.get_latest_blockhash().await?;
+    let transaction = compile_transaction(
+        &payer.pubkey(),
+        &instructions,
+        &[payer, nonce_account],
+        &[],
+        blockhash,
+    )?;
+    let signature = rpc_client
+        .send_and_confirm_transaction(&transaction)
+        .await?;
+
+    Ok((
+        DurableNonce {
+            account: nonce_account.pubkey(),
+            authority: *authority,
+        },
+        signature,
+    ))
+}
+
+/// Fetches the current value of a nonce account.
+pub async fn get_nonce(rpc_client: &RpcClient, nonce_account: &Pubkey) -> ClientResult<NonceInfo> {
+    let account = rpc_client
+        .get_account_with_commitment(nonce_account, rpc_client.commitment())
+        .await?
+        .value;
+    decode_nonce(nonce_account, account)
+}
+
+pub(crate) fn decode_nonce(address: &Pubkey, account: Option<Account>) -> ClientResult<NonceInfo> {
+    let account = account.ok_or_else(|| ClientError::InvalidNonceAccount {
+        address: *address,
+        message: "account not found".to_string(),
+    })?;
+    let data = data_from_account(&account).map_err(|err| ClientError::InvalidNonceAccount {
+        address: *address,
+        message: err.to_string(),
+    })?;
+
+    Ok(NonceInfo {
+        account: *address,
+        authority: data.authority,
+        blockhash: data.blockhash(),
+        lamports_per_signature: data.get_lamports_per_signature(),
+    })
+}
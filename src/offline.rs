@@ -0,0 +1,207 @@
+//! Offline signing of Fortis transactions by members on separate machines.
+//!
+//! A `PartiallySignedTransaction` is built unsigned from the instructions of any Fortis
+//! action, encoded to base64 or base58 for transport, signed independently by each holder,
+//! merged back and submitted once every required signature is present. Every signer should
+//! compare `message_hash` with the one shown to the others before signing.
+use base64::{engine::general_purpose::STANDARD, Engine};
+use solana_message::{AddressLookupTableAccount, Hash};
+use solana_sdk::{instruction::Instruction, pubkey::Pubkey, signature::Signature};
+use solana_signer::Signer;
+use solana_transaction::versioned::VersionedTransaction;
+
+use crate::error::ClientError;
+use crate::fortis_client::compile_message;
+use crate::transaction_size::transaction_size;
+use crate::ClientResult;
+
+/// A transaction collecting the signatures of its required signers.
+///
+/// Missing signatures are left to `Signature::default()`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PartiallySignedTransaction {
+    transaction: VersionedTransaction,
+}
+
+impl PartiallySignedTransaction {
+    /// Builds an unsigned transaction, refusing it if it exceeds the packet size.
+    ///
+    /// `blockhash` bounds how long signers have: use a durable nonce for long signing rounds.
+    pub fn new(
+        payer: &Pubkey,
+        instructions: &[Instruction],
+        address_lookup_table_accounts: &[AddressLookupTableAccount],
+        blockhash: Hash,
+    ) -> ClientResult<Self> {
+        transaction_size(payer, instructions, address_lookup_table_accounts)?.check()?;
+
+        let message = compile_message(
+            payer,
+            instructions,
+            address_lookup_table_accounts,
+            blockhash,
+        )?;
+        let num_signatures = usize::from(message.header().num_required_signatures);
+
+        Ok(Self {
+            transaction: VersionedTransaction {
+                signatures: vec![Signature::default(); num_signatures],
+                message,
+            },
+        })
+    }
+
+    /// Wraps a transaction, keeping the signatures it already carries.
+    ///
+    /// The transaction is sanitized first, so that a malformed one is refused instead of
+    /// making the signer lookups panic.
+    pub fn from_transaction(transaction: VersionedTransaction) -> ClientResult<Self> {
+        transaction
+            .sanitize()
+            .map_err(|err| ClientError::TransactionDecodeError(err.to_string()))?;
+        Ok(Self { transaction })
+    }
+
+    /// SHA-256 of the message bytes, the hash hardware wallets display when blind signing.
+    pub fn message_hash(&self) -> Hash {
+        solana_sdk::hash::hash(&self.transaction.message.serialize())
+    }
+
+    pub fn required_signers(&self) -> &[Pubkey] {
+        let num_signatures = self.transaction.signatures.len();
+        self.transaction
+            .message
+            .static_account_keys()
+            .get(..num_signatures)
+            .unwrap_or_default()
+    }
+
+    /// Required signers whose signature has not been added yet.
+    pub fn missing_signers(&self) -> Vec<Pubkey> {
+        self.required_signers()
+            .iter()
+            .zip(&self.transaction.signatures)
+            .filter(|(_, signature)| **signature == Signature::default())
+            .map(|(pubkey, _)| *pubkey)
+            .collect()
+    }
+
+    /// Signs the message with `signer`, which must be one of the required signers.
+    pub fn sign(&mut self, signer: &dyn Signer) -> ClientResult<()> {
+        let position = self.signer_position(&signer.pubkey())?;
+        let signature = signer.try_sign_message(&self.transaction.message.serialize())?;
+        self.transaction.signatures[position] = signature;
+        Ok(())
+    }
+
+    /// Adds a signature produced elsewhere, after checking it against the message.
+    pub fn add_signature(&mut self, pubkey: &Pubkey, signature: Signature) -> ClientResult<()> {
+        let position = self.signer_position(pubkey)?;
+        if !signature.verify(pubkey.as_ref(), &self.transaction.message.serialize()) {
+            return Err(ClientError::InvalidSignature(*pubkey));
+        }
+        self.transaction.signatures[position] = signature;
+        Ok(())
+    }
+
+    /// Copies the signatures of `other`, a copy of the same transaction signed by other holders.
+    ///
+    /// Every incoming signature is checked against the message first: if one is invalid,
+    /// `InvalidSignature` is returned and no signature is copied.
+    pub fn merge(&mut self, other: &PartiallySignedTransaction) -> ClientResult<()> {
+        if self.transaction.message != other.transaction.message {
+            return Err(ClientError::MessageMismatch);
+        }
+
+        let message = self.transaction.message.serialize();
+        let incoming: Vec<(usize, Signature)> = self
+            .required_signers()
+            .iter()
+            .zip(&other.transaction.signatures)
+            .enumerate()
+            .filter(|(_, (_, signature))| **signature != Signature::default())
+            .map(|(position, (pubkey, signature))| {
+                if signature.verify(pubkey.as_ref(), &message) {
+                    Ok((position, *signature))
+                } else {
+                    Err(ClientError::InvalidSignature(*pubkey))
+                }
+            })
+            .collect::<ClientResult<_>>()?;
+
+        for (position, signature) in incoming {
+            self.transaction.signatures[position] = signature;
+        }
+        Ok(())
+    }
+
+    /// Checks that every required signature is present and valid.
+    pub fn verify(&self) -> ClientResult<()> {
+        let missing = self.missing_signers();
+        if !missing.is_empty() {
+            return Err(ClientError::MissingSignatures(missing));
+        }
+
+        let message = self.transaction.message.serialize();
+        for (pubkey, signature) in self
+            .required_signers()
+            .iter()
+            .zip(&self.transaction.signatures)
+        {
+            if !signature.verify(pubkey.as_ref(), &message) {
+                return Err(ClientError::InvalidSignature(*pubkey));
+            }
+        }
+        Ok(())
+    }
+
+    /// The fully signed transaction, ready to be sent.
+    pub fn into_transaction(self) -> ClientResult<VersionedTransaction> {
+        self.verify()?;
+        Ok(self.transaction)
+    }
+
+    pub fn transaction(&self) -> &VersionedTransaction {
+        &self.transaction
+    }
+
+    pub fn to_bytes(&self) -> ClientResult<Vec<u8>> {
+        bincode::serialize(&self.transaction)
+            .map_err(|err| ClientError::SerializationError(err.to_string()))
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> ClientResult<Self> {
+        let transaction: VersionedTransaction = bincode::deserialize(bytes)
+            .map_err(|err| ClientError::TransactionDecodeError(err.to_string()))?;
+        Self::from_transaction(transaction)
+    }
+
+    pub fn to_base64(&self) -> ClientResult<String> {
+        Ok(STANDARD.encode(self.to_bytes()?))
+    }
+
+    pub fn from_base64(encoded: &str) -> ClientResult<Self> {
+        let bytes = STANDARD
+            .decode(encoded.trim())
+            .map_err(|err| ClientError::TransactionDecodeError(err.to_string()))?;
+        Self::from_bytes(&bytes)
+    }
+
+    pub fn to_base58(&self) -> ClientResult<String> {
+        Ok(bs58::encode(self.to_bytes()?).into_string())
+    }
+
+    pub fn from_base58(encoded: &str) -> ClientResult<Self> {
+        let bytes = bs58::decode(encoded.trim())
+            .into_vec()
+            .map_err(|err| ClientError::TransactionDecodeError(err.to_string()))?;
+        Self::from_bytes(&bytes)
+    }
+
+    fn signer_position(&self, pubkey: &Pubkey) -> ClientResult<usize> {
+        self.required_signers()
+            .iter()
+            .position(|signer| signer == pubkey)
+            .ok_or(ClientError::UnexpectedSigner(*pubkey))
+    }
+}
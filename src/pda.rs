@@ -72,3 +72,17 @@ pub fn get_ephemeral_signer_pda(
         program_id.unwrap_or(&FORTIS_PROGRAM_ID),
     )
 }
+
+/// Derives the first `n` ephemeral signers of the transaction at `transaction_index`,
+/// to be referenced by the vault instructions before proposing them.
+pub fn ephemeral_signers(
+    multisig_pda: &Pubkey,
+    transaction_index: u64,
+    n: u8,
+    program_id: Option<&Pubkey>,
+) -> Vec<Pubkey> {
+    let transaction_pda = get_transaction_pda(multisig_pda, transaction_index, program_id).0;
+    (0..n)
+        .map(|index| get_ephemeral_signer_pda(&transaction_pda, index, program_id).0)
+        .collect()
+}
@@ -0,0 +1,519 @@
+//! Treasury rules checked before approving a proposal, behind the `policy` feature.
+//!
+//! A `Policy` lists the programs the vault may invoke, spending caps per asset and the
+//! reviewers that must approve proposals touching given programs. It is loaded from TOML
+//! or JSON:
+//!
+//! ```toml
+//! allowed_programs = [
+//!     "11111111111111111111111111111111",
+//!     "TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA",
+//! ]
+//!
+//! # SOL, in lamports
+//! [[spending_limits]]
+//! per_recipient = 10_000_000_000
+//! per_day = 50_000_000_000
+//!
+//! # USDC, in base units
+//! [[spending_limits]]
+//! mint = "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v"
+//! per_day = 100_000_000_000
+//!
+//! [[required_reviewers]]
+//! program = "BPFLoaderUpgradeab1e11111111111111111111111"
+//! reviewers = ["<base58>", "<base58>"]
+//! min_approvals = 1
+//! ```
+//!
+//! `evaluate` decodes the stored message with `explain`, extracts what the vault spends and
+//! checks it against the policy and the spends of the last 24 hours.
+use serde::{Deserialize, Serialize};
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_message::AddressLookupTableAccount;
+use solana_sdk::pubkey::Pubkey;
+
+use crate::client::{get_address_lookup_tables, list_proposals_stream, ProposalBundle};
+use crate::error::ClientError;
+use crate::explain::{
+    format_amount, ExplainedInstruction, Explainer, Value, SOL_DECIMALS, SYSTEM_PROGRAM_ID,
+    TOKEN_2022_PROGRAM_ID, TOKEN_PROGRAM_ID,
+};
+use crate::pda::{get_vault_pda, FORTIS_PROGRAM_ID};
+use crate::state::{Multisig, ProposalStatus, VaultTransaction};
+use crate::ClientResult;
+
+/// Number of proposals fetched at once by `spending_history`.
+pub(crate) const HISTORY_PAGE_SIZE: usize = 20;
+
+/// Length of the window of `SpendingLimit::per_day`.
+pub const SPENDING_WINDOW_SECONDS: i64 = 24 * 60 * 60;
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Policy {
+    /// Programs the vault may invoke, any program if unset.
+    #[serde(
+        default,
+        with = "crate::serde_utils::option_pubkey_vec",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub allowed_programs: Option<Vec<Pubkey>>,
+    #[serde(default)]
+    pub spending_limits: Vec<SpendingLimit>,
+    #[serde(default)]
+    pub required_reviewers: Vec<ReviewRequirement>,
+}
+
+/// Caps on what the vault spends of one asset, in lamports or token base units.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SpendingLimit {
+    /// The token the limit applies to, SOL if unset.
+    #[serde(
+        default,
+        with = "crate::serde_utils::option_pubkey",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub mint: Option<Pubkey>,
+    /// Most a single recipient may receive in one transaction.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub per_recipient: Option<u64>,
+    /// Most the vault may spend over `SPENDING_WINDOW_SECONDS`, this transaction included.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub per_day: Option<u64>,
+}
+
+/// Reviewers that must approve any proposal invoking `program`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ReviewRequirement {
+    #[serde(with = "crate::serde_utils::pubkey")]
+    pub program: Pubkey,
+    #[serde(with = "crate::serde_utils::pubkey_vec")]
+    pub reviewers: Vec<Pubkey>,
+    #[serde(default = "default_min_approvals")]
+    pub min_approvals: usize,
+}
+
+fn default_min_approvals() -> usize {
+    1
+}
+
+/// What a vault transaction spends.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Asset {
+    Sol,
+    Token(#[serde(with = "crate::serde_utils::pubkey")] Pubkey),
+    /// Tokens moved by an instruction that does not name their mint, such as `Transfer`.
+    UnknownToken,
+}
+
+/// Lamports or tokens leaving the control of the vault.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Spend {
+    pub asset: Asset,
+    /// The receiving account: a wallet for SOL, a token account for token transfers, the
+    /// delegate or new owner for delegations and ownership changes.
+    #[serde(with = "crate::serde_utils::pubkey")]
+    pub recipient: Pubkey,
+    pub amount: u64,
+}
+
+/// What happened before the evaluated approval.
+///
+/// The daily caps are only as complete as `spends`, which the caller provides. Recording the
+/// `vault_spends` of each proposal when it is executed gives a complete history, while
+/// `spending_history` can only reconstruct it from the proposals still on chain.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PolicyHistory {
+    /// Spends executed by the vault during the last `SPENDING_WINDOW_SECONDS`.
+    pub spends: Vec<Spend>,
+    /// Members who approved the proposal, including the one about to approve.
+    pub approvals: Vec<Pubkey>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Decision {
+    Allow,
+    Deny,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Evaluation {
+    pub decision: Decision,
+    /// Every rule the transaction breaks, empty when allowed.
+    pub reasons: Vec<String>,
+}
+
+impl Policy {
+    pub fn from_json(json: &str) -> ClientResult<Self> {
+        serde_json::from_str(json).map_err(|err| ClientError::InvalidPolicy(err.to_string()))
+    }
+
+    pub fn from_toml(toml: &str) -> ClientResult<Self> {
+        toml::from_str(toml).map_err(|err| ClientError::InvalidPolicy(err.to_string()))
+    }
+
+    pub fn to_json(&self) -> ClientResult<String> {
+        serde_json::to_string_pretty(self)
+            .map_err(|err| ClientError::InvalidPolicy(err.to_string()))
+    }
+
+    pub fn to_toml(&self) -> ClientResult<String> {
+        toml::to_string_pretty(self).map_err(|err| ClientError::InvalidPolicy(err.to_string()))
+    }
+
+    fn spending_limit(&self, asset: Asset) -> Option<&SpendingLimit> {
+        let mint = match asset {
+            Asset::Sol => None,
+            Asset::Token(mint) => Some(mint),
+            Asset::UnknownToken => return None,
+        };
+        self.spending_limits.iter().find(|limit| limit.mint == mint)
+    }
+}
+
+impl PolicyHistory {
+    pub fn new(spends: Vec<Spend>) -> Self {
+        Self {
+            spends,
+            approvals: Vec::new(),
+        }
+    }
+
+    pub fn with_approvals(mut self, approvals: impl IntoIterator<Item = Pubkey>) -> Self {
+        self.approvals.extend(approvals);
+        self
+    }
+}
+
+impl Evaluation {
+    pub fn is_allowed(&self) -> bool {
+        self.decision == Decision::Allow
+    }
+}
+
+/// Checks `vault_transaction` against `policy`, given the spends and approvals in `history`.
+pub fn evaluate(
+    policy: &Policy,
+    multisig: &Multisig,
+    vault_transaction: &VaultTransaction,
+    address_lookup_table_accounts: &[AddressLookupTableAccount],
+    history: &PolicyHistory,
+    program_id: Option<Pubkey>,
+) -> ClientResult<Evaluation> {
+    let program_id = program_id.unwrap_or(FORTIS_PROGRAM_ID);
+    let vault = get_vault_pda(&vault_transaction.multisig, Some(&program_id)).0;
+    let instructions =
+        Explainer::new().explain(&vault_transaction.message, address_lookup_table_accounts)?;
+
+    let mut reasons = Vec::new();
+    check_programs(policy, &instructions, &mut reasons);
+    check_spending(
+        policy,
+        &vault_spends(&vault, &instructions),
+        history,
+        &mut reasons,
+    );
+    check_reviewers(policy, multisig, &instructions, history, &mut reasons);
+
+    Ok(Evaluation {
+        decision: if reasons.is_empty() {
+            Decision::Allow
+        } else {
+            Decision::Deny
+        },
+        reasons,
+    })
+}
+
+/// Lamports and tokens leaving the control of `vault` in `instructions`.
+///
+/// Besides transfers and account funding, this covers transfers out of accounts derived from
+/// the vault with a seed, nonce account withdrawals, token delegations and token account
+/// ownership changes. The latter hand over the whole balance of an account of unknown mint,
+/// so they are reported as `u64::MAX` of `Asset::UnknownToken`.
+pub fn vault_spends(vault: &Pubkey, instructions: &[ExplainedInstruction]) -> Vec<Spend> {
+    instructions
+        .iter()
+        .filter_map(|instruction| {
+            let decoded = instruction.decoded.as_ref()?;
+            let program_id = instruction.instruction.program_id;
+
+            if program_id == SYSTEM_PROGRAM_ID {
+                let (recipient, controller) = match decoded.name.as_str() {
+                    "Transfer" => (decoded.pubkey("to")?, decoded.pubkey("from")?),
+                    // The source is derived from the base, which signs for it.
+                    "TransferWithSeed" => (decoded.pubkey("to")?, decoded.pubkey("base")?),
+                    "CreateAccount" | "CreateAccountWithSeed" => {
+                        (decoded.pubkey("new_account")?, decoded.pubkey("from")?)
+                    }
+                    "WithdrawNonceAccount" => (decoded.pubkey("to")?, decoded.pubkey("authority")?),
+                    _ => return None,
+                };
+                if controller != *vault {
+                    return None;
+                }
+                return Some(Spend {
+                    asset: Asset::Sol,
+                    recipient,
+                    amount: decoded.amount("lamports")?,
+                });
+            }
+
+            if program_id == TOKEN_PROGRAM_ID || program_id == TOKEN_2022_PROGRAM_ID {
+                let mint = || decoded.pubkey("mint").map(Asset::Token);
+                let (asset, recipient, authority, amount) = match decoded.name.as_str() {
+                    "Transfer" => (
+                        Asset::UnknownToken,
+                        decoded.pubkey("destination")?,
+                        decoded.pubkey("authority")?,
+                        decoded.amount("amount")?,
+                    ),
+                    "TransferChecked" => (
+                        mint()?,
+                        decoded.pubkey("destination")?,
+                        decoded.pubkey("authority")?,
+                        decoded.amount("amount")?,
+                    ),
+                    // The delegate may later transfer up to the approved amount.
+                    "Approve" => (
+                        Asset::UnknownToken,
+                        decoded.pubkey("delegate")?,
+                        decoded.pubkey("owner")?,
+                        decoded.amount("amount")?,
+                    ),
+                    "ApproveChecked" => (
+                        mint()?,
+                        decoded.pubkey("delegate")?,
+                        decoded.pubkey("owner")?,
+                        decoded.amount("amount")?,
+                    ),
+                    "SetAuthority"
+                        if matches!(
+                            decoded.field("authority_type"),
+                            Some(Value::Text(authority_type)) if authority_type == "AccountOwner"
+                        ) =>
+                    {
+                        (
+                            Asset::UnknownToken,
+                            decoded.pubkey("new_authority")?,
+                            decoded.pubkey("current_authority")?,
+                            u64::MAX,
+                        )
+                    }
+                    _ => return None,
+                };
+                if authority != *vault {
+                    return None;
+                }
+                return Some(Spend {
+                    asset,
+                    recipient,
+                    amount,
+                });
+            }
+
+            None
+        })
+        .collect()
+}
+
+fn check_programs(
+    policy: &Policy,
+    instructions: &[ExplainedInstruction],
+    reasons: &mut Vec<String>,
+) {
+    let Some(allowed) = &policy.allowed_programs else {
+        return;
+    };
+    for instruction in instructions {
+        let program_id = instruction.instruction.program_id;
+        if !allowed.contains(&program_id) {
+            reasons.push(format!(
+                "instruction #{} invokes {program_id}, which is not an allowed program",
+                instruction.index
+            ));
+        }
+    }
+}
+
+fn check_spending(
+    policy: &Policy,
+    spends: &[Spend],
+    history: &PolicyHistory,
+    reasons: &mut Vec<String>,
+) {
+    let has_token_limits = policy
+        .spending_limits
+        .iter()
+        .any(|limit| limit.mint.is_some());
+    if has_token_limits {
+        for spend in spends
+            .iter()
+            .filter(|spend| spend.asset == Asset::UnknownToken)
+        {
+            reasons.push(format!(
+                "{} gets tokens of a mint the instruction does not name, so token spending \
+                 limits cannot be enforced: use the checked instruction variants",
+                spend.recipient
+            ));
+        }
+    }
+
+    let mut assets: Vec<Asset> = Vec::new();
+    for spend in spends {
+        if !assets.contains(&spend.asset) {
+            assets.push(spend.asset);
+        }
+    }
+    for asset in assets {
+        let Some(limit) = policy.spending_limit(asset) else {
+            continue;
+        };
+        let of_asset = |spends: &[Spend]| -> Vec<Spend> {
+            spends
+                .iter()
+                .filter(|spend| spend.asset == asset)
+                .copied()
+                .collect()
+        };
+        let spent = of_asset(spends);
+
+        if let Some(per_recipient) = limit.per_recipient {
+            let mut recipients: Vec<(Pubkey, u64)> = Vec::new();
+            for spend in &spent {
+                match recipients
+                    .iter_mut()
+                    .find(|(recipient, _)| *recipient == spend.recipient)
+                {
+                    Some((_, total)) => *total = total.saturating_add(spend.amount),
+                    None => recipients.push((spend.recipient, spend.amount)),
+                }
+            }
+            for (recipient, total) in recipients {
+                if total > per_recipient {
+                    reasons.push(format!(
+                        "{recipient} receives {}, above the per-recipient limit of {}",
+                        format_asset(asset, total),
+                        format_asset(asset, per_recipient)
+                    ));
+                }
+            }
+        }
+
+        if let Some(per_day) = limit.per_day {
+            let total = of_asset(&history.spends)
+                .iter()
+                .chain(&spent)
+                .fold(0u64, |total, spend| total.saturating_add(spend.amount));
+            if total > per_day {
+                reasons.push(format!(
+                    "the vault would spend {} over the last 24 hours, above the daily limit of {}",
+                    format_asset(asset, total),
+                    format_asset(asset, per_day)
+                ));
+            }
+        }
+    }
+}
+
+fn check_reviewers(
+    policy: &Policy,
+    multisig: &Multisig,
+    instructions: &[ExplainedInstruction],
+    history: &PolicyHistory,
+    reasons: &mut Vec<String>,
+) {
+    for requirement in &policy.required_reviewers {
+        let Some(instruction) = instructions
+            .iter()
+            .find(|instruction| instruction.instruction.program_id == requirement.program)
+        else {
+            continue;
+        };
+
+        // Only reviewers who are still members count.
+        let approvals = requirement
+            .reviewers
+            .iter()
+            .filter(|reviewer| {
+                multisig.members.contains(reviewer) && history.approvals.contains(reviewer)
+            })
+            .count();
+        if approvals < requirement.min_approvals {
+            reasons.push(format!(
+                "instruction #{} invokes {}, which needs {} reviewer approval(s), got {}",
+                instruction.index, requirement.program, requirement.min_approvals, approvals
+            ));
+        }
+    }
+}
+
+fn format_asset(asset: Asset, amount: u64) -> String {
+    match asset {
+        Asset::Sol => format!("{} SOL", format_amount(amount, SOL_DECIMALS)),
+        Asset::Token(mint) => format!("{amount} base units of {mint}"),
+        Asset::UnknownToken => format!("{amount} base units of an unknown token"),
+    }
+}
+
+/// The transactions of the proposals of `page` executed since `since`, or `None` when no
+/// proposal of the page was updated since then and older pages need not be scanned.
+pub(crate) fn executed_since(
+    page: Vec<ProposalBundle>,
+    since: i64,
+) -> Option<Vec<VaultTransaction>> {
+    let mut recent = false;
+    let mut executed = Vec::new();
+    for bundle in page {
+        let (Some(proposal), Some(transaction)) = (bundle.proposal, bundle.transaction) else {
+            continue;
+        };
+        if proposal.timestamp < since {
+            continue;
+        }
+        recent = true;
+        if proposal.status == ProposalStatus::Executed {
+            executed.push(transaction);
+        }
+    }
+    recent.then_some(executed)
+}
+
+/// Spends of the proposals executed since `since` (unix timestamp), newest first.
+///
+/// This is a best-effort reconstruction: closing executed proposals to reclaim their rent
+/// erases them from it, and the last update of a proposal stands for its execution time.
+/// Keep a record of the spends at execution when the daily caps must hold.
+///
+/// Proposals are scanned from the newest until a page holds none updated since `since`.
+pub async fn spending_history(
+    rpc_client: &RpcClient,
+    multisig_key: &Pubkey,
+    since: i64,
+    program_id: Option<Pubkey>,
+) -> ClientResult<Vec<Spend>> {
+    use futures::StreamExt;
+
+    let vault = get_vault_pda(multisig_key, Some(&program_id.unwrap_or(FORTIS_PROGRAM_ID))).0;
+    let explainer = Explainer::new();
+    let mut spends = Vec::new();
+
+    let pages = list_proposals_stream(rpc_client, multisig_key, HISTORY_PAGE_SIZE, program_id);
+    futures::pin_mut!(pages);
+    while let Some(page) = pages.next().await {
+        let Some(executed) = executed_since(page?, since) else {
+            break;
+        };
+        for transaction in executed {
+            let address_lookup_table_accounts =
+                get_address_lookup_tables(rpc_client, &transaction.message).await?;
+            let instructions =
+                explainer.explain(&transaction.message, &address_lookup_table_accounts)?;
+            spends.extend(vault_spends(&vault, &instructions));
+        }
+    }
+    Ok(spends)
+}
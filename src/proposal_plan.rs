@@ -0,0 +1,196 @@
+//! Splitting of large instruction batches into several proposals.
+//!
+//! `plan_proposals` packs instructions, in order, into the fewest vault messages whose
+//! `proposal_create` and `proposal_execute` transactions both fit in a packet.
+use solana_message::AddressLookupTableAccount;
+use solana_sdk::{instruction::Instruction, pubkey::Pubkey};
+
+use crate::client::{count_ephemeral_signers, proposal_create};
+use crate::error::ClientError;
+use crate::pda::{
+    get_ephemeral_signer_pda, get_proposal_pda, get_transaction_pda, get_vault_pda,
+    FORTIS_PROGRAM_ID,
+};
+use crate::state::{
+    ProposalCreateAccounts, ProposalExecuteAccounts, VaultTransaction, VaultTransactionMessage,
+};
+use crate::transaction_size::{proposal_create_size, proposal_execute_size, TransactionSize};
+use crate::ClientResult;
+
+/// One chunk of a batch, to be proposed at `transaction_index`.
+pub struct ProposalPlan {
+    pub transaction_index: u64,
+    pub proposal: Pubkey,
+    pub transaction: Pubkey,
+    /// The instructions of the chunk, in their original order.
+    pub instructions: Vec<Instruction>,
+    pub message: VaultTransactionMessage,
+    /// Number of ephemeral signers of the transaction the instructions reference.
+    pub ephemeral_signers: u8,
+    pub create_size: TransactionSize,
+    pub execute_size: TransactionSize,
+}
+
+impl ProposalPlan {
+    /// Accounts of the `proposal_create` instruction for this chunk.
+    pub fn create_accounts(&self, multisig: &Pubkey, creator: &Pubkey) -> ProposalCreateAccounts {
+        ProposalCreateAccounts {
+            multisig: *multisig,
+            trasaction: self.transaction,
+            creator: *creator,
+            proposal: self.proposal,
+            system_program: solana_system_interface::program::ID,
+        }
+    }
+
+    /// Builds the `proposal_create` instruction for this chunk.
+    pub fn proposal_create(
+        &self,
+        multisig: &Pubkey,
+        creator: &Pubkey,
+        voting_deadline: i64,
+        program_id: Option<Pubkey>,
+    ) -> ClientResult<Instruction> {
+        proposal_create(
+            self.create_accounts(multisig, creator),
+            self.ephemeral_signers,
+            &self.message,
+            voting_deadline,
+            program_id,
+        )
+    }
+}
+
+/// Greedily packs `instructions` into proposals starting at `first_transaction_index`,
+/// usually `multisig.transaction_index + 1`.
+///
+/// A chunk grows until adding the next instruction would make its `proposal_create`
+/// (paid by `creator`) or `proposal_execute` transaction exceed the packet size.
+/// Fails with `TransactionTooLarge` if a single instruction does not fit on its own,
+/// and with any other error compiling a chunk right away.
+///
+/// Ephemeral signers are counted like `FortisClient::propose` does. Since they are derived
+/// from the transaction index, an instruction signed by one must land in the chunk of
+/// that index, or planning fails with `UnknownSigner`.
+pub fn plan_proposals(
+    multisig: &Pubkey,
+    creator: &Pubkey,
+    first_transaction_index: u64,
+    instructions: Vec<Instruction>,
+    address_lookup_table_accounts: &[AddressLookupTableAccount],
+    program_id: Option<Pubkey>,
+) -> ClientResult<Vec<ProposalPlan>> {
+    let mut plans = Vec::new();
+    let mut chunk = Vec::new();
+    let mut fitting: Option<ProposalPlan> = None;
+
+    for instruction in instructions {
+        let transaction_index = first_transaction_index + plans.len() as u64;
+        chunk.push(instruction);
+
+        match plan_chunk(
+            multisig,
+            creator,
+            transaction_index,
+            &chunk,
+            address_lookup_table_accounts,
+            program_id,
+        ) {
+            Ok(plan) => fitting = Some(plan),
+            Err(err @ ClientError::TransactionTooLarge { .. }) => {
+                let Some(plan) = fitting.take() else {
+                    return Err(err);
+                };
+                // The last instruction does not fit in this chunk and starts the next one.
+                let instruction = chunk.pop().expect("chunk is not empty");
+                plans.push(plan);
+                chunk = vec![instruction];
+                fitting = Some(plan_chunk(
+                    multisig,
+                    creator,
+                    transaction_index + 1,
+                    &chunk,
+                    address_lookup_table_accounts,
+                    program_id,
+                )?);
+            }
+            Err(err) => return Err(err),
+        }
+    }
+    plans.extend(fitting);
+
+    Ok(plans)
+}
+
+/// Compiles `instructions` into the plan of one proposal, failing if either
+/// of its transactions is too large.
+fn plan_chunk(
+    multisig: &Pubkey,
+    creator: &Pubkey,
+    transaction_index: u64,
+    instructions: &[Instruction],
+    address_lookup_table_accounts: &[AddressLookupTableAccount],
+    program_id: Option<Pubkey>,
+) -> ClientResult<ProposalPlan> {
+    let program_key = program_id.unwrap_or(FORTIS_PROGRAM_ID);
+    let (vault, vault_bump) = get_vault_pda(multisig, Some(&program_key));
+    let (proposal, _) = get_proposal_pda(multisig, transaction_index, Some(&program_key));
+    let (transaction, bump) = get_transaction_pda(multisig, transaction_index, Some(&program_key));
+
+    let message =
+        VaultTransactionMessage::try_compile(&vault, instructions, address_lookup_table_accounts)?;
+    let ephemeral_signers =
+        count_ephemeral_signers(&message, multisig, transaction_index, Some(program_key))?;
+
+    let create_size = proposal_create_size(
+        creator,
+        ProposalCreateAccounts {
+            multisig: *multisig,
+            trasaction: transaction,
+            creator: *creator,
+            proposal,
+            system_program: solana_system_interface::program::ID,
+        },
+        ephemeral_signers,
+        &message,
+        0,
+        program_id,
+    )?
+    .check()?;
+
+    // The executing member is not known yet; any member pays for one signature like the creator.
+    let vault_transaction = VaultTransaction {
+        multisig: *multisig,
+        creator: *creator,
+        index: transaction_index,
+        bump,
+        vault_bump,
+        ephemeral_signer_bumps: (0..ephemeral_signers)
+            .map(|index| get_ephemeral_signer_pda(&transaction, index, Some(&program_key)).1)
+            .collect(),
+        message,
+    };
+    let execute_size = proposal_execute_size(
+        &vault_transaction,
+        ProposalExecuteAccounts {
+            multisig: *multisig,
+            proposal,
+            transaction,
+            member: *creator,
+        },
+        address_lookup_table_accounts,
+        program_id,
+    )?
+    .check()?;
+
+    Ok(ProposalPlan {
+        transaction_index,
+        proposal,
+        transaction,
+        instructions: instructions.to_vec(),
+        message: vault_transaction.message,
+        ephemeral_signers,
+        create_size,
+        execute_size,
+    })
+}
@@ -0,0 +1,99 @@
+//! `serde(with = ...)` adapters for the `serde` feature: pubkeys as base58 strings
+//! and opaque byte payloads as base64 strings.
+
+pub(crate) mod pubkey {
+    use std::str::FromStr;
+
+    use serde::{de::Error, Deserialize, Deserializer, Serializer};
+    use solana_sdk::pubkey::Pubkey;
+
+    pub fn serialize<S: Serializer>(pubkey: &Pubkey, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(pubkey)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Pubkey, D::Error> {
+        let value = String::deserialize(deserializer)?;
+        Pubkey::from_str(&value).map_err(|err| D::Error::custom(format!("{value}: {err}")))
+    }
+}
+
+pub(crate) mod option_pubkey {
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+    use solana_sdk::pubkey::Pubkey;
+
+    #[derive(Serialize, Deserialize)]
+    struct Base58(#[serde(with = "super::pubkey")] Pubkey);
+
+    pub fn serialize<S: Serializer>(
+        pubkey: &Option<Pubkey>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        pubkey.map(Base58).serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Option<Pubkey>, D::Error> {
+        Ok(Option::<Base58>::deserialize(deserializer)?.map(|Base58(pubkey)| pubkey))
+    }
+}
+
+pub(crate) mod pubkey_vec {
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+    use solana_sdk::pubkey::Pubkey;
+
+    #[derive(Serialize, Deserialize)]
+    struct Base58(#[serde(with = "super::pubkey")] Pubkey);
+
+    pub fn serialize<S: Serializer>(pubkeys: &[Pubkey], serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(pubkeys.iter().copied().map(Base58))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Vec<Pubkey>, D::Error> {
+        Ok(Vec::<Base58>::deserialize(deserializer)?
+            .into_iter()
+            .map(|Base58(pubkey)| pubkey)
+            .collect())
+    }
+}
+
+pub(crate) mod base64 {
+    use base64::{engine::general_purpose::STANDARD, Engine};
+    use serde::{de::Error, Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(bytes: &[u8], serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&STANDARD.encode(bytes))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<u8>, D::Error> {
+        let value = String::deserialize(deserializer)?;
+        STANDARD.decode(value).map_err(D::Error::custom)
+    }
+}
+
+pub(crate) mod option_pubkey_vec {
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+    use solana_sdk::pubkey::Pubkey;
+
+    #[derive(Serialize, Deserialize)]
+    struct Base58(#[serde(with = "super::pubkey")] Pubkey);
+
+    pub fn serialize<S: Serializer>(
+        pubkeys: &Option<Vec<Pubkey>>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        pubkeys
+            .as_ref()
+            .map(|pubkeys| pubkeys.iter().copied().map(Base58).collect::<Vec<_>>())
+            .serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Option<Vec<Pubkey>>, D::Error> {
+        Ok(Option::<Vec<Base58>>::deserialize(deserializer)?
+            .map(|pubkeys| pubkeys.into_iter().map(|Base58(pubkey)| pubkey).collect()))
+    }
+}
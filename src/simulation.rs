@@ -0,0 +1,573 @@
+//! Simulation of vault transactions, to catch failing proposals before they are approved.
+//!
+//! The inner instructions are decompiled into a standalone transaction paid and signed by
+//! the vault, then simulated without signature verification on a fresh blockhash. The
+//! program runs them through CPI at execution, so account and compute limits may differ
+//! slightly, but any instruction error the vault would hit shows up here.
+//!
+//! `simulate_balance_diff` also requests the post-simulation state of the vault and of every
+//! writable account, and compares it with their SOL and SPL token balances read just before.
+//! The simulation is pinned to run no earlier than the slot of that read, but the cluster may
+//! have moved on: transactions landing in between show up in the diff as well. The fee the
+//! vault pays as the fee payer of the simulated transaction is added back, since the member
+//! executing the proposal pays it.
+use std::collections::HashMap;
+use std::fmt;
+
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_client::rpc_config::{
+    CommitmentConfig, RpcSimulateTransactionAccountsConfig, RpcSimulateTransactionConfig,
+    UiAccountEncoding,
+};
+use solana_client::rpc_response::{Response, RpcSimulateTransactionResult, UiAccount};
+use solana_message::AddressLookupTableAccount;
+use solana_sdk::account::Account;
+use solana_sdk::instruction::InstructionError;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::transaction::TransactionError;
+use solana_transaction::versioned::VersionedTransaction;
+
+use crate::client::{
+    get_address_lookup_tables, get_transaction, message_error, MAX_MULTIPLE_ACCOUNTS,
+};
+use crate::compute_budget::{
+    compute_budget_instructions, simulation_config, simulation_transaction, MAX_COMPUTE_UNIT_LIMIT,
+};
+use crate::explain::{format_amount, SOL_DECIMALS, TOKEN_2022_PROGRAM_ID, TOKEN_PROGRAM_ID};
+use crate::pda::{get_transaction_pda, get_vault_pda, FORTIS_PROGRAM_ID};
+use crate::state::{VaultTransaction, VaultTransactionMessage};
+use crate::ClientResult;
+
+/// Outcome of simulating the instructions of a vault transaction.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VaultSimulation {
+    pub logs: Vec<String>,
+    pub units_consumed: Option<u64>,
+    /// The error the transaction failed with, `None` if it succeeded.
+    pub error: Option<TransactionError>,
+}
+
+impl VaultSimulation {
+    pub fn succeeded(&self) -> bool {
+        self.error.is_none()
+    }
+
+    /// The failing instruction, as an index into the vault message, and its error.
+    pub fn instruction_error(&self) -> Option<(usize, &InstructionError)> {
+        match &self.error {
+            Some(TransactionError::InstructionError(index, err)) => Some((
+                usize::from(*index).checked_sub(num_prefix_instructions())?,
+                err,
+            )),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn from_result(result: RpcSimulateTransactionResult) -> Self {
+        Self {
+            logs: result.logs.unwrap_or_default(),
+            units_consumed: result.units_consumed,
+            error: result.err.map(TransactionError::from),
+        }
+    }
+}
+
+/// Simulates `message` as executed by the vault of `multisig_key`, fetching its lookup tables.
+pub async fn simulate_vault_message(
+    rpc_client: &RpcClient,
+    multisig_key: &Pubkey,
+    message: &VaultTransactionMessage,
+    program_id: Option<Pubkey>,
+) -> ClientResult<VaultSimulation> {
+    let address_lookup_table_accounts = get_address_lookup_tables(rpc_client, message).await?;
+    let transaction = vault_simulation_transaction(
+        multisig_key,
+        message,
+        &address_lookup_table_accounts,
+        program_id,
+    )?;
+
+    let result = rpc_client
+        .simulate_transaction_with_config(&transaction, simulation_config(rpc_client.commitment()))
+        .await?
+        .value;
+    Ok(VaultSimulation::from_result(result))
+}
+
+/// Simulates a vault transaction already stored on chain.
+pub async fn simulate_vault_transaction(
+    rpc_client: &RpcClient,
+    vault_transaction: &VaultTransaction,
+    program_id: Option<Pubkey>,
+) -> ClientResult<VaultSimulation> {
+    simulate_vault_message(
+        rpc_client,
+        &vault_transaction.multisig,
+        &vault_transaction.message,
+        program_id,
+    )
+    .await
+}
+
+/// Fetches the transaction of the proposal at `transaction_index` and simulates it.
+pub async fn simulate_proposal(
+    rpc_client: &RpcClient,
+    multisig_key: &Pubkey,
+    transaction_index: u64,
+    program_id: Option<Pubkey>,
+) -> ClientResult<VaultSimulation> {
+    let transaction_key = get_transaction_pda(
+        multisig_key,
+        transaction_index,
+        Some(&program_id.unwrap_or(FORTIS_PROGRAM_ID)),
+    )
+    .0;
+    let vault_transaction = get_transaction(rpc_client, &transaction_key, program_id).await?;
+
+    simulate_vault_transaction(rpc_client, &vault_transaction, program_id).await
+}
+
+/// The inner instructions of `message` in a transaction paid by the vault, with the
+/// maximum compute unit limit prepended. Signatures are left empty.
+pub(crate) fn vault_simulation_transaction(
+    multisig_key: &Pubkey,
+    message: &VaultTransactionMessage,
+    address_lookup_table_accounts: &[AddressLookupTableAccount],
+    program_id: Option<Pubkey>,
+) -> ClientResult<VersionedTransaction> {
+    let program_id = program_id.unwrap_or(FORTIS_PROGRAM_ID);
+    let vault = get_vault_pda(multisig_key, Some(&program_id)).0;
+    let instructions = message
+        .decompile(address_lookup_table_accounts)
+        .map_err(message_error)?;
+
+    simulation_transaction(
+        &vault,
+        &instructions,
+        address_lookup_table_accounts,
+        None,
+        None,
+    )
+}
+
+/// Number of instructions `simulation_transaction` puts before the vault instructions.
+fn num_prefix_instructions() -> usize {
+    compute_budget_instructions(Some(MAX_COMPUTE_UNIT_LIMIT), None).len()
+}
+
+/// Fee per signature, charged to the vault in simulations of RPC nodes not reporting the fee.
+const LAMPORTS_PER_SIGNATURE: u64 = 5_000;
+
+/// Size of an SPL token account without extensions.
+const TOKEN_ACCOUNT_SIZE: usize = 165;
+/// Size of an SPL mint without extensions.
+const MINT_SIZE: usize = 82;
+/// Offset of the Token-2022 account type byte, following the base account.
+const TOKEN_2022_ACCOUNT_TYPE_OFFSET: usize = TOKEN_ACCOUNT_SIZE;
+const TOKEN_2022_ACCOUNT_TYPE_MINT: u8 = 1;
+const TOKEN_2022_ACCOUNT_TYPE_ACCOUNT: u8 = 2;
+
+/// Balances of the vault and of the writable accounts before and after a simulated vault transaction.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BalanceDiff {
+    pub vault: Pubkey,
+    pub simulation: VaultSimulation,
+    /// Slot the balances before the transaction were read at.
+    pub pre_slot: u64,
+    /// Slot the simulation ran at, never before `pre_slot`.
+    pub post_slot: u64,
+    /// One entry per account, the vault first. Empty if the simulation failed.
+    pub changes: Vec<BalanceChange>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BalanceChange {
+    pub address: Pubkey,
+    pub pre_lamports: u64,
+    pub post_lamports: u64,
+    /// Set for SPL Token and Token-2022 accounts, before or after the transaction.
+    pub token: Option<TokenBalanceChange>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TokenBalanceChange {
+    pub mint: Pubkey,
+    /// Wallet owning the token account.
+    pub owner: Pubkey,
+    /// `None` if the mint could not be fetched.
+    pub decimals: Option<u8>,
+    pub pre_amount: u64,
+    pub post_amount: u64,
+}
+
+impl BalanceDiff {
+    /// Changes of the accounts whose SOL or token balance moved.
+    pub fn changed(&self) -> impl Iterator<Item = &BalanceChange> {
+        self.changes.iter().filter(|change| {
+            change.lamports_delta() != 0
+                || change.token.is_some_and(|token| token.amount_delta() != 0)
+        })
+    }
+
+    pub fn get(&self, address: &Pubkey) -> Option<&BalanceChange> {
+        self.changes
+            .iter()
+            .find(|change| change.address == *address)
+    }
+}
+
+impl BalanceChange {
+    pub fn lamports_delta(&self) -> i128 {
+        i128::from(self.post_lamports) - i128::from(self.pre_lamports)
+    }
+}
+
+impl TokenBalanceChange {
+    pub fn amount_delta(&self) -> i128 {
+        i128::from(self.post_amount) - i128::from(self.pre_amount)
+    }
+}
+
+/// Simulates `message` and reports the balance changes of the vault and of its writable accounts.
+pub async fn simulate_balance_diff(
+    rpc_client: &RpcClient,
+    multisig_key: &Pubkey,
+    message: &VaultTransactionMessage,
+    program_id: Option<Pubkey>,
+) -> ClientResult<BalanceDiff> {
+    let address_lookup_table_accounts = get_address_lookup_tables(rpc_client, message).await?;
+    let request = BalanceDiffRequest::new(
+        multisig_key,
+        message,
+        &address_lookup_table_accounts,
+        program_id,
+    )?;
+
+    let mut pre_accounts = Vec::with_capacity(request.addresses.len());
+    let mut pre_slot = 0;
+    for chunk in request.addresses.chunks(MAX_MULTIPLE_ACCOUNTS) {
+        let response = rpc_client
+            .get_multiple_accounts_with_commitment(chunk, rpc_client.commitment())
+            .await?;
+        pre_slot = pre_slot.max(response.context.slot);
+        pre_accounts.extend(response.value);
+    }
+    let response = rpc_client
+        .simulate_transaction_with_config(
+            &request.transaction,
+            request.simulation_config(rpc_client.commitment(), pre_slot),
+        )
+        .await?;
+    let simulated = request.simulated((pre_slot, pre_accounts), response);
+
+    let mut mint_accounts = Vec::with_capacity(simulated.mints.len());
+    for chunk in simulated.mints.chunks(MAX_MULTIPLE_ACCOUNTS) {
+        mint_accounts.extend(rpc_client.get_multiple_accounts(chunk).await?);
+    }
+
+    Ok(simulated.diff(&mint_accounts))
+}
+
+/// The simulation `simulate_balance_diff` sends and the accounts it compares.
+pub(crate) struct BalanceDiffRequest {
+    pub(crate) vault: Pubkey,
+    pub(crate) transaction: VersionedTransaction,
+    /// The vault followed by the writable accounts of the message, without duplicates.
+    pub(crate) addresses: Vec<Pubkey>,
+}
+
+impl BalanceDiffRequest {
+    pub(crate) fn new(
+        multisig_key: &Pubkey,
+        message: &VaultTransactionMessage,
+        address_lookup_table_accounts: &[AddressLookupTableAccount],
+        program_id: Option<Pubkey>,
+    ) -> ClientResult<Self> {
+        let transaction = vault_simulation_transaction(
+            multisig_key,
+            message,
+            address_lookup_table_accounts,
+            program_id,
+        )?;
+        let vault = get_vault_pda(multisig_key, Some(&program_id.unwrap_or(FORTIS_PROGRAM_ID))).0;
+        let addresses = balance_diff_addresses(&vault, message, address_lookup_table_accounts)?;
+
+        Ok(Self {
+            vault,
+            transaction,
+            addresses,
+        })
+    }
+
+    /// `simulation_config` also returning the state of `addresses` after the simulation,
+    /// run on a bank at `pre_slot` or later.
+    pub(crate) fn simulation_config(
+        &self,
+        commitment: CommitmentConfig,
+        pre_slot: u64,
+    ) -> RpcSimulateTransactionConfig {
+        RpcSimulateTransactionConfig {
+            accounts: Some(RpcSimulateTransactionAccountsConfig {
+                encoding: Some(UiAccountEncoding::Base64),
+                addresses: self.addresses.iter().map(Pubkey::to_string).collect(),
+            }),
+            min_context_slot: Some(pre_slot),
+            ..simulation_config(commitment)
+        }
+    }
+
+    /// Pairs the accounts read at `pre_slot` with the simulated ones of `response`.
+    pub(crate) fn simulated(
+        self,
+        (pre_slot, pre_accounts): (u64, Vec<Option<Account>>),
+        response: Response<RpcSimulateTransactionResult>,
+    ) -> SimulatedBalances {
+        let post_accounts =
+            simulated_accounts(&response.value, &self.transaction, self.addresses.len());
+        let mints = token_mints(pre_accounts.iter().chain(&post_accounts));
+
+        SimulatedBalances {
+            vault: self.vault,
+            simulation: VaultSimulation::from_result(response.value),
+            slots: (pre_slot, response.context.slot),
+            addresses: self.addresses,
+            pre_accounts,
+            post_accounts,
+            mints,
+        }
+    }
+}
+
+/// The balances before and after the simulation, waiting for the decimals of their mints.
+pub(crate) struct SimulatedBalances {
+    vault: Pubkey,
+    simulation: VaultSimulation,
+    slots: (u64, u64),
+    addresses: Vec<Pubkey>,
+    pre_accounts: Vec<Option<Account>>,
+    post_accounts: Vec<Option<Account>>,
+    /// Mints of the token accounts, to fetch for `diff`.
+    pub(crate) mints: Vec<Pubkey>,
+}
+
+impl SimulatedBalances {
+    /// Compares the balances, given the accounts fetched for `mints`.
+    pub(crate) fn diff(self, mint_accounts: &[Option<Account>]) -> BalanceDiff {
+        balance_diff(
+            self.vault,
+            self.simulation,
+            self.slots,
+            &self.addresses,
+            self.pre_accounts,
+            self.post_accounts,
+            mint_decimals(&self.mints, mint_accounts),
+        )
+    }
+}
+
+/// The vault followed by the writable accounts of `message`, without duplicates.
+fn balance_diff_addresses(
+    vault: &Pubkey,
+    message: &VaultTransactionMessage,
+    address_lookup_table_accounts: &[AddressLookupTableAccount],
+) -> ClientResult<Vec<Pubkey>> {
+    let account_metas = message
+        .resolve_account_metas(address_lookup_table_accounts)
+        .map_err(message_error)?;
+
+    let mut addresses = vec![*vault];
+    for meta in account_metas {
+        if meta.is_writable && !addresses.contains(&meta.pubkey) {
+            addresses.push(meta.pubkey);
+        }
+    }
+    Ok(addresses)
+}
+
+/// The fee the vault paid as the fee payer of the simulated `transaction`.
+fn simulated_fee(result: &RpcSimulateTransactionResult, transaction: &VersionedTransaction) -> u64 {
+    result.fee.unwrap_or_else(|| {
+        u64::from(transaction.message.header().num_required_signatures) * LAMPORTS_PER_SIGNATURE
+    })
+}
+
+/// The accounts returned by a successful simulation of `transaction`, all `None` if it failed.
+/// The vault, first, gets back the fee it paid as the fee payer.
+fn simulated_accounts(
+    result: &RpcSimulateTransactionResult,
+    transaction: &VersionedTransaction,
+    len: usize,
+) -> Vec<Option<Account>> {
+    let (None, Some(accounts)) = (&result.err, &result.accounts) else {
+        return vec![None; len];
+    };
+    let mut accounts: Vec<Option<Account>> = accounts
+        .iter()
+        .map(|account| account.as_ref().and_then(UiAccount::decode))
+        .collect();
+    if let Some(Some(vault)) = accounts.first_mut() {
+        vault.lamports = vault
+            .lamports
+            .saturating_add(simulated_fee(result, transaction));
+    }
+    accounts
+}
+
+/// Mints of the token accounts among `accounts`, without duplicates.
+fn token_mints<'a>(accounts: impl Iterator<Item = &'a Option<Account>>) -> Vec<Pubkey> {
+    let mut mints = Vec::new();
+    for account in accounts.flatten() {
+        if let Some((mint, _, _)) = token_account(account) {
+            if !mints.contains(&mint) {
+                mints.push(mint);
+            }
+        }
+    }
+    mints
+}
+
+fn mint_decimals(mints: &[Pubkey], mint_accounts: &[Option<Account>]) -> HashMap<Pubkey, u8> {
+    mints
+        .iter()
+        .zip(mint_accounts)
+        .filter_map(|(mint, account)| Some((*mint, decimals(account.as_ref()?)?)))
+        .collect()
+}
+
+fn balance_diff(
+    vault: Pubkey,
+    simulation: VaultSimulation,
+    (pre_slot, post_slot): (u64, u64),
+    addresses: &[Pubkey],
+    pre_accounts: Vec<Option<Account>>,
+    post_accounts: Vec<Option<Account>>,
+    decimals: HashMap<Pubkey, u8>,
+) -> BalanceDiff {
+    let changes = if simulation.succeeded() {
+        addresses
+            .iter()
+            .zip(pre_accounts.into_iter().zip(post_accounts))
+            .map(|(address, (pre, post))| {
+                balance_change(*address, pre.as_ref(), post.as_ref(), &decimals)
+            })
+            .collect()
+    } else {
+        Vec::new()
+    };
+
+    BalanceDiff {
+        vault,
+        simulation,
+        pre_slot,
+        post_slot,
+        changes,
+    }
+}
+
+fn balance_change(
+    address: Pubkey,
+    pre: Option<&Account>,
+    post: Option<&Account>,
+    decimals: &HashMap<Pubkey, u8>,
+) -> BalanceChange {
+    let pre_token = pre.and_then(token_account);
+    let post_token = post.and_then(token_account);
+    // A closed or created token account still reports its mint and owner from the other side.
+    let token = pre_token
+        .or(post_token)
+        .map(|(mint, owner, _)| TokenBalanceChange {
+            mint,
+            owner,
+            decimals: decimals.get(&mint).copied(),
+            pre_amount: pre_token.map_or(0, |(_, _, amount)| amount),
+            post_amount: post_token.map_or(0, |(_, _, amount)| amount),
+        });
+
+    BalanceChange {
+        address,
+        pre_lamports: pre.map_or(0, |account| account.lamports),
+        post_lamports: post.map_or(0, |account| account.lamports),
+        token,
+    }
+}
+
+/// Mint, owner and amount of an SPL Token or Token-2022 account.
+fn token_account(account: &Account) -> Option<(Pubkey, Pubkey, u64)> {
+    if !is_token_program(&account.owner) {
+        return None;
+    }
+    let data = &account.data;
+    let is_account = data.len() == TOKEN_ACCOUNT_SIZE
+        || data.get(TOKEN_2022_ACCOUNT_TYPE_OFFSET) == Some(&TOKEN_2022_ACCOUNT_TYPE_ACCOUNT);
+    if !is_account {
+        return None;
+    }
+
+    let mint = Pubkey::new_from_array(data[0..32].try_into().ok()?);
+    let owner = Pubkey::new_from_array(data[32..64].try_into().ok()?);
+    let amount = u64::from_le_bytes(data[64..72].try_into().ok()?);
+    Some((mint, owner, amount))
+}
+
+/// Decimals of an SPL Token or Token-2022 mint.
+fn decimals(account: &Account) -> Option<u8> {
+    if !is_token_program(&account.owner) {
+        return None;
+    }
+    let data = &account.data;
+    let is_mint = data.len() == MINT_SIZE
+        || data.get(TOKEN_2022_ACCOUNT_TYPE_OFFSET) == Some(&TOKEN_2022_ACCOUNT_TYPE_MINT);
+    // `mint_authority: COption<Pubkey>` and `supply: u64` come first.
+    is_mint.then(|| data.get(44).copied()).flatten()
+}
+
+fn is_token_program(program_id: &Pubkey) -> bool {
+    *program_id == TOKEN_PROGRAM_ID || *program_id == TOKEN_2022_PROGRAM_ID
+}
+
+fn format_delta(delta: i128, decimals: u8) -> String {
+    let sign = if delta < 0 { "-" } else { "+" };
+    let magnitude = u64::try_from(delta.unsigned_abs()).unwrap_or(u64::MAX);
+    format!("{sign}{}", format_amount(magnitude, decimals))
+}
+
+impl fmt::Display for BalanceDiff {
+    /// One line per moved balance, e.g. `<vault> (vault): -12.5 <mint>`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(err) = &self.simulation.error {
+            return write!(f, "simulation failed: {err}");
+        }
+        for change in self.changed() {
+            let label = |holder: &Pubkey| {
+                if *holder == self.vault {
+                    format!("{holder} (vault)")
+                } else {
+                    holder.to_string()
+                }
+            };
+            if change.lamports_delta() != 0 {
+                writeln!(
+                    f,
+                    "{}: {} SOL",
+                    label(&change.address),
+                    format_delta(change.lamports_delta(), SOL_DECIMALS)
+                )?;
+            }
+            if let Some(token) = change.token.filter(|token| token.amount_delta() != 0) {
+                let amount = match token.decimals {
+                    Some(decimals) => format_delta(token.amount_delta(), decimals),
+                    None => format!("{:+} base units of", token.amount_delta()),
+                };
+                writeln!(
+                    f,
+                    "{} (token account {}): {} {}",
+                    label(&token.owner),
+                    change.address,
+                    amount,
+                    token.mint
+                )?;
+            }
+        }
+        Ok(())
+    }
+}
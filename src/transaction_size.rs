@@ -0,0 +1,144 @@
+//! Exact serialized size of the transactions carrying Fortis instructions,
+//! to catch oversized transactions before they are sent.
+//!
+//! `transaction_size` measures any set of instructions, such as the output of the builders
+//! in `client`. `proposal_create_size` and `proposal_execute_size` cover the two builders
+//! whose size grows with the vault transaction.
+use solana_message::{AddressLookupTableAccount, Hash, VersionedMessage};
+use solana_sdk::{instruction::Instruction, pubkey::Pubkey};
+
+use crate::client::{proposal_create, proposal_execute_from_transaction};
+use crate::error::ClientError;
+use crate::fortis_client::compile_message;
+use crate::state::{
+    ProposalCreateAccounts, ProposalExecuteAccounts, VaultTransaction, VaultTransactionMessage,
+};
+use crate::ClientResult;
+
+/// Maximum size of a serialized transaction, the data size of a network packet.
+pub const PACKET_DATA_SIZE: usize = 1232;
+
+const SIGNATURE_SIZE: usize = 64;
+const PUBKEY_SIZE: usize = 32;
+
+/// Size breakdown of a serialized transaction, in bytes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TransactionSize {
+    /// Whole transaction: signatures followed by the message.
+    pub size: usize,
+    /// Signatures, including their length prefix.
+    pub signatures: usize,
+    /// Message, account keys included.
+    pub message: usize,
+    /// Static account keys, including their length prefix.
+    pub account_keys: usize,
+    pub limit: usize,
+}
+
+impl TransactionSize {
+    /// Bytes left before reaching the limit, negative when the transaction is too large.
+    pub fn headroom(&self) -> isize {
+        self.limit as isize - self.size as isize
+    }
+
+    pub fn fits(&self) -> bool {
+        self.size <= self.limit
+    }
+
+    /// Returns `TransactionTooLarge` when the transaction exceeds the limit.
+    pub fn check(self) -> ClientResult<Self> {
+        if self.fits() {
+            Ok(self)
+        } else {
+            Err(ClientError::TransactionTooLarge {
+                size: self.size,
+                limit: self.limit,
+            })
+        }
+    }
+}
+
+/// Size of the transaction paid by `payer` carrying `instructions`, compiled the way
+/// `FortisClient` sends it: legacy without lookup tables, v0 otherwise.
+///
+/// The signers are the payer and every signer of the instructions.
+pub fn transaction_size(
+    payer: &Pubkey,
+    instructions: &[Instruction],
+    address_lookup_table_accounts: &[AddressLookupTableAccount],
+) -> ClientResult<TransactionSize> {
+    let message = compile_message(
+        payer,
+        instructions,
+        address_lookup_table_accounts,
+        Hash::default(),
+    )?;
+
+    Ok(message_size(&message))
+}
+
+/// Size of a `proposal_create` transaction paid by `payer`.
+pub fn proposal_create_size(
+    payer: &Pubkey,
+    accounts: ProposalCreateAccounts,
+    num_ephemeral_signers: u8,
+    message: &VaultTransactionMessage,
+    voting_deadline: i64,
+    program_id: Option<Pubkey>,
+) -> ClientResult<TransactionSize> {
+    let instruction = proposal_create(
+        accounts,
+        num_ephemeral_signers,
+        message,
+        voting_deadline,
+        program_id,
+    )?;
+
+    transaction_size(payer, &[instruction], &[])
+}
+
+/// Size of a `proposal_execute` transaction paid by the executing member.
+///
+/// The outer transaction uses the same lookup tables as the vault message.
+pub fn proposal_execute_size(
+    vault_transaction: &VaultTransaction,
+    accounts: ProposalExecuteAccounts,
+    address_lookup_table_accounts: &[AddressLookupTableAccount],
+    program_id: Option<Pubkey>,
+) -> ClientResult<TransactionSize> {
+    let payer = accounts.member;
+    let instruction = proposal_execute_from_transaction(
+        vault_transaction,
+        accounts,
+        address_lookup_table_accounts,
+        program_id,
+    )?;
+
+    transaction_size(&payer, &[instruction], address_lookup_table_accounts)
+}
+
+pub(crate) fn message_size(message: &VersionedMessage) -> TransactionSize {
+    let num_signatures = usize::from(message.header().num_required_signatures);
+    let num_account_keys = message.static_account_keys().len();
+
+    let signatures = short_vec_len(num_signatures) + num_signatures * SIGNATURE_SIZE;
+    let account_keys = short_vec_len(num_account_keys) + num_account_keys * PUBKEY_SIZE;
+    let message = message.serialize().len();
+
+    TransactionSize {
+        size: signatures + message,
+        signatures,
+        message,
+        account_keys,
+        limit: PACKET_DATA_SIZE,
+    }
+}
+
+/// Length of the compact-u16 prefix encoding `len`.
+fn short_vec_len(len: usize) -> usize {
+    match len {
+        0..=0x7f => 1,
+        0x80..=0x3fff => 2,
+        _ => 3,
+    }
+}
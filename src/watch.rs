@@ -0,0 +1,674 @@
+//! Live proposal events for a multisig, from websocket account subscriptions.
+//!
+//! `watch_proposals` subscribes to the multisig account and to each open proposal (or to
+//! every proposal of the multisig at once with `programSubscribe`), turns account updates
+//! into `ProposalEvent`s and resubscribes whenever the connection drops. The accounts are
+//! also polled over RPC after each (re)connection and every `poll_interval`, so updates sent
+//! while disconnected are not lost.
+//!
+//! The diffing itself is done by `ProposalTracker`, which can be fed from any other source.
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use futures::future::BoxFuture;
+use futures::stream::{BoxStream, SelectAll};
+use futures::{Stream, StreamExt};
+use solana_client::nonblocking::pubsub_client::PubsubClient;
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_client::rpc_config::CommitmentConfig;
+use solana_client::rpc_config::{
+    RpcAccountInfoConfig, RpcProgramAccountsConfig, UiAccountEncoding,
+};
+use solana_client::rpc_filter::{Memcmp, RpcFilterType};
+use solana_client::rpc_response::UiAccount;
+use solana_sdk::pubkey::Pubkey;
+use tokio::sync::mpsc::{unbounded_channel, UnboundedSender};
+
+use crate::client::{decode_account, get_multisig, get_proposals};
+use crate::error::ClientError;
+use crate::pda::{get_proposal_pda, FORTIS_PROGRAM_ID};
+use crate::state::{FortisAccount, Multisig, Proposal, ProposalStatus};
+use crate::ClientResult;
+
+/// A change in the lifecycle of a proposal of the watched multisig.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ProposalEvent {
+    ProposalCreated {
+        transaction_index: u64,
+        proposal: Proposal,
+    },
+    Approved {
+        transaction_index: u64,
+        member: Pubkey,
+    },
+    /// The approvals met the multisig threshold: the proposal can be executed.
+    ThresholdReached {
+        transaction_index: u64,
+    },
+    Executed {
+        transaction_index: u64,
+    },
+    /// The voting deadline passed while the proposal was still `Active`.
+    Expired {
+        transaction_index: u64,
+    },
+    /// The proposal account was closed.
+    Closed {
+        transaction_index: u64,
+    },
+}
+
+/// Last known state of the proposals of a multisig, diffed against incoming updates.
+#[derive(Debug, Clone)]
+pub struct ProposalTracker {
+    multisig_key: Pubkey,
+    program_id: Pubkey,
+    multisig: Option<Multisig>,
+    proposals: BTreeMap<u64, TrackedProposal>,
+}
+
+#[derive(Debug, Clone)]
+struct TrackedProposal {
+    key: Pubkey,
+    /// `None` while the multisig announced the index but the account was not seen yet.
+    proposal: Option<Proposal>,
+    threshold_reached: bool,
+    executed: bool,
+    expired: bool,
+}
+
+impl ProposalTracker {
+    pub fn new(multisig_key: Pubkey, program_id: Option<Pubkey>) -> Self {
+        Self {
+            multisig_key,
+            program_id: program_id.unwrap_or(FORTIS_PROGRAM_ID),
+            multisig: None,
+            proposals: BTreeMap::new(),
+        }
+    }
+
+    /// Records the current state without emitting events, `None` for closed proposals.
+    pub fn seed(
+        &mut self,
+        multisig: Multisig,
+        proposals: impl IntoIterator<Item = (u64, Option<Proposal>)>,
+        now: i64,
+    ) {
+        self.multisig = Some(multisig);
+        for (transaction_index, proposal) in proposals {
+            if let Some(proposal) = proposal {
+                self.insert_seen(transaction_index, proposal, now);
+            }
+        }
+    }
+
+    /// Applies a new state of the multisig account.
+    ///
+    /// Indices created since the last update start being tracked, see `watched_indexes`.
+    /// A lowered threshold or a membership change can complete open proposals.
+    pub fn update_multisig(&mut self, multisig: Multisig, now: i64) -> Vec<ProposalEvent> {
+        for transaction_index in self.transaction_index() + 1..=multisig.transaction_index {
+            if !self.proposals.contains_key(&transaction_index) {
+                self.insert_pending(transaction_index);
+            }
+        }
+        self.multisig = Some(multisig);
+        self.check_deadlines(now)
+    }
+
+    /// Applies a new state of the proposal at `transaction_index`, `None` once it is closed.
+    pub fn update_proposal(
+        &mut self,
+        transaction_index: u64,
+        proposal: Option<Proposal>,
+        now: i64,
+    ) -> Vec<ProposalEvent> {
+        if !self.proposals.contains_key(&transaction_index) {
+            match proposal {
+                None => return Vec::new(),
+                Some(proposal) if transaction_index <= self.transaction_index() => {
+                    // An older proposal outside of the tracked ones: its previous
+                    // state is unknown, so it is adopted without events.
+                    self.insert_seen(transaction_index, proposal, now);
+                    return Vec::new();
+                }
+                // Seen before the multisig update announcing it.
+                Some(_) => self.insert_pending(transaction_index),
+            }
+        }
+        let Some(tracked) = self.proposals.get_mut(&transaction_index) else {
+            return Vec::new();
+        };
+
+        let Some(proposal) = proposal else {
+            let was_seen = tracked.proposal.is_some();
+            self.proposals.remove(&transaction_index);
+            return if was_seen {
+                vec![ProposalEvent::Closed { transaction_index }]
+            } else {
+                Vec::new()
+            };
+        };
+
+        let mut events = Vec::new();
+        let previously_approved = match &tracked.proposal {
+            Some(previous) => previous.approved.as_slice(),
+            None => {
+                events.push(ProposalEvent::ProposalCreated {
+                    transaction_index,
+                    proposal: proposal.clone(),
+                });
+                &[]
+            }
+        };
+        events.extend(
+            proposal
+                .approved
+                .iter()
+                .filter(|member| !previously_approved.contains(member))
+                .map(|member| ProposalEvent::Approved {
+                    transaction_index,
+                    member: *member,
+                }),
+        );
+        tracked.proposal = Some(proposal);
+
+        events.extend(self.check_proposal(transaction_index, now));
+        events
+    }
+
+    /// Reports the proposals whose threshold was reached, or whose deadline passed at `now`,
+    /// since the last call.
+    pub fn check_deadlines(&mut self, now: i64) -> Vec<ProposalEvent> {
+        let indexes: Vec<u64> = self.proposals.keys().copied().collect();
+        indexes
+            .into_iter()
+            .flat_map(|transaction_index| self.check_proposal(transaction_index, now))
+            .collect()
+    }
+
+    /// Indices of the proposals still worth watching: open ones and announced ones not seen yet.
+    ///
+    /// Executed and expired proposals stay tracked until closed but are no longer watched.
+    pub fn watched_indexes(&self) -> Vec<u64> {
+        self.proposals
+            .iter()
+            .filter(|(_, tracked)| !tracked.is_finalized())
+            .map(|(transaction_index, _)| *transaction_index)
+            .collect()
+    }
+
+    /// Transaction index of the tracked proposal stored at `proposal_key`.
+    pub fn proposal_index(&self, proposal_key: &Pubkey) -> Option<u64> {
+        self.proposals
+            .iter()
+            .find(|(_, tracked)| tracked.key == *proposal_key)
+            .map(|(transaction_index, _)| *transaction_index)
+    }
+
+    pub fn proposal(&self, transaction_index: u64) -> Option<&Proposal> {
+        self.proposals
+            .get(&transaction_index)
+            .and_then(|tracked| tracked.proposal.as_ref())
+    }
+
+    pub fn multisig(&self) -> Option<&Multisig> {
+        self.multisig.as_ref()
+    }
+
+    fn transaction_index(&self) -> u64 {
+        self.multisig
+            .as_ref()
+            .map_or(0, |multisig| multisig.transaction_index)
+    }
+
+    fn insert_pending(&mut self, transaction_index: u64) {
+        let key = get_proposal_pda(
+            &self.multisig_key,
+            transaction_index,
+            Some(&self.program_id),
+        )
+        .0;
+        self.proposals.insert(
+            transaction_index,
+            TrackedProposal {
+                key,
+                proposal: None,
+                threshold_reached: false,
+                executed: false,
+                expired: false,
+            },
+        );
+    }
+
+    /// Tracks a proposal in its current state, as if its past events were already reported.
+    fn insert_seen(&mut self, transaction_index: u64, proposal: Proposal, now: i64) {
+        self.insert_pending(transaction_index);
+        let threshold_reached = self
+            .multisig
+            .as_ref()
+            .is_some_and(|multisig| threshold_reached(&proposal, multisig));
+        if let Some(tracked) = self.proposals.get_mut(&transaction_index) {
+            tracked.threshold_reached = threshold_reached;
+            tracked.executed = proposal.status == ProposalStatus::Executed;
+            tracked.expired = is_expired(&proposal, now);
+            tracked.proposal = Some(proposal);
+        }
+    }
+
+    /// Emits the threshold, execution and expiry events not reported yet for one proposal.
+    fn check_proposal(&mut self, transaction_index: u64, now: i64) -> Vec<ProposalEvent> {
+        let Some(tracked) = self.proposals.get_mut(&transaction_index) else {
+            return Vec::new();
+        };
+        let Some(proposal) = &tracked.proposal else {
+            return Vec::new();
+        };
+
+        let mut events = Vec::new();
+        let reached = self
+            .multisig
+            .as_ref()
+            .is_some_and(|multisig| threshold_reached(proposal, multisig));
+        if reached && !tracked.threshold_reached {
+            tracked.threshold_reached = true;
+            events.push(ProposalEvent::ThresholdReached { transaction_index });
+        }
+        if proposal.status == ProposalStatus::Executed && !tracked.executed {
+            tracked.executed = true;
+            events.push(ProposalEvent::Executed { transaction_index });
+        }
+        if is_expired(proposal, now) && !tracked.expired {
+            tracked.expired = true;
+            events.push(ProposalEvent::Expired { transaction_index });
+        }
+        events
+    }
+}
+
+impl TrackedProposal {
+    /// Executed or expired: no further update is expected before the account is closed.
+    fn is_finalized(&self) -> bool {
+        self.executed || self.expired
+    }
+}
+
+fn threshold_reached(proposal: &Proposal, multisig: &Multisig) -> bool {
+    match proposal.status {
+        ProposalStatus::Active => proposal.remaining_approvals(multisig) == 0,
+        ProposalStatus::Approved | ProposalStatus::Executed => true,
+    }
+}
+
+fn is_expired(proposal: &Proposal, now: i64) -> bool {
+    proposal.status == ProposalStatus::Active && proposal.is_expired(now)
+}
+
+/// Settings of `watch_proposals`.
+#[derive(Debug, Clone)]
+pub struct WatchConfig {
+    /// Websocket endpoint of the cluster, e.g. `wss://api.devnet.solana.com`.
+    pub ws_url: String,
+    pub commitment: CommitmentConfig,
+    /// Use a single `programSubscribe` filtered by multisig instead of one
+    /// subscription per open proposal.
+    ///
+    /// Only with `programSubscribe` is `Closed` reported for executed and expired
+    /// proposals, which are otherwise unsubscribed from once finalized.
+    pub program_subscribe: bool,
+    /// Interval of the RPC polling that catches updates the subscriptions missed.
+    pub poll_interval: Duration,
+    /// Delay before the first reconnection attempt, doubled up to `max_reconnect_delay`.
+    pub reconnect_delay: Duration,
+    pub max_reconnect_delay: Duration,
+    /// Number of most recent proposals tracked when the watch starts.
+    pub backfill: u64,
+}
+
+impl WatchConfig {
+    pub fn new(ws_url: impl Into<String>) -> Self {
+        Self {
+            ws_url: ws_url.into(),
+            commitment: CommitmentConfig::confirmed(),
+            program_subscribe: false,
+            poll_interval: Duration::from_secs(30),
+            reconnect_delay: Duration::from_millis(500),
+            max_reconnect_delay: Duration::from_secs(30),
+            backfill: 100,
+        }
+    }
+}
+
+/// Streams the proposal events of `multisig_key` until the stream is dropped.
+///
+/// Connection and RPC failures are yielded as errors and followed by a reconnection,
+/// the stream itself never ends. Must be called within a tokio runtime.
+pub fn watch_proposals(
+    rpc_client: Arc<RpcClient>,
+    multisig_key: Pubkey,
+    config: WatchConfig,
+    program_id: Option<Pubkey>,
+) -> impl Stream<Item = ClientResult<ProposalEvent>> {
+    let (sender, receiver) = unbounded_channel();
+    let watcher = Watcher {
+        rpc_client,
+        multisig_key,
+        program_id: program_id.unwrap_or(FORTIS_PROGRAM_ID),
+        config,
+        events: sender,
+    };
+    tokio::spawn(watcher.run());
+
+    futures::stream::unfold(receiver, |mut receiver| async move {
+        receiver.recv().await.map(|event| (event, receiver))
+    })
+}
+
+/// Ends an account subscription, as returned by `PubsubClient::account_subscribe`.
+type UnsubscribeFn = Box<dyn FnOnce() -> BoxFuture<'static, ()> + Send>;
+
+/// An account notification, tagged with what it belongs to.
+enum Update {
+    Multisig(UiAccount),
+    Proposal(u64, UiAccount),
+    Program(Pubkey, UiAccount),
+}
+
+struct Watcher {
+    rpc_client: Arc<RpcClient>,
+    multisig_key: Pubkey,
+    program_id: Pubkey,
+    config: WatchConfig,
+    events: UnboundedSender<ClientResult<ProposalEvent>>,
+}
+
+impl Watcher {
+    async fn run(self) {
+        let mut tracker = ProposalTracker::new(self.multisig_key, Some(self.program_id));
+        let mut reconnect_delay = self.config.reconnect_delay;
+
+        loop {
+            let result = match tracker.multisig() {
+                None => self.seed(&mut tracker).await,
+                Some(_) => self.listen(&mut tracker, &mut reconnect_delay).await,
+            };
+            if self.events.is_closed() {
+                return;
+            }
+            if let Err(err) = result {
+                if self.events.send(Err(err)).is_err() {
+                    return;
+                }
+                tokio::time::sleep(reconnect_delay).await;
+                reconnect_delay = (reconnect_delay * 2).min(self.config.max_reconnect_delay);
+            }
+        }
+    }
+
+    /// Records the multisig and its `backfill` most recent proposals without emitting events.
+    async fn seed(&self, tracker: &mut ProposalTracker) -> ClientResult<()> {
+        let multisig =
+            get_multisig(&self.rpc_client, &self.multisig_key, Some(self.program_id)).await?;
+        let last = multisig.transaction_index;
+        let first = last
+            .saturating_sub(self.config.backfill)
+            .saturating_add(1)
+            .max(1);
+        let indexes: Vec<u64> = (first..=last).collect();
+        let proposals = self.get_proposals(&indexes).await?;
+
+        tracker.seed(
+            multisig,
+            indexes.into_iter().zip(proposals),
+            unix_timestamp(),
+        );
+        Ok(())
+    }
+
+    /// Subscribes, catches up by polling, then forwards updates until the connection drops.
+    ///
+    /// Returns `Ok` once the receiving stream is dropped.
+    async fn listen(
+        &self,
+        tracker: &mut ProposalTracker,
+        reconnect_delay: &mut Duration,
+    ) -> ClientResult<()> {
+        let pubsub = PubsubClient::new(self.config.ws_url.as_str()).await?;
+        let mut updates: SelectAll<BoxStream<'_, Update>> = SelectAll::new();
+        let mut subscriptions: HashMap<u64, UnsubscribeFn> = HashMap::new();
+
+        let (multisig_updates, _) = pubsub
+            .account_subscribe(&self.multisig_key, Some(self.account_config()))
+            .await?;
+        updates.push(
+            multisig_updates
+                .map(|response| Update::Multisig(response.value))
+                .boxed(),
+        );
+        if self.config.program_subscribe {
+            let (program_updates, _) = pubsub
+                .program_subscribe(&self.program_id, Some(self.program_config()))
+                .await?;
+            updates.push(
+                program_updates
+                    .filter_map(|response| async move {
+                        let address = response.value.pubkey.parse().ok()?;
+                        Some(Update::Program(address, response.value.account))
+                    })
+                    .boxed(),
+            );
+        }
+
+        // Whatever changed while disconnected is caught by this first poll.
+        let events = self.poll(tracker).await?;
+        if !self.emit(events) {
+            return Ok(());
+        }
+        *reconnect_delay = self.config.reconnect_delay;
+
+        let mut poll_interval = tokio::time::interval_at(
+            tokio::time::Instant::now() + self.config.poll_interval,
+            self.config.poll_interval,
+        );
+        loop {
+            if !self.config.program_subscribe {
+                let watched = tracker.watched_indexes();
+                let finalized: Vec<u64> = subscriptions
+                    .keys()
+                    .filter(|transaction_index| !watched.contains(transaction_index))
+                    .copied()
+                    .collect();
+                for transaction_index in finalized {
+                    if let Some(unsubscribe) = subscriptions.remove(&transaction_index) {
+                        // Ends the update stream, which `updates` then drops.
+                        tokio::spawn(unsubscribe());
+                    }
+                }
+
+                let mut subscribed = Vec::new();
+                for transaction_index in watched {
+                    if subscriptions.contains_key(&transaction_index) {
+                        continue;
+                    }
+                    let proposal_key = get_proposal_pda(
+                        &self.multisig_key,
+                        transaction_index,
+                        Some(&self.program_id),
+                    )
+                    .0;
+                    let (proposal_updates, unsubscribe) = pubsub
+                        .account_subscribe(&proposal_key, Some(self.account_config()))
+                        .await?;
+                    updates.push(
+                        proposal_updates
+                            .map(move |response| {
+                                Update::Proposal(transaction_index, response.value)
+                            })
+                            .boxed(),
+                    );
+                    subscriptions.insert(transaction_index, unsubscribe);
+                    subscribed.push(transaction_index);
+                }
+
+                // A proposal is usually created by the transaction that announces it on the
+                // multisig, before its subscription existed: fetch it once not to wait for
+                // the next poll.
+                if !subscribed.is_empty() {
+                    let events = self.poll_proposals(tracker, &subscribed).await;
+                    if !self.forward(events) {
+                        return Ok(());
+                    }
+                }
+            }
+
+            let events = tokio::select! {
+                update = updates.next() => match update {
+                    Some(update) => self.apply(tracker, update),
+                    None => return Err(ClientError::SubscriptionClosed),
+                },
+                _ = poll_interval.tick() => self.poll(tracker).await,
+                _ = self.events.closed() => return Ok(()),
+            };
+            if !self.forward(events) {
+                return Ok(());
+            }
+        }
+    }
+
+    /// Fetches the multisig and every watched proposal.
+    async fn poll(&self, tracker: &mut ProposalTracker) -> ClientResult<Vec<ProposalEvent>> {
+        let multisig =
+            get_multisig(&self.rpc_client, &self.multisig_key, Some(self.program_id)).await?;
+        let now = unix_timestamp();
+        let mut events = tracker.update_multisig(multisig, now);
+        let indexes = tracker.watched_indexes();
+        events.extend(self.poll_proposals(tracker, &indexes).await?);
+        Ok(events)
+    }
+
+    /// Fetches the proposals at `indexes`.
+    async fn poll_proposals(
+        &self,
+        tracker: &mut ProposalTracker,
+        indexes: &[u64],
+    ) -> ClientResult<Vec<ProposalEvent>> {
+        let proposals = self.get_proposals(indexes).await?;
+        let now = unix_timestamp();
+        Ok(indexes
+            .iter()
+            .zip(proposals)
+            .flat_map(|(transaction_index, proposal)| {
+                tracker.update_proposal(*transaction_index, proposal, now)
+            })
+            .collect())
+    }
+
+    fn apply(
+        &self,
+        tracker: &mut ProposalTracker,
+        update: Update,
+    ) -> ClientResult<Vec<ProposalEvent>> {
+        let now = unix_timestamp();
+        match update {
+            Update::Multisig(account) => {
+                match decode_update(&self.multisig_key, account, &self.program_id)? {
+                    Some(multisig) => Ok(tracker.update_multisig(multisig, now)),
+                    None => Err(ClientError::AccountClosed(self.multisig_key)),
+                }
+            }
+            Update::Proposal(transaction_index, account) => {
+                let proposal_key = get_proposal_pda(
+                    &self.multisig_key,
+                    transaction_index,
+                    Some(&self.program_id),
+                )
+                .0;
+                let proposal = decode_update(&proposal_key, account, &self.program_id)?;
+                Ok(tracker.update_proposal(transaction_index, proposal, now))
+            }
+            Update::Program(address, account) => {
+                // Transactions of the multisig match the same filter and are skipped.
+                match decode_update::<Proposal>(&address, account, &self.program_id) {
+                    Ok(Some(proposal)) => Ok(tracker.update_proposal(
+                        proposal.transaction_index,
+                        Some(proposal),
+                        now,
+                    )),
+                    Ok(None) => Ok(match tracker.proposal_index(&address) {
+                        Some(transaction_index) => {
+                            tracker.update_proposal(transaction_index, None, now)
+                        }
+                        None => Vec::new(),
+                    }),
+                    Err(_) => Ok(Vec::new()),
+                }
+            }
+        }
+    }
+
+    async fn get_proposals(&self, indexes: &[u64]) -> ClientResult<Vec<Option<Proposal>>> {
+        let keys: Vec<Pubkey> = indexes
+            .iter()
+            .map(|index| get_proposal_pda(&self.multisig_key, *index, Some(&self.program_id)).0)
+            .collect();
+        get_proposals(&self.rpc_client, &keys, Some(self.program_id)).await
+    }
+
+    /// Sends `events`, or the error that prevented computing them, returning false once the
+    /// receiving stream is dropped.
+    ///
+    /// A single undecodable update or failed poll does not drop the connection.
+    fn forward(&self, events: ClientResult<Vec<ProposalEvent>>) -> bool {
+        match events {
+            Ok(events) => self.emit(events),
+            Err(err) => self.events.send(Err(err)).is_ok(),
+        }
+    }
+
+    /// Sends `events`, returning false once the receiving stream is dropped.
+    fn emit(&self, events: Vec<ProposalEvent>) -> bool {
+        events
+            .into_iter()
+            .all(|event| self.events.send(Ok(event)).is_ok())
+    }
+
+    fn account_config(&self) -> RpcAccountInfoConfig {
+        RpcAccountInfoConfig {
+            encoding: Some(UiAccountEncoding::Base64),
+            commitment: Some(self.config.commitment),
+            ..RpcAccountInfoConfig::default()
+        }
+    }
+
+    /// Proposals and transactions of the multisig, both starting with the multisig address.
+    fn program_config(&self) -> RpcProgramAccountsConfig {
+        RpcProgramAccountsConfig {
+            filters: Some(vec![RpcFilterType::Memcmp(Memcmp::new_raw_bytes(
+                0,
+                self.multisig_key.to_bytes().to_vec(),
+            ))]),
+            account_config: self.account_config(),
+            ..RpcProgramAccountsConfig::default()
+        }
+    }
+}
+
+/// Decodes an account notification, `None` once the account was closed.
+fn decode_update<T: FortisAccount>(
+    address: &Pubkey,
+    account: UiAccount,
+    program_id: &Pubkey,
+) -> ClientResult<Option<T>> {
+    if account.lamports == 0 {
+        return Ok(None);
+    }
+    let account = account.decode().ok_or(ClientError::DeserializationError)?;
+    decode_account(address, &account, program_id).map(Some)
+}
+
+fn unix_timestamp() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_secs() as i64)
+}
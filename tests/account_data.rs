@@ -0,0 +1,178 @@
+//! Decoding raw account data, including short, truncated and oversized accounts.
+use fortis_sdk::client::{
+    MULTISIG_HEADER_SIZE, PROPOSAL_HEADER_SIZE, VAULT_TRANSACTION_HEADER_SIZE,
+};
+use fortis_sdk::error::ClientError;
+use fortis_sdk::state::{
+    AccountType, Multisig, Proposal, ProposalStatus, VaultTransaction, VaultTransactionMessage,
+};
+use solana_sdk::instruction::{AccountMeta, Instruction};
+use solana_sdk::pubkey::Pubkey;
+
+fn multisig() -> Multisig {
+    Multisig {
+        create_key: Pubkey::new_unique(),
+        rent_collector: Pubkey::new_unique(),
+        transaction_index: 3,
+        threshold: 2,
+        bump: 255,
+        members: vec![Pubkey::new_unique(), Pubkey::new_unique()],
+    }
+}
+
+fn proposal() -> Proposal {
+    Proposal {
+        multisig: Pubkey::new_unique(),
+        transaction_index: 3,
+        timestamp: 10,
+        deadline: 1_000,
+        status: ProposalStatus::Active,
+        bump: 254,
+        approved: vec![Pubkey::new_unique()],
+    }
+}
+
+fn vault_transaction() -> VaultTransaction {
+    let vault = Pubkey::new_unique();
+    let instruction = Instruction::new_with_bytes(
+        Pubkey::new_unique(),
+        &[1, 2, 3],
+        vec![
+            AccountMeta::new(vault, true),
+            AccountMeta::new(Pubkey::new_unique(), false),
+        ],
+    );
+    VaultTransaction {
+        multisig: Pubkey::new_unique(),
+        creator: Pubkey::new_unique(),
+        index: 3,
+        bump: 253,
+        vault_bump: 252,
+        ephemeral_signer_bumps: vec![],
+        message: VaultTransactionMessage::try_compile(&vault, &[instruction], &[]).unwrap(),
+    }
+}
+
+fn length_error(result: Result<impl std::fmt::Debug, ClientError>) -> (AccountType, usize, usize) {
+    match result {
+        Err(ClientError::InvalidAccountDataLength {
+            account,
+            expected,
+            actual,
+        }) => (account, expected, actual),
+        other => panic!("expected a length error, got {other:?}"),
+    }
+}
+
+#[test]
+fn multisig_decodes_exactly_sized_data() {
+    let multisig = multisig();
+    let data = borsh::to_vec(&multisig).unwrap();
+    assert_eq!(data.len(), MULTISIG_HEADER_SIZE + 4 + 2 * 32);
+    assert_eq!(Multisig::try_from_account_data(&data).unwrap(), multisig);
+
+    assert_eq!(
+        length_error(Multisig::try_from_account_data(
+            &data[..MULTISIG_HEADER_SIZE]
+        )),
+        (
+            AccountType::Multisig,
+            MULTISIG_HEADER_SIZE + 4,
+            MULTISIG_HEADER_SIZE
+        )
+    );
+    assert_eq!(
+        length_error(Multisig::try_from_account_data(&data[..data.len() - 1])),
+        (AccountType::Multisig, data.len(), data.len() - 1)
+    );
+    let mut trailing = data.clone();
+    trailing.push(0);
+    assert_eq!(
+        length_error(Multisig::try_from_account_data(&trailing)),
+        (AccountType::Multisig, data.len(), data.len() + 1)
+    );
+}
+
+#[test]
+fn proposal_ignores_unused_approval_space() {
+    let proposal = proposal();
+    let data = borsh::to_vec(&proposal).unwrap();
+    assert_eq!(data.len(), PROPOSAL_HEADER_SIZE + 4 + 32);
+
+    // Room for two more approvals, as allocated for a three member multisig.
+    let mut allocated = data.clone();
+    allocated.resize(data.len() + 2 * 32, 0);
+    assert_eq!(
+        Proposal::try_from_account_data(&allocated).unwrap(),
+        proposal
+    );
+
+    assert_eq!(
+        length_error(Proposal::try_from_account_data(&[])),
+        (AccountType::Proposal, PROPOSAL_HEADER_SIZE + 4, 0)
+    );
+    assert_eq!(
+        length_error(Proposal::try_from_account_data(&data[..data.len() - 1])),
+        (AccountType::Proposal, data.len(), data.len() - 1)
+    );
+}
+
+#[test]
+fn vault_transaction_rejects_truncated_and_trailing_data() {
+    let transaction = vault_transaction();
+    let data = borsh::to_vec(&transaction).unwrap();
+    assert_eq!(
+        VaultTransaction::try_from_account_data(&data).unwrap(),
+        transaction
+    );
+
+    assert_eq!(
+        length_error(VaultTransaction::try_from_account_data(&data[..10])),
+        (
+            AccountType::VaultTransaction,
+            VAULT_TRANSACTION_HEADER_SIZE,
+            10
+        )
+    );
+    let mut trailing = data.clone();
+    trailing.extend_from_slice(&[0; 3]);
+    assert_eq!(
+        length_error(VaultTransaction::try_from_account_data(&trailing)),
+        (AccountType::VaultTransaction, data.len(), data.len() + 3)
+    );
+    assert!(matches!(
+        VaultTransaction::try_from_account_data(&data[..data.len() - 1]),
+        Err(ClientError::AccountDecodeError {
+            account: AccountType::VaultTransaction,
+            ..
+        })
+    ));
+}
+
+#[test]
+fn decode_errors_report_where_decoding_stopped() {
+    let mut data = borsh::to_vec(&multisig()).unwrap();
+    // Claim one member fewer than stored: the length check catches it before decoding.
+    data[MULTISIG_HEADER_SIZE] = 1;
+    assert_eq!(
+        length_error(Multisig::try_from_account_data(&data)),
+        (
+            AccountType::Multisig,
+            MULTISIG_HEADER_SIZE + 4 + 32,
+            data.len()
+        )
+    );
+
+    let mut data = borsh::to_vec(&proposal()).unwrap();
+    let status_offset = 32 + 8 + 8 + 8;
+    data[status_offset] = 9;
+    // The status byte was read before it was found invalid.
+    assert!(matches!(
+        Proposal::try_from_account_data(&data),
+        Err(ClientError::AccountDecodeError {
+            account: AccountType::Proposal,
+            offset,
+            ..
+        }) if offset == status_offset + 1
+    ));
+}
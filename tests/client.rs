@@ -0,0 +1,618 @@
+//! Multisig lookups against an RPC stand-in serving program accounts.
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::ops::Bound::{self, Excluded, Included, Unbounded};
+use std::sync::{Arc, Mutex};
+
+use async_trait::async_trait;
+use base64::Engine;
+use fortis_sdk::client::{
+    find_multisigs_by_create_key, find_multisigs_for_member, get_multisig, get_multisigs,
+    get_proposal, get_proposals, list_proposals, list_proposals_stream, proposal_execute_auto,
+    proposal_execute_from_transaction, ProposalBundle, MAX_MULTIPLE_ACCOUNTS,
+};
+use fortis_sdk::error::ClientError;
+use fortis_sdk::pda::{
+    get_multisig_pda, get_proposal_pda, get_transaction_pda, get_vault_pda, FORTIS_PROGRAM_ID,
+};
+use fortis_sdk::solana_client::client_error::Result as RpcResult;
+use fortis_sdk::solana_client::nonblocking::rpc_client::RpcClient;
+use fortis_sdk::solana_client::rpc_client::RpcClientConfig;
+use fortis_sdk::solana_client::rpc_request::RpcRequest;
+use fortis_sdk::solana_client::rpc_sender::{RpcSender, RpcTransportStats};
+use fortis_sdk::state::{
+    AccountType, Multisig, Proposal, ProposalExecuteAccounts, ProposalStatus, VaultTransaction,
+    VaultTransactionMessage,
+};
+use futures::TryStreamExt;
+use serde_json::{json, Value};
+use solana_address_lookup_table_interface::state::{AddressLookupTable, LookupTableMeta};
+use solana_message::AddressLookupTableAccount;
+use solana_sdk::instruction::{AccountMeta, Instruction};
+use solana_sdk::pubkey::Pubkey;
+
+/// Program accounts served over RPC, recording the offsets of the `memcmp` filters
+/// and the number of keys of the `getMultipleAccounts` requests it is asked for.
+#[derive(Clone, Default)]
+struct Program {
+    accounts: Vec<(Pubkey, Vec<u8>)>,
+    /// Owners of the accounts not owned by the Fortis program.
+    owners: HashMap<Pubkey, Pubkey>,
+    memcmp_offsets: Arc<Mutex<Vec<usize>>>,
+    batch_sizes: Arc<Mutex<Vec<usize>>>,
+}
+
+impl Program {
+    fn add(&mut self, address: Pubkey, account: &impl borsh::BorshSerialize) {
+        self.accounts
+            .push((address, borsh::to_vec(account).unwrap()));
+    }
+
+    fn account(&self, address: &Pubkey) -> Value {
+        match self.accounts.iter().find(|(key, _)| key == address) {
+            Some((_, data)) => ui_account(data, data.len(), self.owner(address)),
+            None => Value::Null,
+        }
+    }
+
+    fn owner(&self, address: &Pubkey) -> Pubkey {
+        self.owners
+            .get(address)
+            .copied()
+            .unwrap_or(FORTIS_PROGRAM_ID)
+    }
+
+    fn program_accounts(&self, config: &Value) -> Value {
+        let memcmp = &config["filters"][0]["memcmp"];
+        assert_eq!(memcmp["encoding"], "base58");
+        let offset = memcmp["offset"].as_u64().unwrap() as usize;
+        let bytes = bs58::decode(memcmp["bytes"].as_str().unwrap())
+            .into_vec()
+            .unwrap();
+        self.memcmp_offsets.lock().unwrap().push(offset);
+
+        self.accounts
+            .iter()
+            .filter(|(_, data)| data.get(offset..offset + bytes.len()) == Some(&bytes[..]))
+            .map(|(address, data)| {
+                let account = ui_account(data, data.len(), self.owner(address));
+                json!({ "pubkey": address.to_string(), "account": account })
+            })
+            .collect()
+    }
+}
+
+fn ui_account(data: &[u8], space: usize, owner: Pubkey) -> Value {
+    json!({
+        "lamports": 1_000_000,
+        "data": [base64::engine::general_purpose::STANDARD.encode(data), "base64"],
+        "owner": owner.to_string(),
+        "executable": false,
+        "rentEpoch": 0,
+        "space": space,
+    })
+}
+
+#[async_trait]
+impl RpcSender for Program {
+    async fn send(&self, request: RpcRequest, params: Value) -> RpcResult<Value> {
+        match request {
+            RpcRequest::GetProgramAccounts => Ok(self.program_accounts(&params[1])),
+            RpcRequest::GetAccountInfo => {
+                let value = self.account(&params[0].as_str().unwrap().parse().unwrap());
+                Ok(json!({ "context": { "slot": 1 }, "value": value }))
+            }
+            RpcRequest::GetMultipleAccounts => {
+                let keys = params[0].as_array().unwrap();
+                self.batch_sizes.lock().unwrap().push(keys.len());
+                let value: Value = keys
+                    .iter()
+                    .map(|key| self.account(&key.as_str().unwrap().parse().unwrap()))
+                    .collect();
+                Ok(json!({ "context": { "slot": 1 }, "value": value }))
+            }
+            other => panic!("unexpected request {other}"),
+        }
+    }
+
+    fn get_transport_stats(&self) -> RpcTransportStats {
+        RpcTransportStats::default()
+    }
+
+    fn url(&self) -> String {
+        "stand-in".to_string()
+    }
+}
+
+fn multisig(members: Vec<Pubkey>) -> (Pubkey, Multisig) {
+    let create_key = Pubkey::new_unique();
+    let (address, bump) = get_multisig_pda(&create_key, None);
+    let multisig = Multisig {
+        create_key,
+        rent_collector: Pubkey::new_unique(),
+        transaction_index: 1,
+        threshold: 1,
+        bump,
+        members,
+    };
+    (address, multisig)
+}
+
+fn proposal(multisig: &Pubkey, approved: Vec<Pubkey>) -> (Pubkey, Proposal) {
+    let (address, bump) = get_proposal_pda(multisig, 1, None);
+    let proposal = Proposal {
+        multisig: *multisig,
+        transaction_index: 1,
+        timestamp: 0,
+        deadline: i64::MAX,
+        status: ProposalStatus::Active,
+        bump,
+        approved,
+    };
+    (address, proposal)
+}
+
+/// Adds the proposal and transaction accounts at `index` that are still open.
+fn add_index(
+    program: &mut Program,
+    multisig_key: &Pubkey,
+    index: u64,
+    (with_proposal, with_transaction): (bool, bool),
+) {
+    if with_proposal {
+        let (address, bump) = get_proposal_pda(multisig_key, index, None);
+        let proposal = Proposal {
+            multisig: *multisig_key,
+            transaction_index: index,
+            timestamp: 0,
+            deadline: i64::MAX,
+            status: ProposalStatus::Active,
+            bump,
+            approved: vec![],
+        };
+        program.add(address, &proposal);
+    }
+    if with_transaction {
+        let vault = get_vault_pda(multisig_key, None).0;
+        let message = VaultTransactionMessage::try_compile(&vault, &[], &[]).unwrap();
+        add_transaction(program, multisig_key, index, message);
+    }
+}
+
+fn add_transaction(
+    program: &mut Program,
+    multisig_key: &Pubkey,
+    index: u64,
+    message: VaultTransactionMessage,
+) -> VaultTransaction {
+    let (address, bump) = get_transaction_pda(multisig_key, index, None);
+    let transaction = VaultTransaction {
+        multisig: *multisig_key,
+        creator: Pubkey::new_unique(),
+        index,
+        bump,
+        vault_bump: get_vault_pda(multisig_key, None).1,
+        ephemeral_signer_bumps: vec![],
+        message,
+    };
+    program.add(address, &transaction);
+    transaction
+}
+
+fn add_lookup_table(
+    program: &mut Program,
+    table: &AddressLookupTableAccount,
+    meta: LookupTableMeta,
+) {
+    let data = AddressLookupTable {
+        meta,
+        addresses: Cow::Borrowed(&table.addresses),
+    }
+    .serialize_for_tests()
+    .unwrap();
+    program.accounts.push((table.key, data));
+    program.owners.insert(
+        table.key,
+        solana_address_lookup_table_interface::program::ID,
+    );
+}
+
+fn indexes(bundles: &[ProposalBundle]) -> Vec<u64> {
+    bundles.iter().map(|bundle| bundle.index).collect()
+}
+
+async fn list_indexes(
+    rpc_client: &RpcClient,
+    multisig_key: &Pubkey,
+    range: (Bound<u64>, Bound<u64>),
+) -> Vec<u64> {
+    indexes(
+        &list_proposals(rpc_client, multisig_key, range, None)
+            .await
+            .unwrap(),
+    )
+}
+
+#[tokio::test]
+async fn fetch_rejects_accounts_of_another_owner() {
+    let (address, multisig) = multisig(vec![Pubkey::new_unique()]);
+    let owner = Pubkey::new_unique();
+    let mut program = Program::default();
+    program.add(address, &multisig);
+    program.owners.insert(address, owner);
+    let rpc_client = RpcClient::new_sender(program, RpcClientConfig::default());
+
+    let result = get_multisig(&rpc_client, &address, None).await;
+    assert!(matches!(
+        result,
+        Err(ClientError::WrongAccountOwner { address: key, expected, actual })
+            if key == address && expected == FORTIS_PROGRAM_ID && actual == owner
+    ));
+}
+
+#[tokio::test]
+async fn fetch_rejects_accounts_of_another_type() {
+    let (multisig_key, multisig) = multisig(vec![Pubkey::new_unique(), Pubkey::new_unique()]);
+    let (proposal_key, proposal) = proposal(&multisig_key, vec![multisig.members[0]]);
+    let mut program = Program::default();
+    program.add(multisig_key, &multisig);
+    program.add(proposal_key, &proposal);
+    let rpc_client = RpcClient::new_sender(program, RpcClientConfig::default());
+
+    assert!(matches!(
+        get_multisig(&rpc_client, &proposal_key, None).await,
+        Err(ClientError::WrongAccountType { address, expected: AccountType::Multisig })
+            if address == proposal_key
+    ));
+    assert!(matches!(
+        get_proposal(&rpc_client, &multisig_key, None).await,
+        Err(ClientError::WrongAccountType { address, expected: AccountType::Proposal })
+            if address == multisig_key
+    ));
+    assert_eq!(
+        get_proposal(&rpc_client, &proposal_key, None)
+            .await
+            .unwrap(),
+        proposal
+    );
+}
+
+#[tokio::test]
+async fn member_scan_matches_each_member_slot() {
+    let member = Pubkey::new_unique();
+    let others = |count: usize| (0..count).map(|_| Pubkey::new_unique());
+    let (second, second_multisig) = multisig(others(1).chain([member]).collect());
+    let (without_member, without_member_multisig) = multisig(others(3).collect());
+    let (fourth, fourth_multisig) = multisig(others(3).chain([member]).collect());
+    let (proposal_key, proposal) = proposal(&second, vec![member, member]);
+    let corrupt = Pubkey::new_unique();
+
+    let mut program = Program::default();
+    program.add(second, &second_multisig);
+    program.add(without_member, &without_member_multisig);
+    program.add(fourth, &fourth_multisig);
+    program.add(proposal_key, &proposal);
+    // Holds the member at the first slot, but with a bogus member count.
+    let mut corrupt_data = vec![0xff; 111];
+    corrupt_data[79..].copy_from_slice(member.as_ref());
+    program.accounts.push((corrupt, corrupt_data));
+
+    let rpc_client = RpcClient::new_sender(program.clone(), RpcClientConfig::default());
+    let search = find_multisigs_for_member(&rpc_client, &member, 3, None)
+        .await
+        .unwrap();
+
+    assert_eq!(*program.memcmp_offsets.lock().unwrap(), vec![79, 111, 143]);
+    let found: Vec<Pubkey> = search.multisigs.iter().map(|info| info.address).collect();
+    assert_eq!(found, vec![second]);
+    assert_eq!(search.multisigs[0].multisig, second_multisig);
+    let skipped: Vec<Pubkey> = search.skipped.iter().map(|(key, _)| *key).collect();
+    assert_eq!(skipped, vec![corrupt]);
+
+    let search = find_multisigs_for_member(&rpc_client, &member, 4, None)
+        .await
+        .unwrap();
+    let found: Vec<Pubkey> = search.multisigs.iter().map(|info| info.address).collect();
+    assert_eq!(found, vec![second, fourth]);
+}
+
+#[tokio::test]
+async fn member_scan_rejects_undecodable_accounts() {
+    let member = Pubkey::new_unique();
+    let (address, multisig) = multisig(vec![member]);
+    let mut program = Program::default();
+    program.add(address, &multisig);
+
+    /// Returns the accounts parsed to JSON, as for a `jsonParsed` request.
+    #[derive(Clone)]
+    struct Parsed(Program);
+
+    #[async_trait]
+    impl RpcSender for Parsed {
+        async fn send(&self, request: RpcRequest, params: Value) -> RpcResult<Value> {
+            let mut value = self.0.send(request, params).await?;
+            for keyed in value.as_array_mut().unwrap() {
+                keyed["account"]["data"] =
+                    json!({ "program": "fortis", "parsed": {}, "space": 111 });
+            }
+            Ok(value)
+        }
+
+        fn get_transport_stats(&self) -> RpcTransportStats {
+            RpcTransportStats::default()
+        }
+
+        fn url(&self) -> String {
+            "stand-in".to_string()
+        }
+    }
+
+    let rpc_client = RpcClient::new_sender(Parsed(program), RpcClientConfig::default());
+    let result = find_multisigs_for_member(&rpc_client, &member, 1, None).await;
+    assert!(matches!(
+        result,
+        Err(ClientError::IncompleteRpcAccount(key)) if key == address
+    ));
+}
+
+#[tokio::test]
+async fn create_key_lookup_returns_the_multisig_or_none() {
+    let (address, multisig) = multisig(vec![Pubkey::new_unique()]);
+    let mut program = Program::default();
+    program.add(address, &multisig);
+    let rpc_client = RpcClient::new_sender(program, RpcClientConfig::default());
+
+    let found = find_multisigs_by_create_key(&rpc_client, &multisig.create_key, None)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!((found.address, found.multisig), (address, multisig));
+
+    assert!(
+        find_multisigs_by_create_key(&rpc_client, &Pubkey::new_unique(), None)
+            .await
+            .unwrap()
+            .is_none()
+    );
+}
+
+#[tokio::test]
+async fn batched_fetch_is_chunked_and_aligned() {
+    let mut program = Program::default();
+    let mut keys: Vec<Pubkey> = (0..150).map(|_| Pubkey::new_unique()).collect();
+    let mut expected = vec![None; keys.len()];
+    for slot in [0, 99, 100, 149] {
+        let (address, multisig) = multisig(vec![Pubkey::new_unique()]);
+        program.add(address, &multisig);
+        keys[slot] = address;
+        expected[slot] = Some(multisig);
+    }
+    let rpc_client = RpcClient::new_sender(program.clone(), RpcClientConfig::default());
+
+    let multisigs = get_multisigs(&rpc_client, &keys, None).await.unwrap();
+
+    assert_eq!(multisigs, expected);
+    assert_eq!(
+        *program.batch_sizes.lock().unwrap(),
+        vec![MAX_MULTIPLE_ACCOUNTS, 50]
+    );
+    assert!(get_multisigs(&rpc_client, &[], None)
+        .await
+        .unwrap()
+        .is_empty());
+}
+
+#[tokio::test]
+async fn batched_fetch_checks_each_account() {
+    let (multisig_key, multisig) = multisig(vec![Pubkey::new_unique()]);
+    let (proposal_key, proposal) = proposal(&multisig_key, vec![]);
+    let mut program = Program::default();
+    program.add(multisig_key, &multisig);
+    program.add(proposal_key, &proposal);
+    let rpc_client = RpcClient::new_sender(program, RpcClientConfig::default());
+
+    assert_eq!(
+        get_proposals(&rpc_client, &[Pubkey::new_unique(), proposal_key], None)
+            .await
+            .unwrap(),
+        vec![None, Some(proposal)]
+    );
+    assert!(matches!(
+        get_proposals(&rpc_client, &[proposal_key, multisig_key], None).await,
+        Err(ClientError::WrongAccountType { address, expected: AccountType::Proposal })
+            if address == multisig_key
+    ));
+}
+
+/// A multisig with five transactions: the second closed, the fourth without its proposal.
+fn five_transactions() -> (Pubkey, Program) {
+    let (multisig_key, mut multisig) = multisig(vec![Pubkey::new_unique()]);
+    multisig.transaction_index = 5;
+    let mut program = Program::default();
+    program.add(multisig_key, &multisig);
+    for index in 1..=5 {
+        let open = match index {
+            2 => (false, false),
+            4 => (false, true),
+            _ => (true, true),
+        };
+        add_index(&mut program, &multisig_key, index, open);
+    }
+    (multisig_key, program)
+}
+
+#[tokio::test]
+async fn lists_proposals_within_the_range() {
+    let (multisig_key, program) = five_transactions();
+    let rpc_client = RpcClient::new_sender(program, RpcClientConfig::default());
+
+    let bundles = list_proposals(&rpc_client, &multisig_key, .., None)
+        .await
+        .unwrap();
+    assert_eq!(indexes(&bundles), vec![1, 2, 3, 4, 5]);
+    for bundle in &bundles {
+        assert_eq!(
+            bundle.proposal_key,
+            get_proposal_pda(&multisig_key, bundle.index, None).0
+        );
+        assert_eq!(
+            bundle.transaction_key,
+            get_transaction_pda(&multisig_key, bundle.index, None).0
+        );
+    }
+    let closed: Vec<bool> = bundles.iter().map(ProposalBundle::is_closed).collect();
+    assert_eq!(closed, vec![false, true, false, false, false]);
+    assert!(bundles[3].proposal.is_none());
+    assert_eq!(bundles[3].transaction.as_ref().unwrap().index, 4);
+    assert_eq!(bundles[4].proposal.as_ref().unwrap().transaction_index, 5);
+
+    let list = |range| list_indexes(&rpc_client, &multisig_key, range);
+    assert_eq!(list((Included(2), Excluded(4))).await, vec![2, 3]);
+    assert_eq!(
+        list((Included(0), Included(100))).await,
+        vec![1, 2, 3, 4, 5]
+    );
+    assert_eq!(list((Excluded(4), Unbounded)).await, vec![5]);
+    assert!(list((Included(6), Unbounded)).await.is_empty());
+}
+
+#[tokio::test]
+async fn streams_pages_newest_first() {
+    let (multisig_key, program) = five_transactions();
+    let rpc_client = RpcClient::new_sender(program, RpcClientConfig::default());
+
+    let pages: Vec<Vec<ProposalBundle>> =
+        list_proposals_stream(&rpc_client, &multisig_key, 2, None)
+            .try_collect()
+            .await
+            .unwrap();
+    let pages: Vec<Vec<u64>> = pages.iter().map(|page| indexes(page)).collect();
+    assert_eq!(pages, vec![vec![5, 4], vec![3, 2], vec![1]]);
+
+    let (empty_key, empty) = multisig(vec![Pubkey::new_unique()]);
+    let mut program = Program::default();
+    program.add(
+        empty_key,
+        &Multisig {
+            transaction_index: 0,
+            ..empty
+        },
+    );
+    let rpc_client = RpcClient::new_sender(program, RpcClientConfig::default());
+    let pages: Vec<Vec<ProposalBundle>> = list_proposals_stream(&rpc_client, &empty_key, 2, None)
+        .try_collect()
+        .await
+        .unwrap();
+    assert!(pages.is_empty());
+}
+
+/// A multisig whose first transaction writes to and reads from accounts of `table`.
+fn transaction_with_lookups(
+    table: &AddressLookupTableAccount,
+) -> (Pubkey, Program, VaultTransaction) {
+    let (multisig_key, mut multisig) = multisig(vec![Pubkey::new_unique()]);
+    multisig.transaction_index = 1;
+    let vault = get_vault_pda(&multisig_key, None).0;
+    let instruction = Instruction::new_with_bytes(
+        Pubkey::new_unique(),
+        &[1],
+        vec![
+            AccountMeta::new(vault, true),
+            AccountMeta::new(table.addresses[2], false),
+            AccountMeta::new_readonly(table.addresses[0], false),
+        ],
+    );
+    let message =
+        VaultTransactionMessage::try_compile(&vault, &[instruction], std::slice::from_ref(table))
+            .unwrap();
+    assert_eq!(message.address_table_lookups.len(), 1);
+
+    let mut program = Program::default();
+    program.add(multisig_key, &multisig);
+    let transaction = add_transaction(&mut program, &multisig_key, 1, message);
+    (multisig_key, program, transaction)
+}
+
+fn lookup_table(len: usize) -> AddressLookupTableAccount {
+    AddressLookupTableAccount {
+        key: Pubkey::new_unique(),
+        addresses: (0..len).map(|_| Pubkey::new_unique()).collect(),
+    }
+}
+
+#[tokio::test]
+async fn execute_fetches_the_lookup_tables_of_the_message() {
+    let table = lookup_table(3);
+    let (multisig_key, mut program, transaction) = transaction_with_lookups(&table);
+    add_lookup_table(&mut program, &table, LookupTableMeta::default());
+    let rpc_client = RpcClient::new_sender(program, RpcClientConfig::default());
+    let member = Pubkey::new_unique();
+
+    let (instruction, tables) = proposal_execute_auto(&rpc_client, &multisig_key, 1, &member, None)
+        .await
+        .unwrap();
+
+    assert_eq!(tables, vec![table.clone()]);
+    let accounts = ProposalExecuteAccounts {
+        multisig: multisig_key,
+        proposal: get_proposal_pda(&multisig_key, 1, None).0,
+        transaction: get_transaction_pda(&multisig_key, 1, None).0,
+        member,
+    };
+    assert_eq!(
+        instruction,
+        proposal_execute_from_transaction(&transaction, accounts, &[table], None).unwrap()
+    );
+}
+
+#[tokio::test]
+async fn execute_rejects_unusable_lookup_tables() {
+    let table = lookup_table(3);
+    let execute = |program: Program| {
+        let multisig_key = program.accounts[0].0;
+        async move {
+            let rpc_client = RpcClient::new_sender(program, RpcClientConfig::default());
+            proposal_execute_auto(&rpc_client, &multisig_key, 1, &Pubkey::new_unique(), None)
+                .await
+                .map(|_| ())
+        }
+    };
+
+    let (_, missing, _) = transaction_with_lookups(&table);
+    assert!(matches!(
+        execute(missing).await,
+        Err(ClientError::LookupTableNotFound(key)) if key == table.key
+    ));
+
+    let (_, mut deactivated, _) = transaction_with_lookups(&table);
+    let meta = LookupTableMeta {
+        deactivation_slot: 10,
+        ..LookupTableMeta::default()
+    };
+    add_lookup_table(&mut deactivated, &table, meta);
+    assert!(matches!(
+        execute(deactivated).await,
+        Err(ClientError::LookupTableDeactivated(key)) if key == table.key
+    ));
+
+    // The message uses index 2, past the end of a table holding two addresses.
+    let (_, mut truncated, _) = transaction_with_lookups(&table);
+    let shorter = AddressLookupTableAccount {
+        key: table.key,
+        addresses: table.addresses[..2].to_vec(),
+    };
+    add_lookup_table(&mut truncated, &shorter, LookupTableMeta::default());
+    assert!(matches!(
+        execute(truncated).await,
+        Err(ClientError::LookupTableIndexOutOfRange { table: key, index: 2, len: 2 })
+            if key == table.key
+    ));
+
+    let (_, mut foreign, _) = transaction_with_lookups(&table);
+    add_lookup_table(&mut foreign, &table, LookupTableMeta::default());
+    foreign.owners.remove(&table.key);
+    assert!(matches!(
+        execute(foreign).await,
+        Err(ClientError::WrongAccountOwner { address, .. }) if address == table.key
+    ));
+}
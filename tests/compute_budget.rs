@@ -0,0 +1,187 @@
+//! Compute budget resolution, against an RPC stand-in for the fees and the simulation.
+use std::sync::{Arc, Mutex};
+
+use async_trait::async_trait;
+use base64::Engine;
+use fortis_sdk::compute_budget::{
+    fee_percentile, limit_with_margin, resolve_compute_budget, ComputeBudgetConfig,
+    ComputeUnitLimit, ComputeUnitPrice, MAX_COMPUTE_UNIT_LIMIT,
+};
+use fortis_sdk::nonce::DurableNonce;
+use fortis_sdk::solana_client::client_error::Result as RpcResult;
+use fortis_sdk::solana_client::nonblocking::rpc_client::RpcClient;
+use fortis_sdk::solana_client::rpc_client::RpcClientConfig;
+use fortis_sdk::solana_client::rpc_request::RpcRequest;
+use fortis_sdk::solana_client::rpc_sender::{RpcSender, RpcTransportStats};
+use serde_json::{json, Value};
+use solana_compute_budget_interface::ComputeBudgetInstruction;
+use solana_sdk::instruction::{AccountMeta, Instruction};
+use solana_sdk::pubkey::Pubkey;
+use solana_transaction::versioned::VersionedTransaction;
+
+const UNITS_CONSUMED: u64 = 200_000;
+
+/// Serves recent prioritization fees and simulations, recording what it is asked for.
+#[derive(Clone, Default)]
+struct Cluster {
+    fees: Vec<u64>,
+    fee_accounts: Arc<Mutex<Vec<Pubkey>>>,
+    simulated: Arc<Mutex<Option<VersionedTransaction>>>,
+}
+
+#[async_trait]
+impl RpcSender for Cluster {
+    async fn send(&self, request: RpcRequest, params: Value) -> RpcResult<Value> {
+        match request {
+            RpcRequest::GetRecentPrioritizationFees => {
+                *self.fee_accounts.lock().unwrap() = params[0]
+                    .as_array()
+                    .unwrap()
+                    .iter()
+                    .map(|key| key.as_str().unwrap().parse().unwrap())
+                    .collect();
+                Ok(self
+                    .fees
+                    .iter()
+                    .enumerate()
+                    .map(|(slot, fee)| json!({ "slot": slot, "prioritizationFee": fee }))
+                    .collect())
+            }
+            RpcRequest::SimulateTransaction => {
+                let wire = base64::engine::general_purpose::STANDARD
+                    .decode(params[0].as_str().unwrap())
+                    .unwrap();
+                *self.simulated.lock().unwrap() = Some(bincode::deserialize(&wire).unwrap());
+                Ok(json!({
+                    "context": { "slot": 1 },
+                    "value": { "err": null, "logs": [], "unitsConsumed": UNITS_CONSUMED },
+                }))
+            }
+            other => panic!("unexpected request {other}"),
+        }
+    }
+
+    fn get_transport_stats(&self) -> RpcTransportStats {
+        RpcTransportStats::default()
+    }
+
+    fn url(&self) -> String {
+        "stand-in".to_string()
+    }
+}
+
+#[test]
+fn fee_percentile_takes_the_nearest_rank() {
+    let fees = [50, 10, 40, 20, 30];
+
+    assert_eq!(fee_percentile(&fees, 20), 10);
+    assert_eq!(fee_percentile(&fees, 21), 20);
+    assert_eq!(fee_percentile(&fees, 50), 30);
+    assert_eq!(fee_percentile(&fees, 80), 40);
+    assert_eq!(fee_percentile(&fees, 81), 50);
+}
+
+#[test]
+fn fee_percentile_bounds() {
+    let fees = [50, 10, 40, 20, 30];
+
+    assert_eq!(fee_percentile(&[], 50), 0);
+    assert_eq!(fee_percentile(&fees, 0), 10);
+    assert_eq!(fee_percentile(&fees, 100), 50);
+    assert_eq!(fee_percentile(&fees, u8::MAX), 50);
+    assert_eq!(fee_percentile(&[7], 0), 7);
+}
+
+#[test]
+fn fee_percentile_with_ties() {
+    let fees = [5, 9, 5, 5];
+
+    assert_eq!(fee_percentile(&fees, 75), 5);
+    assert_eq!(fee_percentile(&fees, 76), 9);
+}
+
+#[test]
+fn limit_with_margin_rounds_down() {
+    assert_eq!(limit_with_margin(100_000, 10), 110_000);
+    assert_eq!(limit_with_margin(999, 10), 1_098);
+    assert_eq!(limit_with_margin(100_000, 0), 100_000);
+    assert_eq!(limit_with_margin(0, 50), 0);
+}
+
+#[test]
+fn limit_with_margin_is_capped() {
+    assert_eq!(limit_with_margin(1_300_000, 20), MAX_COMPUTE_UNIT_LIMIT);
+    assert_eq!(
+        limit_with_margin(u64::from(MAX_COMPUTE_UNIT_LIMIT), 0),
+        MAX_COMPUTE_UNIT_LIMIT
+    );
+    // The multiplication saturates instead of wrapping around.
+    assert_eq!(limit_with_margin(u64::MAX, 10), MAX_COMPUTE_UNIT_LIMIT);
+    assert_eq!(limit_with_margin(1_000, u32::MAX), MAX_COMPUTE_UNIT_LIMIT);
+}
+
+#[tokio::test]
+async fn simulates_the_durable_nonce_advance() {
+    let payer = Pubkey::new_unique();
+    let durable_nonce = DurableNonce {
+        account: Pubkey::new_unique(),
+        authority: payer,
+    };
+    let written = Pubkey::new_unique();
+    let instruction = Instruction::new_with_bytes(
+        Pubkey::new_unique(),
+        &[1, 2, 3],
+        vec![AccountMeta::new(written, false)],
+    );
+    let cluster = Cluster {
+        fees: vec![300, 100, 200],
+        ..Cluster::default()
+    };
+    let rpc_client = RpcClient::new_sender(cluster.clone(), RpcClientConfig::default());
+    let config = ComputeBudgetConfig {
+        unit_limit: Some(ComputeUnitLimit::Simulated { margin_percent: 10 }),
+        unit_price: Some(ComputeUnitPrice::Percentile(50)),
+    };
+
+    let budget = resolve_compute_budget(
+        &rpc_client,
+        &config,
+        &payer,
+        std::slice::from_ref(&instruction),
+        &[],
+        Some(&durable_nonce),
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(
+        budget,
+        vec![
+            ComputeBudgetInstruction::set_compute_unit_limit(limit_with_margin(UNITS_CONSUMED, 10)),
+            ComputeBudgetInstruction::set_compute_unit_price(200),
+        ]
+    );
+    let fee_accounts = cluster.fee_accounts.lock().unwrap().clone();
+    assert!(fee_accounts.contains(&durable_nonce.account));
+    assert!(fee_accounts.contains(&written));
+
+    // Laid out like the transaction sent: the advance first, then the budget.
+    let transaction = cluster.simulated.lock().unwrap().take().unwrap();
+    let keys = transaction.message.static_account_keys();
+    let simulated: Vec<(Pubkey, Vec<u8>)> = transaction
+        .message
+        .instructions()
+        .iter()
+        .map(|ix| (keys[usize::from(ix.program_id_index)], ix.data.clone()))
+        .collect();
+    let expected: Vec<(Pubkey, Vec<u8>)> = [
+        durable_nonce.advance_instruction(),
+        ComputeBudgetInstruction::set_compute_unit_limit(MAX_COMPUTE_UNIT_LIMIT),
+        ComputeBudgetInstruction::set_compute_unit_price(200),
+        instruction,
+    ]
+    .into_iter()
+    .map(|ix| (ix.program_id, ix.data))
+    .collect();
+    assert_eq!(simulated, expected);
+}
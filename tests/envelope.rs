@@ -0,0 +1,124 @@
+#![cfg(feature = "serde")]
+
+use fortis_sdk::envelope::{VaultTransactionEnvelope, ENVELOPE_VERSION};
+use fortis_sdk::error::ClientError;
+use fortis_sdk::pda::{get_vault_pda, FORTIS_PROGRAM_ID};
+use fortis_sdk::state::VaultTransactionMessage;
+use serde_json::Value;
+use solana_message::AddressLookupTableAccount;
+use solana_sdk::instruction::{AccountMeta, Instruction};
+use solana_sdk::pubkey::Pubkey;
+
+struct Fixture {
+    multisig: Pubkey,
+    tables: Vec<AddressLookupTableAccount>,
+    envelope: VaultTransactionEnvelope,
+}
+
+impl Fixture {
+    /// An envelope whose message loads one account from each of two lookup tables.
+    fn new() -> Self {
+        let multisig = Pubkey::new_unique();
+        let vault = get_vault_pda(&multisig, None).0;
+        let tables: Vec<AddressLookupTableAccount> = (0..2)
+            .map(|_| AddressLookupTableAccount {
+                key: Pubkey::new_unique(),
+                addresses: vec![Pubkey::new_unique(), Pubkey::new_unique()],
+            })
+            .collect();
+        let instruction = Instruction::new_with_bytes(
+            Pubkey::new_unique(),
+            &[1, 2, 3],
+            vec![
+                AccountMeta::new(vault, true),
+                AccountMeta::new(tables[0].addresses[1], false),
+                AccountMeta::new_readonly(tables[1].addresses[0], false),
+            ],
+        );
+        let message =
+            VaultTransactionMessage::try_compile(&vault, &[instruction], &tables).unwrap();
+        assert_eq!(message.address_table_lookups.len(), 2);
+
+        let envelope = VaultTransactionEnvelope::new(multisig, 7, 0, message, &tables, None);
+        Self {
+            multisig,
+            tables,
+            envelope,
+        }
+    }
+
+    fn json(&self) -> Value {
+        serde_json::from_str(&self.envelope.to_json().unwrap()).unwrap()
+    }
+}
+
+fn from_value(value: &Value) -> Result<VaultTransactionEnvelope, ClientError> {
+    VaultTransactionEnvelope::from_json(&value.to_string())
+}
+
+#[test]
+fn round_trips_through_json() {
+    let fixture = Fixture::new();
+    let decoded =
+        VaultTransactionEnvelope::from_json(&fixture.envelope.to_json().unwrap()).unwrap();
+
+    assert_eq!(decoded, fixture.envelope);
+    assert_eq!(decoded.address_lookup_table_accounts(), fixture.tables);
+    assert_eq!(decoded.vault(), get_vault_pda(&fixture.multisig, None).0);
+}
+
+#[test]
+fn encodes_pubkeys_as_base58_and_data_as_base64() {
+    let fixture = Fixture::new();
+    let json = fixture.json();
+
+    assert_eq!(json["version"], ENVELOPE_VERSION);
+    assert_eq!(json["program_id"], FORTIS_PROGRAM_ID.to_string());
+    assert_eq!(json["multisig"], fixture.multisig.to_string());
+    assert_eq!(
+        json["message"]["address_table_lookups"][0]["account_key"],
+        fixture.tables[0].key.to_string()
+    );
+    assert_eq!(
+        json["address_lookup_tables"][1]["addresses"][0],
+        fixture.tables[1].addresses[0].to_string()
+    );
+    assert_eq!(json["message"]["instructions"][0]["data"], "AQID");
+    assert!(json["message"]["instructions"][0]["accounts"].is_array());
+}
+
+#[test]
+fn rejects_unknown_versions() {
+    let mut json = Fixture::new().json();
+    json["version"] = Value::from(2);
+
+    assert!(matches!(
+        from_value(&json),
+        Err(ClientError::UnsupportedEnvelopeVersion(2))
+    ));
+}
+
+#[test]
+fn rejects_lookup_tables_not_matching_the_message() {
+    let fixture = Fixture::new();
+
+    let mut missing = fixture.json();
+    missing["address_lookup_tables"]
+        .as_array_mut()
+        .unwrap()
+        .pop();
+    let mut swapped = fixture.json();
+    swapped["address_lookup_tables"]
+        .as_array_mut()
+        .unwrap()
+        .swap(0, 1);
+    let mut other_key = fixture.json();
+    other_key["address_lookup_tables"][0]["key"] = Value::from(Pubkey::new_unique().to_string());
+
+    for json in [missing, swapped, other_key] {
+        assert!(matches!(
+            from_value(&json),
+            Err(ClientError::InvalidEnvelope(_))
+        ));
+    }
+}
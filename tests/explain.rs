@@ -0,0 +1,225 @@
+//! One decode per built-in decoder, pinning the field names `policy` and `lint` read.
+use fortis_sdk::explain::{
+    AssociatedTokenDecoder, DecodedInstruction, InstructionDecoder, MemoDecoder, StakeDecoder,
+    SystemDecoder, TokenDecoder, UpgradeableLoaderDecoder, Value, ASSOCIATED_TOKEN_PROGRAM_ID,
+    BPF_LOADER_UPGRADEABLE_PROGRAM_ID, MEMO_PROGRAM_ID, STAKE_PROGRAM_ID, SYSTEM_PROGRAM_ID,
+    TOKEN_PROGRAM_ID,
+};
+use solana_sdk::instruction::{AccountMeta, Instruction};
+use solana_sdk::pubkey::Pubkey;
+use solana_system_interface::instruction as system_instruction;
+
+fn keys<const N: usize>() -> [Pubkey; N] {
+    std::array::from_fn(|_| Pubkey::new_unique())
+}
+
+fn instruction(program_id: Pubkey, data: Vec<u8>, accounts: &[Pubkey]) -> Instruction {
+    Instruction::new_with_bytes(
+        program_id,
+        &data,
+        accounts
+            .iter()
+            .map(|key| AccountMeta::new(*key, false))
+            .collect(),
+    )
+}
+
+#[test]
+fn system() {
+    let [from, base, to, nonce, authority, new_authority] = keys();
+    let decoder = SystemDecoder;
+    assert!(decoder.handles(&SYSTEM_PROGRAM_ID));
+
+    let transfer = system_instruction::transfer_with_seed(
+        &from,
+        &base,
+        "seed".to_string(),
+        &Pubkey::new_unique(),
+        &to,
+        42,
+    );
+    assert_eq!(
+        decoder.decode(&transfer),
+        Some(
+            DecodedInstruction::new("System Program", "TransferWithSeed")
+                .with("from", Value::Pubkey(from))
+                .with("base", Value::Pubkey(base))
+                .with("to", Value::Pubkey(to))
+                .with("seed", Value::Text("seed".to_string()))
+                .with("lamports", Value::Lamports(42))
+        )
+    );
+
+    let authorize = system_instruction::authorize_nonce_account(&nonce, &authority, &new_authority);
+    assert_eq!(
+        decoder.decode(&authorize),
+        Some(
+            DecodedInstruction::new("System Program", "AuthorizeNonceAccount")
+                .with("nonce_account", Value::Pubkey(nonce))
+                .with("authority", Value::Pubkey(authority))
+                .with("new_authority", Value::Pubkey(new_authority))
+        )
+    );
+
+    let unknown = instruction(SYSTEM_PROGRAM_ID, 99u32.to_le_bytes().to_vec(), &[from]);
+    assert_eq!(decoder.decode(&unknown), None);
+}
+
+#[test]
+fn token() {
+    let [account, current_authority, new_authority, source, mint, destination] = keys();
+    let decoder = TokenDecoder;
+    assert!(decoder.handles(&TOKEN_PROGRAM_ID));
+
+    let mut data = vec![6, 2, 1];
+    data.extend_from_slice(new_authority.as_ref());
+    let set_authority = instruction(TOKEN_PROGRAM_ID, data, &[account, current_authority]);
+    assert_eq!(
+        decoder.decode(&set_authority),
+        Some(
+            DecodedInstruction::new("Token Program", "SetAuthority")
+                .with("account", Value::Pubkey(account))
+                .with("current_authority", Value::Pubkey(current_authority))
+                .with("authority_type", Value::Text("AccountOwner".to_string()))
+                .with("new_authority", Value::OptionalPubkey(Some(new_authority)))
+        )
+    );
+
+    let mut data = vec![12];
+    data.extend_from_slice(&1_500u64.to_le_bytes());
+    data.push(6);
+    let transfer = instruction(
+        TOKEN_PROGRAM_ID,
+        data,
+        &[source, mint, destination, current_authority],
+    );
+    assert_eq!(
+        decoder.decode(&transfer),
+        Some(
+            DecodedInstruction::new("Token Program", "TransferChecked")
+                .with("source", Value::Pubkey(source))
+                .with("mint", Value::Pubkey(mint))
+                .with("destination", Value::Pubkey(destination))
+                .with("authority", Value::Pubkey(current_authority))
+                .with(
+                    "amount",
+                    Value::TokenAmount {
+                        amount: 1_500,
+                        decimals: Some(6)
+                    }
+                )
+        )
+    );
+
+    // A truncated amount does not decode.
+    let truncated = instruction(
+        TOKEN_PROGRAM_ID,
+        vec![3, 1, 2],
+        &[source, destination, mint],
+    );
+    assert_eq!(decoder.decode(&truncated), None);
+}
+
+#[test]
+fn associated_token() {
+    let [payer, account, wallet, mint, system_program, token_program] = keys();
+    let decoder = AssociatedTokenDecoder;
+    assert!(decoder.handles(&ASSOCIATED_TOKEN_PROGRAM_ID));
+
+    let accounts = [payer, account, wallet, mint, system_program, token_program];
+    let create = instruction(ASSOCIATED_TOKEN_PROGRAM_ID, vec![1], &accounts);
+    assert_eq!(
+        decoder.decode(&create),
+        Some(
+            DecodedInstruction::new("Associated Token Account Program", "CreateIdempotent")
+                .with("payer", Value::Pubkey(payer))
+                .with("account", Value::Pubkey(account))
+                .with("wallet", Value::Pubkey(wallet))
+                .with("mint", Value::Pubkey(mint))
+                .with("token_program", Value::Pubkey(token_program))
+        )
+    );
+
+    let legacy = instruction(ASSOCIATED_TOKEN_PROGRAM_ID, vec![], &accounts);
+    assert_eq!(decoder.decode(&legacy).unwrap().name, "Create");
+}
+
+#[test]
+fn memo() {
+    let [signer] = keys();
+    let decoder = MemoDecoder;
+    assert!(decoder.handles(&MEMO_PROGRAM_ID));
+
+    let memo = instruction(MEMO_PROGRAM_ID, b"payroll".to_vec(), &[signer]);
+    assert_eq!(
+        decoder.decode(&memo),
+        Some(
+            DecodedInstruction::new("Memo Program", "Memo")
+                .with("memo", Value::Text("payroll".to_string()))
+                .with("signer", Value::Pubkey(signer))
+        )
+    );
+
+    let not_utf8 = instruction(MEMO_PROGRAM_ID, vec![0xff, 0xfe], &[]);
+    assert_eq!(decoder.decode(&not_utf8), None);
+}
+
+#[test]
+fn loader() {
+    let [program_data, current_authority, new_authority] = keys();
+    let decoder = UpgradeableLoaderDecoder;
+    assert!(decoder.handles(&BPF_LOADER_UPGRADEABLE_PROGRAM_ID));
+
+    let data = 4u32.to_le_bytes().to_vec();
+    let set_authority = instruction(
+        BPF_LOADER_UPGRADEABLE_PROGRAM_ID,
+        data.clone(),
+        &[program_data, current_authority, new_authority],
+    );
+    assert_eq!(
+        decoder.decode(&set_authority),
+        Some(
+            DecodedInstruction::new("BPF Upgradeable Loader", "SetAuthority")
+                .with("account", Value::Pubkey(program_data))
+                .with("current_authority", Value::Pubkey(current_authority))
+                .with("new_authority", Value::OptionalPubkey(Some(new_authority)))
+        )
+    );
+
+    // Without a new authority the program becomes immutable.
+    let immutable = instruction(
+        BPF_LOADER_UPGRADEABLE_PROGRAM_ID,
+        data,
+        &[program_data, current_authority],
+    );
+    assert_eq!(
+        decoder.decode(&immutable).unwrap().field("new_authority"),
+        Some(&Value::OptionalPubkey(None))
+    );
+}
+
+#[test]
+fn stake() {
+    let [stake_account, clock, authority, new_authority] = keys();
+    let decoder = StakeDecoder;
+    assert!(decoder.handles(&STAKE_PROGRAM_ID));
+
+    let mut data = 1u32.to_le_bytes().to_vec();
+    data.extend_from_slice(new_authority.as_ref());
+    data.extend_from_slice(&1u32.to_le_bytes());
+    let authorize = instruction(STAKE_PROGRAM_ID, data, &[stake_account, clock, authority]);
+    assert_eq!(
+        decoder.decode(&authorize),
+        Some(
+            DecodedInstruction::new("Stake Program", "Authorize")
+                .with("stake_account", Value::Pubkey(stake_account))
+                .with("authority", Value::Pubkey(authority))
+                .with("new_authority", Value::Pubkey(new_authority))
+                .with("authority_type", Value::Text("Withdrawer".to_string()))
+        )
+    );
+
+    // `Authorize` reads its authority at the third account.
+    let missing_authority = instruction(STAKE_PROGRAM_ID, authorize.data, &[stake_account, clock]);
+    assert_eq!(decoder.decode(&missing_authority), None);
+}
@@ -0,0 +1,119 @@
+use fortis_sdk::client::proposal_create;
+use fortis_sdk::error::ClientError;
+use fortis_sdk::instruction::{FortisInstruction, PROPOSAL_ACCOUNTS_CLOSE, PROPOSAL_APPROVE};
+use fortis_sdk::state::{
+    MultisigCreateArgs, ProposalApproveArgs, ProposalCreateAccounts, ProposalCreateArgs,
+    ProposallExecuteArgs, VaultTransactionMessage,
+};
+use solana_sdk::instruction::{AccountMeta, Instruction};
+use solana_sdk::pubkey::Pubkey;
+
+fn round_trip(instruction: FortisInstruction) {
+    let data = instruction.to_vec();
+    assert_eq!(data[0], instruction.discriminator());
+    assert_eq!(
+        FortisInstruction::try_from_slice(&data).unwrap(),
+        instruction
+    );
+}
+
+#[test]
+fn multisig_create_round_trips() {
+    round_trip(FortisInstruction::MultisigCreate(MultisigCreateArgs {
+        threshold: 2,
+        rent_collector: Some(Pubkey::new_unique()),
+        members: vec![Pubkey::new_unique(), Pubkey::new_unique()],
+    }));
+    round_trip(FortisInstruction::MultisigCreate(MultisigCreateArgs {
+        threshold: 1,
+        rent_collector: None,
+        members: vec![Pubkey::new_unique()],
+    }));
+}
+
+#[test]
+fn multisig_create_matches_args_encoding() {
+    let args = MultisigCreateArgs {
+        threshold: 2,
+        rent_collector: Some(Pubkey::new_unique()),
+        members: vec![Pubkey::new_unique()],
+    };
+    let data = FortisInstruction::MultisigCreate(args.clone()).to_vec();
+    assert_eq!(&data[1..], args.to_vec().as_slice());
+}
+
+#[test]
+fn proposal_create_round_trips() {
+    round_trip(FortisInstruction::ProposalCreate(ProposalCreateArgs {
+        ephemeral_signers: 2,
+        voting_deadline: 1_700_000_000,
+        transaction_message: vec![1, 2, 3, 4],
+    }));
+}
+
+#[test]
+fn proposal_create_builder_data_parses_back() {
+    let vault = Pubkey::new_unique();
+    let message = VaultTransactionMessage::try_compile(
+        &vault,
+        &[Instruction::new_with_bytes(
+            Pubkey::new_unique(),
+            &[5, 6],
+            vec![AccountMeta::new(vault, true)],
+        )],
+        &[],
+    )
+    .unwrap();
+    let accounts = ProposalCreateAccounts {
+        multisig: Pubkey::new_unique(),
+        trasaction: Pubkey::new_unique(),
+        creator: Pubkey::new_unique(),
+        proposal: Pubkey::new_unique(),
+        system_program: Pubkey::default(),
+    };
+    let instruction = proposal_create(accounts, 0, &message, 1_700_000_000, None).unwrap();
+
+    let FortisInstruction::ProposalCreate(args) =
+        FortisInstruction::try_from_slice(&instruction.data).unwrap()
+    else {
+        panic!("not a proposal_create instruction");
+    };
+    assert_eq!(args.ephemeral_signers, 0);
+    assert_eq!(args.voting_deadline, 1_700_000_000);
+    assert_eq!(args.transaction_message().unwrap(), message);
+}
+
+#[test]
+fn proposal_approve_round_trips() {
+    round_trip(FortisInstruction::ProposalApprove(ProposalApproveArgs {}));
+}
+
+#[test]
+fn proposal_execute_round_trips() {
+    round_trip(FortisInstruction::ProposalExecute(ProposallExecuteArgs {}));
+}
+
+#[test]
+fn proposal_accounts_close_round_trips() {
+    round_trip(FortisInstruction::ProposalAccountsClose);
+}
+
+#[test]
+fn rejects_bad_instruction_data() {
+    assert!(matches!(
+        FortisInstruction::try_from_slice(&[]),
+        Err(ClientError::InvalidInstructionData(_))
+    ));
+    assert!(matches!(
+        FortisInstruction::try_from_slice(&[9]),
+        Err(ClientError::UnknownInstruction(9))
+    ));
+    assert!(matches!(
+        FortisInstruction::try_from_slice(&[PROPOSAL_APPROVE, 0]),
+        Err(ClientError::InvalidInstructionData(_))
+    ));
+    assert!(matches!(
+        FortisInstruction::try_from_slice(&[PROPOSAL_ACCOUNTS_CLOSE, 0]),
+        Err(ClientError::InvalidInstructionData(_))
+    ));
+}
@@ -0,0 +1,102 @@
+use fortis_sdk::explain::BPF_LOADER_UPGRADEABLE_PROGRAM_ID;
+use fortis_sdk::lint::{Finding, LintConfig, LintContext, Linter, Rule};
+use fortis_sdk::state::VaultTransactionMessage;
+use solana_sdk::instruction::{AccountMeta, Instruction};
+use solana_sdk::pubkey::Pubkey;
+
+struct Fixture {
+    vault: Pubkey,
+    member: Pubkey,
+}
+
+impl Fixture {
+    fn new() -> Self {
+        Self {
+            vault: Pubkey::new_unique(),
+            member: Pubkey::new_unique(),
+        }
+    }
+
+    fn context(&self) -> LintContext {
+        LintContext {
+            vault: self.vault,
+            members: vec![self.member],
+            ephemeral_signers: vec![],
+            vault_lamports: None,
+            rent_exempt_minimum: 0,
+        }
+    }
+
+    fn lint(&self, linter: &Linter, instructions: &[Instruction]) -> Vec<Finding> {
+        let message = VaultTransactionMessage::try_compile(&self.vault, instructions, &[]).unwrap();
+        linter.lint(&message, &[], &self.context()).unwrap()
+    }
+
+    /// Upgrade authority change of a program data account held by the vault.
+    fn set_upgrade_authority(&self, new_authority: Pubkey) -> Instruction {
+        Instruction::new_with_bytes(
+            BPF_LOADER_UPGRADEABLE_PROGRAM_ID,
+            &4u32.to_le_bytes(),
+            vec![
+                AccountMeta::new(Pubkey::new_unique(), false),
+                AccountMeta::new_readonly(self.vault, true),
+                AccountMeta::new_readonly(new_authority, false),
+            ],
+        )
+    }
+}
+
+fn rules(findings: &[Finding]) -> Vec<(Rule, usize)> {
+    findings
+        .iter()
+        .map(|finding| (finding.rule, finding.instruction_index))
+        .collect()
+}
+
+#[test]
+fn authority_handed_to_a_member_is_flagged() {
+    let fixture = Fixture::new();
+    let findings = fixture.lint(
+        &Linter::default(),
+        &[fixture.set_upgrade_authority(fixture.member)],
+    );
+    assert_eq!(rules(&findings), vec![(Rule::AuthorityChange, 0)]);
+}
+
+#[test]
+fn authority_handed_to_a_trusted_account_is_allowed() {
+    let fixture = Fixture::new();
+    let trusted = Pubkey::new_unique();
+    let linter = Linter::new(LintConfig::default().with_trusted_accounts(vec![trusted]));
+
+    assert!(fixture
+        .lint(&linter, &[fixture.set_upgrade_authority(trusted)])
+        .is_empty());
+    assert!(fixture
+        .lint(&linter, &[fixture.set_upgrade_authority(fixture.vault)])
+        .is_empty());
+}
+
+#[test]
+fn unexpected_signer_is_reported_per_instruction() {
+    let fixture = Fixture::new();
+    let outsider = Pubkey::new_unique();
+    let program = Pubkey::new_unique();
+    let signed_by_outsider = |data: u8| {
+        Instruction::new_with_bytes(
+            program,
+            &[data],
+            vec![AccountMeta::new_readonly(outsider, true)],
+        )
+    };
+    let unrelated = Instruction::new_with_bytes(program, &[], vec![]);
+
+    let findings = fixture.lint(
+        &Linter::default(),
+        &[signed_by_outsider(0), unrelated, signed_by_outsider(1)],
+    );
+    assert_eq!(
+        rules(&findings),
+        vec![(Rule::UnexpectedSigner, 0), (Rule::UnexpectedSigner, 2)]
+    );
+}
@@ -0,0 +1,181 @@
+//! Durable nonces, reading nonce accounts from an RPC stand-in.
+use std::collections::HashMap;
+
+use async_trait::async_trait;
+use base64::Engine;
+use fortis_sdk::error::ClientError;
+use fortis_sdk::nonce::{get_nonce, DurableNonce, NonceInfo};
+use fortis_sdk::solana_client::client_error::Result as RpcResult;
+use fortis_sdk::solana_client::nonblocking::rpc_client::RpcClient;
+use fortis_sdk::solana_client::rpc_client::RpcClientConfig;
+use fortis_sdk::solana_client::rpc_request::RpcRequest;
+use fortis_sdk::solana_client::rpc_sender::{RpcSender, RpcTransportStats};
+use serde_json::{json, Value};
+use solana_message::Hash;
+use solana_nonce::state::{Data, State};
+use solana_nonce::versions::Versions;
+use solana_sdk::instruction::Instruction;
+use solana_sdk::pubkey::Pubkey;
+
+const SYSTEM_PROGRAM_ID: Pubkey = Pubkey::new_from_array([0; 32]);
+
+/// Serves accounts by address, as `(owner, data)`.
+#[derive(Default)]
+struct Accounts(HashMap<Pubkey, (Pubkey, Vec<u8>)>);
+
+impl Accounts {
+    fn with_nonce(mut self, address: Pubkey, state: State) -> Self {
+        let data = bincode::serialize(&Versions::new(state)).unwrap();
+        self.0.insert(address, (SYSTEM_PROGRAM_ID, data));
+        self
+    }
+}
+
+#[async_trait]
+impl RpcSender for Accounts {
+    async fn send(&self, request: RpcRequest, params: Value) -> RpcResult<Value> {
+        assert_eq!(request, RpcRequest::GetAccountInfo);
+        let address: Pubkey = params[0].as_str().unwrap().parse().unwrap();
+        let value = self.0.get(&address).map(|(owner, data)| {
+            json!({
+                "lamports": 1_447_680,
+                "data": [base64::engine::general_purpose::STANDARD.encode(data), "base64"],
+                "owner": owner.to_string(),
+                "executable": false,
+                "rentEpoch": 0,
+                "space": data.len(),
+            })
+        });
+        Ok(json!({ "context": { "slot": 1 }, "value": value }))
+    }
+
+    fn get_transport_stats(&self) -> RpcTransportStats {
+        RpcTransportStats::default()
+    }
+
+    fn url(&self) -> String {
+        "stand-in".to_string()
+    }
+}
+
+fn initialized(authority: Pubkey, blockhash: &Hash) -> State {
+    State::Initialized(Data::new(
+        authority,
+        solana_nonce::state::DurableNonce::from_blockhash(blockhash),
+        5_000,
+    ))
+}
+
+async fn fetch(accounts: Accounts, address: &Pubkey) -> Result<NonceInfo, ClientError> {
+    get_nonce(
+        &RpcClient::new_sender(accounts, RpcClientConfig::default()),
+        address,
+    )
+    .await
+}
+
+#[tokio::test]
+async fn reads_an_initialized_nonce_account() {
+    let (address, authority) = (Pubkey::new_unique(), Pubkey::new_unique());
+    let state = initialized(authority, &Hash::new_unique());
+    let State::Initialized(data) = &state else {
+        unreachable!()
+    };
+    let stored = data.blockhash();
+
+    let info = fetch(Accounts::default().with_nonce(address, state), &address)
+        .await
+        .unwrap();
+
+    assert_eq!(
+        info,
+        NonceInfo {
+            account: address,
+            authority,
+            blockhash: stored,
+            lamports_per_signature: 5_000,
+        }
+    );
+    let durable_nonce = DurableNonce {
+        account: address,
+        authority,
+    };
+    durable_nonce.check(&info).unwrap();
+    assert_eq!(durable_nonce.blockhash(&info).unwrap(), stored);
+}
+
+#[tokio::test]
+async fn rejects_accounts_that_are_not_nonces() {
+    let (missing, uninitialized, foreign) = (
+        Pubkey::new_unique(),
+        Pubkey::new_unique(),
+        Pubkey::new_unique(),
+    );
+    let mut accounts = Accounts::default().with_nonce(uninitialized, State::Uninitialized);
+    let data = bincode::serialize(&Versions::new(initialized(
+        Pubkey::new_unique(),
+        &Hash::new_unique(),
+    )))
+    .unwrap();
+    accounts.0.insert(foreign, (Pubkey::new_unique(), data));
+    let rpc_client = RpcClient::new_sender(accounts, RpcClientConfig::default());
+
+    for address in [missing, uninitialized, foreign] {
+        assert!(matches!(
+            get_nonce(&rpc_client, &address).await,
+            Err(ClientError::InvalidNonceAccount { address: reported, .. }) if reported == address
+        ));
+    }
+    assert!(matches!(
+        get_nonce(&rpc_client, &missing).await,
+        Err(ClientError::InvalidNonceAccount { message, .. }) if message == "account not found"
+    ));
+}
+
+#[test]
+fn check_rejects_another_authority_or_account() {
+    let durable_nonce = DurableNonce {
+        account: Pubkey::new_unique(),
+        authority: Pubkey::new_unique(),
+    };
+    let info = NonceInfo {
+        account: durable_nonce.account,
+        authority: durable_nonce.authority,
+        blockhash: Hash::new_unique(),
+        lamports_per_signature: 5_000,
+    };
+    let other_authority = NonceInfo {
+        authority: Pubkey::new_unique(),
+        ..info
+    };
+    let other_account = NonceInfo {
+        account: Pubkey::new_unique(),
+        ..info
+    };
+
+    assert!(matches!(
+        durable_nonce.check(&other_authority),
+        Err(ClientError::NonceAuthorityMismatch { account, expected, actual })
+            if account == durable_nonce.account
+                && expected == durable_nonce.authority
+                && actual == other_authority.authority
+    ));
+    assert!(matches!(
+        durable_nonce.blockhash(&other_account),
+        Err(ClientError::NonceAuthorityMismatch { account, .. }) if account == durable_nonce.account
+    ));
+}
+
+#[test]
+fn advance_comes_first() {
+    let durable_nonce = DurableNonce {
+        account: Pubkey::new_unique(),
+        authority: Pubkey::new_unique(),
+    };
+    let instruction = Instruction::new_with_bytes(Pubkey::new_unique(), &[1], vec![]);
+
+    assert_eq!(
+        durable_nonce.with_advance_instruction(std::slice::from_ref(&instruction)),
+        vec![durable_nonce.advance_instruction(), instruction]
+    );
+}
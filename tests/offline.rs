@@ -0,0 +1,115 @@
+use fortis_sdk::error::ClientError;
+use fortis_sdk::offline::PartiallySignedTransaction;
+use solana_message::{Hash, Message, VersionedMessage};
+use solana_sdk::instruction::{AccountMeta, Instruction};
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::{Keypair, Signature};
+use solana_signer::Signer;
+use solana_transaction::versioned::VersionedTransaction;
+
+fn message(payer: &Pubkey, other_signer: &Pubkey) -> Message {
+    let instruction = Instruction::new_with_bytes(
+        Pubkey::new_unique(),
+        &[],
+        vec![AccountMeta::new_readonly(*other_signer, true)],
+    );
+    Message::new_with_blockhash(&[instruction], Some(payer), &Hash::new_unique())
+}
+
+#[test]
+fn signs_and_round_trips() {
+    let (payer, member) = (Keypair::new(), Keypair::new());
+    let instruction = Instruction::new_with_bytes(
+        Pubkey::new_unique(),
+        &[],
+        vec![AccountMeta::new_readonly(member.pubkey(), true)],
+    );
+    let mut transaction =
+        PartiallySignedTransaction::new(&payer.pubkey(), &[instruction], &[], Hash::new_unique())
+            .unwrap();
+    assert_eq!(
+        transaction.required_signers(),
+        &[payer.pubkey(), member.pubkey()]
+    );
+
+    transaction.sign(&payer).unwrap();
+    let mut received =
+        PartiallySignedTransaction::from_base64(&transaction.to_base64().unwrap()).unwrap();
+    assert_eq!(received.missing_signers(), vec![member.pubkey()]);
+
+    received.sign(&member).unwrap();
+    transaction.merge(&received).unwrap();
+    assert!(transaction.verify().is_ok());
+}
+
+#[test]
+fn rejects_header_claiming_more_signers_than_keys() {
+    let (payer, member) = (Pubkey::new_unique(), Pubkey::new_unique());
+    let mut message = message(&payer, &member);
+    let num_keys = message.account_keys.len() as u8;
+    message.header.num_required_signatures = num_keys + 2;
+    let transaction = VersionedTransaction {
+        signatures: vec![Signature::default(); usize::from(num_keys) + 2],
+        message: VersionedMessage::Legacy(message),
+    };
+    let bytes = bincode::serialize(&transaction).unwrap();
+
+    assert!(matches!(
+        PartiallySignedTransaction::from_bytes(&bytes),
+        Err(ClientError::TransactionDecodeError(_))
+    ));
+    assert!(matches!(
+        PartiallySignedTransaction::from_base58(&bs58::encode(&bytes).into_string()),
+        Err(ClientError::TransactionDecodeError(_))
+    ));
+    assert!(matches!(
+        PartiallySignedTransaction::from_transaction(transaction),
+        Err(ClientError::TransactionDecodeError(_))
+    ));
+}
+
+#[test]
+fn rejects_signature_count_mismatch() {
+    let (payer, member) = (Pubkey::new_unique(), Pubkey::new_unique());
+    let transaction = VersionedTransaction {
+        signatures: vec![Signature::default()],
+        message: VersionedMessage::Legacy(message(&payer, &member)),
+    };
+
+    assert!(matches!(
+        PartiallySignedTransaction::from_transaction(transaction),
+        Err(ClientError::TransactionDecodeError(_))
+    ));
+}
+
+#[test]
+fn merge_refuses_invalid_signatures() {
+    let (payer, member) = (Keypair::new(), Keypair::new());
+    let instruction = Instruction::new_with_bytes(
+        Pubkey::new_unique(),
+        &[],
+        vec![AccountMeta::new_readonly(member.pubkey(), true)],
+    );
+    let unsigned =
+        PartiallySignedTransaction::new(&payer.pubkey(), &[instruction], &[], Hash::new_unique())
+            .unwrap();
+
+    let mut transaction = unsigned.clone();
+    transaction.sign(&payer).unwrap();
+    let mut signed_by_member = unsigned.clone();
+    signed_by_member.sign(&member).unwrap();
+
+    // A copy also carrying a payer signature over another message.
+    let mut corrupted = signed_by_member.transaction().clone();
+    corrupted.signatures[0] = payer.sign_message(b"another message");
+    let corrupted = PartiallySignedTransaction::from_transaction(corrupted).unwrap();
+
+    assert!(matches!(
+        transaction.merge(&corrupted),
+        Err(ClientError::InvalidSignature(key)) if key == payer.pubkey()
+    ));
+    assert_eq!(transaction.missing_signers(), vec![member.pubkey()]);
+
+    transaction.merge(&signed_by_member).unwrap();
+    assert!(transaction.verify().is_ok());
+}
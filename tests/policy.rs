@@ -0,0 +1,206 @@
+#![cfg(feature = "policy")]
+
+use fortis_sdk::explain::TOKEN_PROGRAM_ID;
+use fortis_sdk::pda::{get_multisig_pda, get_vault_pda};
+use fortis_sdk::policy::{evaluate, Asset, Evaluation, Policy, PolicyHistory, Spend};
+use fortis_sdk::state::{Multisig, VaultTransaction, VaultTransactionMessage};
+use solana_sdk::instruction::{AccountMeta, Instruction};
+use solana_sdk::pubkey::Pubkey;
+
+const SOL: u64 = 1_000_000_000;
+
+struct Fixture {
+    multisig_key: Pubkey,
+    multisig: Multisig,
+    vault: Pubkey,
+}
+
+impl Fixture {
+    fn new() -> Self {
+        let create_key = Pubkey::new_unique();
+        let (multisig_key, bump) = get_multisig_pda(&create_key, None);
+        Self {
+            multisig_key,
+            multisig: Multisig {
+                create_key,
+                rent_collector: Pubkey::new_unique(),
+                transaction_index: 1,
+                threshold: 1,
+                bump,
+                members: vec![Pubkey::new_unique()],
+            },
+            vault: get_vault_pda(&multisig_key, None).0,
+        }
+    }
+
+    fn evaluate(
+        &self,
+        policy: &Policy,
+        instructions: &[Instruction],
+        history: &PolicyHistory,
+    ) -> Evaluation {
+        let vault_transaction = VaultTransaction {
+            multisig: self.multisig_key,
+            creator: self.multisig.members[0],
+            index: 1,
+            bump: 0,
+            vault_bump: 0,
+            ephemeral_signer_bumps: vec![],
+            message: VaultTransactionMessage::try_compile(&self.vault, instructions, &[]).unwrap(),
+        };
+        evaluate(
+            policy,
+            &self.multisig,
+            &vault_transaction,
+            &[],
+            history,
+            None,
+        )
+        .unwrap()
+    }
+
+    fn token_instruction(&self, data: Vec<u8>, accounts: Vec<Pubkey>) -> Instruction {
+        let mut metas: Vec<AccountMeta> = accounts
+            .into_iter()
+            .map(|account| AccountMeta::new(account, false))
+            .collect();
+        metas.push(AccountMeta::new_readonly(self.vault, true));
+        Instruction::new_with_bytes(TOKEN_PROGRAM_ID, &data, metas)
+    }
+
+    fn approve_checked(&self, mint: Pubkey, delegate: Pubkey, amount: u64) -> Instruction {
+        let mut data = vec![13];
+        data.extend_from_slice(&amount.to_le_bytes());
+        data.push(6);
+        self.token_instruction(data, vec![Pubkey::new_unique(), mint, delegate])
+    }
+
+    fn approve(&self, delegate: Pubkey, amount: u64) -> Instruction {
+        let mut data = vec![4];
+        data.extend_from_slice(&amount.to_le_bytes());
+        self.token_instruction(data, vec![Pubkey::new_unique(), delegate])
+    }
+
+    fn set_owner(&self, new_owner: Pubkey) -> Instruction {
+        let mut data = vec![6, 2, 1];
+        data.extend_from_slice(new_owner.as_ref());
+        self.token_instruction(data, vec![Pubkey::new_unique()])
+    }
+}
+
+fn token_policy(mint: Pubkey, per_day: u64) -> Policy {
+    Policy::from_toml(&format!(
+        "[[spending_limits]]\nmint = \"{mint}\"\nper_day = {per_day}\n"
+    ))
+    .unwrap()
+}
+
+#[test]
+fn loads_toml_and_json() {
+    let policy = Policy::from_toml(
+        r#"
+allowed_programs = ["11111111111111111111111111111111"]
+
+[[spending_limits]]
+per_recipient = 10_000_000_000
+
+[[required_reviewers]]
+program = "BPFLoaderUpgradeab1e11111111111111111111111"
+reviewers = ["11111111111111111111111111111111"]
+"#,
+    )
+    .unwrap();
+
+    assert_eq!(policy.required_reviewers[0].min_approvals, 1);
+    assert_eq!(
+        Policy::from_json(&policy.to_json().unwrap()).unwrap(),
+        policy
+    );
+    assert_eq!(
+        Policy::from_toml(&policy.to_toml().unwrap()).unwrap(),
+        policy
+    );
+    assert!(Policy::from_toml("per_week = 1").is_err());
+}
+
+#[test]
+fn daily_cap_includes_history() {
+    let fixture = Fixture::new();
+    let recipient = Pubkey::new_unique();
+    let policy = Policy::from_toml("[[spending_limits]]\nper_day = 15_000_000_000\n").unwrap();
+    let transfer =
+        solana_system_interface::instruction::transfer(&fixture.vault, &recipient, 12 * SOL);
+
+    assert!(fixture
+        .evaluate(
+            &policy,
+            std::slice::from_ref(&transfer),
+            &PolicyHistory::default()
+        )
+        .is_allowed());
+
+    let history = PolicyHistory::new(vec![Spend {
+        asset: Asset::Sol,
+        recipient,
+        amount: 5 * SOL,
+    }]);
+    let evaluation = fixture.evaluate(&policy, &[transfer], &history);
+    assert!(!evaluation.is_allowed());
+    assert_eq!(evaluation.reasons.len(), 1);
+}
+
+#[test]
+fn transfer_with_seed_from_the_vault_is_a_spend() {
+    let fixture = Fixture::new();
+    let seeded = Pubkey::create_with_seed(&fixture.vault, "savings", &Pubkey::default()).unwrap();
+    let policy = Policy::from_toml("[[spending_limits]]\nper_day = 1_000_000_000\n").unwrap();
+    let transfer = solana_system_interface::instruction::transfer_with_seed(
+        &seeded,
+        &fixture.vault,
+        "savings".to_string(),
+        &Pubkey::default(),
+        &Pubkey::new_unique(),
+        2 * SOL,
+    );
+
+    assert!(!fixture
+        .evaluate(&policy, &[transfer], &PolicyHistory::default())
+        .is_allowed());
+}
+
+#[test]
+fn delegation_counts_against_the_token_cap() {
+    let fixture = Fixture::new();
+    let mint = Pubkey::new_unique();
+    let policy = token_policy(mint, 1_000);
+
+    let within = fixture.approve_checked(mint, Pubkey::new_unique(), 1_000);
+    assert!(fixture
+        .evaluate(&policy, &[within], &PolicyHistory::default())
+        .is_allowed());
+
+    let unlimited = fixture.approve_checked(mint, Pubkey::new_unique(), u64::MAX);
+    assert!(!fixture
+        .evaluate(&policy, &[unlimited], &PolicyHistory::default())
+        .is_allowed());
+}
+
+#[test]
+fn unknown_mint_is_denied_only_with_token_limits() {
+    let fixture = Fixture::new();
+    let token_policy = token_policy(Pubkey::new_unique(), 1_000);
+    let sol_policy = Policy::from_toml("[[spending_limits]]\nper_day = 1\n").unwrap();
+
+    for instruction in [
+        fixture.approve(Pubkey::new_unique(), 1),
+        fixture.set_owner(Pubkey::new_unique()),
+    ] {
+        let instructions = [instruction];
+        assert!(!fixture
+            .evaluate(&token_policy, &instructions, &PolicyHistory::default())
+            .is_allowed());
+        assert!(fixture
+            .evaluate(&sol_policy, &instructions, &PolicyHistory::default())
+            .is_allowed());
+    }
+}
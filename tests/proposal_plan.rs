@@ -0,0 +1,148 @@
+use fortis_sdk::client::proposal_create;
+use fortis_sdk::error::ClientError;
+use fortis_sdk::instruction::FortisInstruction;
+use fortis_sdk::pda::{get_ephemeral_signer_pda, get_transaction_pda, get_vault_pda};
+use fortis_sdk::proposal_plan::plan_proposals;
+use fortis_sdk::state::{ProposalCreateAccounts, VaultTransactionMessage};
+use solana_sdk::instruction::{AccountMeta, Instruction};
+use solana_sdk::pubkey::Pubkey;
+
+struct Fixture {
+    multisig: Pubkey,
+    vault: Pubkey,
+    creator: Pubkey,
+}
+
+impl Fixture {
+    fn new() -> Self {
+        let multisig = Pubkey::new_unique();
+        Self {
+            multisig,
+            vault: get_vault_pda(&multisig, None).0,
+            creator: Pubkey::new_unique(),
+        }
+    }
+
+    /// An instruction signed by the vault carrying `len` bytes of data.
+    fn instruction(&self, len: usize) -> Instruction {
+        Instruction::new_with_bytes(
+            Pubkey::new_unique(),
+            &vec![0; len],
+            vec![AccountMeta::new(self.vault, true)],
+        )
+    }
+
+    fn signed_by(&self, signer: Pubkey) -> Instruction {
+        let mut instruction = self.instruction(8);
+        instruction.accounts.push(AccountMeta::new(signer, true));
+        instruction
+    }
+
+    fn ephemeral_signer(&self, transaction_index: u64, index: u8) -> Pubkey {
+        let transaction = get_transaction_pda(&self.multisig, transaction_index, None).0;
+        get_ephemeral_signer_pda(&transaction, index, None).0
+    }
+}
+
+#[test]
+fn splits_batches_into_fitting_chunks() {
+    let fixture = Fixture::new();
+    let instructions: Vec<Instruction> = (0..6).map(|_| fixture.instruction(300)).collect();
+
+    let plans = plan_proposals(
+        &fixture.multisig,
+        &fixture.creator,
+        4,
+        instructions.clone(),
+        &[],
+        None,
+    )
+    .unwrap();
+
+    assert!(plans.len() > 1);
+    let indexes: Vec<u64> = plans.iter().map(|plan| plan.transaction_index).collect();
+    assert_eq!(indexes, (4..4 + plans.len() as u64).collect::<Vec<_>>());
+    let planned: Vec<Instruction> = plans
+        .iter()
+        .flat_map(|plan| plan.instructions.clone())
+        .collect();
+    assert_eq!(planned, instructions);
+    assert!(plans
+        .iter()
+        .all(|plan| plan.create_size.fits() && plan.execute_size.fits()));
+}
+
+#[test]
+fn counts_ephemeral_signers() {
+    let fixture = Fixture::new();
+    let instructions = vec![
+        fixture.signed_by(fixture.ephemeral_signer(1, 0)),
+        fixture.signed_by(fixture.ephemeral_signer(1, 1)),
+    ];
+
+    let plans = plan_proposals(
+        &fixture.multisig,
+        &fixture.creator,
+        1,
+        instructions,
+        &[],
+        None,
+    )
+    .unwrap();
+
+    assert_eq!(plans.len(), 1);
+    assert_eq!(plans[0].ephemeral_signers, 2);
+    let instruction = plans[0]
+        .proposal_create(&fixture.multisig, &fixture.creator, 0, None)
+        .unwrap();
+    match FortisInstruction::try_from_slice(&instruction.data).unwrap() {
+        FortisInstruction::ProposalCreate(args) => assert_eq!(args.ephemeral_signers, 2),
+        other => panic!("unexpected instruction {other:?}"),
+    }
+}
+
+#[test]
+fn propagates_errors_other_than_size() {
+    let fixture = Fixture::new();
+    let stranger = Pubkey::new_unique();
+    let instructions = vec![fixture.instruction(8), fixture.signed_by(stranger)];
+
+    let result = plan_proposals(
+        &fixture.multisig,
+        &fixture.creator,
+        1,
+        instructions,
+        &[],
+        None,
+    );
+
+    assert!(matches!(result, Err(ClientError::UnknownSigner(key)) if key == stranger));
+}
+
+#[test]
+fn proposal_create_checks_declared_ephemeral_signers_only() {
+    let fixture = Fixture::new();
+    let stranger = Pubkey::new_unique();
+    let instructions = vec![
+        fixture.signed_by(fixture.ephemeral_signer(1, 0)),
+        fixture.signed_by(stranger),
+    ];
+    let message = VaultTransactionMessage::try_compile(&fixture.vault, &instructions, &[]).unwrap();
+    let accounts = || ProposalCreateAccounts {
+        multisig: fixture.multisig,
+        trasaction: get_transaction_pda(&fixture.multisig, 1, None).0,
+        creator: fixture.creator,
+        proposal: Pubkey::new_unique(),
+        system_program: solana_system_interface::program::ID,
+    };
+
+    // Signers other than the declared ephemeral signers are left to the caller.
+    assert!(proposal_create(accounts(), 1, &message, 0, None).is_ok());
+
+    let result = proposal_create(accounts(), 2, &message, 0, None);
+    assert!(matches!(
+        result,
+        Err(ClientError::UnusedEphemeralSigner { index: 1, signer })
+            if signer == fixture.ephemeral_signer(1, 1)
+    ));
+}
@@ -0,0 +1,104 @@
+use fortis_sdk::error::ClientError;
+use fortis_sdk::state::{Multisig, Proposal, ProposalStatus};
+use solana_sdk::pubkey::Pubkey;
+
+const DEADLINE: i64 = 1_000;
+const BEFORE_DEADLINE: i64 = DEADLINE - 1;
+const AFTER_DEADLINE: i64 = DEADLINE + 1;
+
+fn multisig(threshold: u16) -> Multisig {
+    Multisig {
+        create_key: Pubkey::new_unique(),
+        rent_collector: Pubkey::new_unique(),
+        transaction_index: 1,
+        threshold,
+        bump: 255,
+        members: vec![Pubkey::new_unique(), Pubkey::new_unique()],
+    }
+}
+
+fn proposal(status: ProposalStatus, approved: Vec<Pubkey>) -> Proposal {
+    Proposal {
+        multisig: Pubkey::new_unique(),
+        transaction_index: 1,
+        timestamp: 0,
+        deadline: DEADLINE,
+        status,
+        bump: 255,
+        approved,
+    }
+}
+
+fn transitions(from: ProposalStatus) -> Vec<ProposalStatus> {
+    [
+        ProposalStatus::Active,
+        ProposalStatus::Approved,
+        ProposalStatus::Executed,
+    ]
+    .into_iter()
+    .filter(|next| from.can_transition_to(*next))
+    .collect()
+}
+
+#[test]
+fn active() {
+    let status = ProposalStatus::Active;
+    assert!(!status.is_final());
+    assert_eq!(transitions(status), vec![ProposalStatus::Approved]);
+
+    let multisig = multisig(2);
+    let pending = proposal(status, vec![multisig.members[0]]);
+    assert_eq!(pending.remaining_approvals(&multisig), 1);
+    assert!(!pending.is_executable());
+    assert!(!pending.is_closable(BEFORE_DEADLINE));
+    assert!(pending.is_closable(AFTER_DEADLINE));
+
+    // The threshold is met, but the program has not marked the proposal approved.
+    let at_threshold = proposal(status, multisig.members.clone());
+    assert_eq!(at_threshold.remaining_approvals(&multisig), 0);
+    assert!(!at_threshold.is_executable());
+    assert!(at_threshold.is_closable(AFTER_DEADLINE));
+}
+
+#[test]
+fn approved() {
+    let status = ProposalStatus::Approved;
+    assert!(!status.is_final());
+    assert_eq!(transitions(status), vec![ProposalStatus::Executed]);
+
+    let multisig = multisig(2);
+    let proposal = proposal(status, multisig.members.clone());
+    assert!(proposal.is_executable());
+    assert!(!proposal.is_closable(BEFORE_DEADLINE));
+    assert!(!proposal.is_closable(AFTER_DEADLINE));
+}
+
+#[test]
+fn executed() {
+    let status = ProposalStatus::Executed;
+    assert!(status.is_final());
+    assert!(transitions(status).is_empty());
+
+    let multisig = multisig(2);
+    let proposal = proposal(status, multisig.members.clone());
+    assert!(!proposal.is_executable());
+    assert!(proposal.is_closable(BEFORE_DEADLINE));
+    assert!(proposal.is_closable(AFTER_DEADLINE));
+}
+
+#[test]
+fn unknown_status_byte_is_rejected() {
+    assert!(ProposalStatus::try_from(3u8).is_err());
+
+    let proposal = proposal(ProposalStatus::Active, vec![Pubkey::new_unique()]);
+    let mut data = borsh::to_vec(&proposal).unwrap();
+    // `status` follows `multisig`, `transaction_index`, `timestamp` and `deadline`.
+    let status_offset = 32 + 8 + 8 + 8;
+    assert_eq!(data[status_offset], u8::from(ProposalStatus::Active));
+    data[status_offset] = 3;
+
+    assert!(matches!(
+        Proposal::try_from_account_data(&data),
+        Err(ClientError::AccountDecodeError { .. })
+    ));
+}
@@ -0,0 +1,179 @@
+//! `simulate_balance_diff` against an RPC stand-in.
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use async_trait::async_trait;
+use base64::Engine;
+use fortis_sdk::pda::get_vault_pda;
+use fortis_sdk::simulation::{simulate_balance_diff, simulate_vault_message};
+use fortis_sdk::solana_client::client_error::Result as RpcResult;
+use fortis_sdk::solana_client::nonblocking::rpc_client::RpcClient;
+use fortis_sdk::solana_client::rpc_client::RpcClientConfig;
+use fortis_sdk::solana_client::rpc_request::RpcRequest;
+use fortis_sdk::solana_client::rpc_sender::{RpcSender, RpcTransportStats};
+use fortis_sdk::state::VaultTransactionMessage;
+use serde_json::{json, Value};
+use solana_sdk::instruction::InstructionError;
+use solana_sdk::pubkey::Pubkey;
+use solana_transaction::versioned::VersionedTransaction;
+
+const SOL: u64 = 1_000_000_000;
+const PRE_SLOT: u64 = 10;
+const SIMULATION_SLOT: u64 = 12;
+
+/// Lamport balances before and after the simulated transaction.
+#[derive(Clone, Default)]
+struct Bank {
+    pre: HashMap<Pubkey, u64>,
+    post: HashMap<Pubkey, u64>,
+    /// Fee reported by the simulation, `None` for nodes leaving it out.
+    fee: Option<u64>,
+    /// Error the simulation fails with, as serialized by the RPC node.
+    err: Option<Value>,
+    min_context_slot: Arc<Mutex<Option<u64>>>,
+    simulated: Arc<Mutex<Option<VersionedTransaction>>>,
+}
+
+fn ui_account(lamports: Option<&u64>) -> Value {
+    match lamports {
+        Some(lamports) => json!({
+            "lamports": lamports,
+            "data": ["", "base64"],
+            "owner": Pubkey::default().to_string(),
+            "executable": false,
+            "rentEpoch": 0,
+            "space": 0,
+        }),
+        None => Value::Null,
+    }
+}
+
+fn accounts(balances: &HashMap<Pubkey, u64>, addresses: &Value) -> Value {
+    let Some(addresses) = addresses.as_array() else {
+        return Value::Null;
+    };
+    addresses
+        .iter()
+        .map(|key| ui_account(balances.get(&key.as_str().unwrap().parse().unwrap())))
+        .collect()
+}
+
+#[async_trait]
+impl RpcSender for Bank {
+    async fn send(&self, request: RpcRequest, params: Value) -> RpcResult<Value> {
+        let (slot, value) = match request {
+            RpcRequest::GetMultipleAccounts => (PRE_SLOT, accounts(&self.pre, &params[0])),
+            RpcRequest::SimulateTransaction => {
+                let config = &params[1];
+                *self.min_context_slot.lock().unwrap() = config["minContextSlot"].as_u64();
+                assert_eq!(config["encoding"], "base64");
+                let wire = base64::engine::general_purpose::STANDARD
+                    .decode(params[0].as_str().unwrap())
+                    .unwrap();
+                *self.simulated.lock().unwrap() = Some(bincode::deserialize(&wire).unwrap());
+                (
+                    SIMULATION_SLOT,
+                    json!({
+                        "err": self.err,
+                        "logs": [],
+                        "accounts": accounts(&self.post, &config["accounts"]["addresses"]),
+                        "unitsConsumed": 150,
+                        "fee": self.fee,
+                    }),
+                )
+            }
+            other => panic!("unexpected request {other}"),
+        };
+        Ok(json!({ "context": { "slot": slot }, "value": value }))
+    }
+
+    fn get_transport_stats(&self) -> RpcTransportStats {
+        RpcTransportStats::default()
+    }
+
+    fn url(&self) -> String {
+        "stand-in".to_string()
+    }
+}
+
+async fn vault_transfer_diff(fee: Option<u64>, charged_fee: u64) {
+    let multisig_key = Pubkey::new_unique();
+    let vault = get_vault_pda(&multisig_key, None).0;
+    let recipient = Pubkey::new_unique();
+    let transfer = solana_system_interface::instruction::transfer(&vault, &recipient, SOL);
+    let message = VaultTransactionMessage::try_compile(&vault, &[transfer], &[]).unwrap();
+
+    let bank = Bank {
+        pre: HashMap::from([(vault, 5 * SOL)]),
+        post: HashMap::from([(vault, 4 * SOL - charged_fee), (recipient, SOL)]),
+        fee,
+        ..Bank::default()
+    };
+    let rpc_client = RpcClient::new_sender(bank.clone(), RpcClientConfig::default());
+
+    let diff = simulate_balance_diff(&rpc_client, &multisig_key, &message, None)
+        .await
+        .unwrap();
+
+    assert_eq!(*bank.min_context_slot.lock().unwrap(), Some(PRE_SLOT));
+    assert_eq!((diff.pre_slot, diff.post_slot), (PRE_SLOT, SIMULATION_SLOT));
+    assert_eq!(diff.get(&vault).unwrap().lamports_delta(), -i128::from(SOL));
+    assert_eq!(
+        diff.get(&recipient).unwrap().lamports_delta(),
+        i128::from(SOL)
+    );
+}
+
+#[tokio::test]
+async fn vault_delta_excludes_the_reported_fee() {
+    vault_transfer_diff(Some(7_000), 7_000).await;
+}
+
+#[tokio::test]
+async fn vault_delta_excludes_the_signature_fee() {
+    vault_transfer_diff(None, 5_000).await;
+}
+
+#[tokio::test]
+async fn instruction_errors_point_into_the_vault_message() {
+    let multisig_key = Pubkey::new_unique();
+    let vault = get_vault_pda(&multisig_key, None).0;
+    let transfers: Vec<_> = [SOL, 2 * SOL]
+        .into_iter()
+        .map(|lamports| {
+            solana_system_interface::instruction::transfer(&vault, &Pubkey::new_unique(), lamports)
+        })
+        .collect();
+    let message = VaultTransactionMessage::try_compile(&vault, &transfers, &[]).unwrap();
+
+    // The compute budget instruction comes first in the simulated transaction.
+    for (simulated_index, vault_index) in [(0, None), (1, Some(0)), (2, Some(1))] {
+        let bank = Bank {
+            err: Some(json!({ "InstructionError": [simulated_index, "InvalidArgument"] })),
+            ..Bank::default()
+        };
+        let rpc_client = RpcClient::new_sender(bank.clone(), RpcClientConfig::default());
+
+        let simulation = simulate_vault_message(&rpc_client, &multisig_key, &message, None)
+            .await
+            .unwrap();
+
+        assert!(!simulation.succeeded());
+        let error = simulation.instruction_error();
+        assert_eq!(
+            error.map(|(index, _)| index),
+            vault_index,
+            "simulated instruction {simulated_index}"
+        );
+        assert!(error.is_none_or(|(_, err)| *err == InstructionError::InvalidArgument));
+
+        let transaction = bank.simulated.lock().unwrap().take().unwrap();
+        let keys = transaction.message.static_account_keys();
+        let failing = &transaction.message.instructions()[simulated_index];
+        let program_id = keys[usize::from(failing.program_id_index)];
+        match vault_index {
+            None => assert_eq!(program_id, solana_compute_budget_interface::ID),
+            Some(index) => assert_eq!(failing.data, transfers[index].data),
+        }
+    }
+}
@@ -0,0 +1,118 @@
+//! Transaction sizes, against the length of the transactions actually serialized.
+use fortis_sdk::error::ClientError;
+use fortis_sdk::transaction_size::{transaction_size, TransactionSize, PACKET_DATA_SIZE};
+use solana_message::{v0, AddressLookupTableAccount, Hash, Message, VersionedMessage};
+use solana_sdk::instruction::{AccountMeta, Instruction};
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::Signature;
+use solana_transaction::versioned::VersionedTransaction;
+
+fn instruction(data_len: usize, accounts: &[Pubkey]) -> Instruction {
+    Instruction::new_with_bytes(
+        Pubkey::new_unique(),
+        &vec![7; data_len],
+        accounts
+            .iter()
+            .map(|key| AccountMeta::new(*key, false))
+            .collect(),
+    )
+}
+
+fn serialized_len(message: VersionedMessage) -> usize {
+    let transaction = VersionedTransaction {
+        signatures: vec![
+            Signature::default();
+            usize::from(message.header().num_required_signatures)
+        ],
+        message,
+    };
+    bincode::serialize(&transaction).unwrap().len()
+}
+
+#[test]
+fn matches_the_serialized_legacy_transaction() {
+    let payer = Pubkey::new_unique();
+    let signer = Pubkey::new_unique();
+    let mut instructions = vec![instruction(40, &[Pubkey::new_unique()])];
+    instructions.push(Instruction::new_with_bytes(
+        Pubkey::new_unique(),
+        &[1],
+        vec![AccountMeta::new_readonly(signer, true)],
+    ));
+
+    let size = transaction_size(&payer, &instructions, &[]).unwrap();
+
+    let message = Message::new_with_blockhash(&instructions, Some(&payer), &Hash::default());
+    assert_eq!(size.size, serialized_len(VersionedMessage::Legacy(message)));
+    assert_eq!(size.signatures, 1 + 2 * 64);
+    assert_eq!(size.account_keys, 1 + 5 * 32);
+    assert_eq!(size.size, size.signatures + size.message);
+    assert_eq!(size.limit, PACKET_DATA_SIZE);
+}
+
+#[test]
+fn matches_the_serialized_v0_transaction() {
+    let payer = Pubkey::new_unique();
+    let looked_up: Vec<Pubkey> = (0..20).map(|_| Pubkey::new_unique()).collect();
+    let table = AddressLookupTableAccount {
+        key: Pubkey::new_unique(),
+        addresses: looked_up.clone(),
+    };
+    let instructions = [instruction(10, &looked_up)];
+
+    let size = transaction_size(&payer, &instructions, std::slice::from_ref(&table)).unwrap();
+
+    let message = v0::Message::try_compile(
+        &payer,
+        &instructions,
+        std::slice::from_ref(&table),
+        Hash::default(),
+    )
+    .unwrap();
+    assert_eq!(size.size, serialized_len(VersionedMessage::V0(message)));
+    // Only the payer and the program are static keys.
+    assert_eq!(size.account_keys, 1 + 2 * 32);
+}
+
+#[test]
+fn check_accepts_up_to_the_limit() {
+    let payer = Pubkey::new_unique();
+    let size = transaction_size(&payer, &[instruction(200, &[])], &[]).unwrap();
+    // Past 127 bytes the data length prefix stays two bytes, each extra byte adds one.
+    let data_len = 200 + (PACKET_DATA_SIZE - size.size);
+
+    let at_limit = transaction_size(&payer, &[instruction(data_len, &[])], &[]).unwrap();
+    assert_eq!(at_limit.size, PACKET_DATA_SIZE);
+    assert_eq!(at_limit.headroom(), 0);
+    assert_eq!(at_limit.check().unwrap(), at_limit);
+
+    let over = transaction_size(&payer, &[instruction(data_len + 1, &[])], &[]).unwrap();
+    assert_eq!(over.headroom(), -1);
+    assert!(!over.fits());
+    assert!(matches!(
+        over.check(),
+        Err(ClientError::TransactionTooLarge { size, limit })
+            if size == PACKET_DATA_SIZE + 1 && limit == PACKET_DATA_SIZE
+    ));
+}
+
+#[test]
+fn check_uses_the_given_limit() {
+    let size = TransactionSize {
+        size: 600,
+        signatures: 65,
+        message: 535,
+        account_keys: 97,
+        limit: 512,
+    };
+
+    assert_eq!(size.headroom(), -88);
+    assert!(matches!(
+        size.check(),
+        Err(ClientError::TransactionTooLarge {
+            size: 600,
+            limit: 512
+        })
+    ));
+    assert!(TransactionSize { limit: 600, ..size }.check().is_ok());
+}
@@ -0,0 +1,158 @@
+use std::collections::BTreeMap;
+
+use fortis_sdk::state::VaultTransactionMessage;
+use proptest::prelude::*;
+use solana_message::AddressLookupTableAccount;
+use solana_sdk::instruction::{AccountMeta, Instruction};
+use solana_sdk::pubkey::Pubkey;
+
+const NUM_KEYS: u8 = 8;
+
+fn key(index: u8) -> Pubkey {
+    Pubkey::new_from_array([index + 1; 32])
+}
+
+fn vault() -> Pubkey {
+    key(0)
+}
+
+fn account_meta() -> impl Strategy<Value = AccountMeta> {
+    (0..NUM_KEYS, any::<bool>(), any::<bool>()).prop_map(|(index, is_signer, is_writable)| {
+        AccountMeta {
+            pubkey: key(index),
+            is_signer,
+            is_writable,
+        }
+    })
+}
+
+fn instruction() -> impl Strategy<Value = Instruction> {
+    (
+        0..NUM_KEYS,
+        prop::collection::vec(account_meta(), 0..6),
+        prop::collection::vec(any::<u8>(), 0..16),
+    )
+        .prop_map(|(program, accounts, data)| Instruction {
+            program_id: key(program),
+            accounts,
+            data,
+        })
+}
+
+fn lookup_table() -> impl Strategy<Value = Option<AddressLookupTableAccount>> {
+    prop::option::of(prop::collection::vec(0..NUM_KEYS, 1..NUM_KEYS as usize)).prop_map(|indexes| {
+        indexes.map(|indexes| AddressLookupTableAccount {
+            key: Pubkey::new_from_array([0xff; 32]),
+            addresses: indexes.into_iter().map(key).collect(),
+        })
+    })
+}
+
+/// The signer and writable flags of every key merged across `instructions`, the vault being
+/// a writable signer.
+fn merged_flags(instructions: &[Instruction]) -> BTreeMap<Pubkey, (bool, bool)> {
+    let mut flags = BTreeMap::from([(vault(), (true, true))]);
+    for ix in instructions {
+        for meta in &ix.accounts {
+            let entry = flags.entry(meta.pubkey).or_insert((false, false));
+            entry.0 |= meta.is_signer;
+            entry.1 |= meta.is_writable;
+        }
+    }
+    flags
+}
+
+proptest! {
+    #[test]
+    fn decompile_inverts_try_compile(
+        instructions in prop::collection::vec(instruction(), 1..5),
+        lookup_table in lookup_table(),
+    ) {
+        let alts: Vec<AddressLookupTableAccount> = lookup_table.into_iter().collect();
+        let message = VaultTransactionMessage::try_compile(&vault(), &instructions, &alts).unwrap();
+        let decompiled = message.decompile(&alts).unwrap();
+
+        let flags = merged_flags(&instructions);
+        let expected: Vec<Instruction> = instructions
+            .iter()
+            .map(|ix| Instruction {
+                program_id: ix.program_id,
+                accounts: ix
+                    .accounts
+                    .iter()
+                    .map(|meta| {
+                        let (is_signer, is_writable) = flags[&meta.pubkey];
+                        AccountMeta { pubkey: meta.pubkey, is_signer, is_writable }
+                    })
+                    .collect(),
+                data: ix.data.clone(),
+            })
+            .collect();
+        prop_assert_eq!(decompiled, expected);
+    }
+
+    #[test]
+    fn resolve_account_metas_matches_header(
+        instructions in prop::collection::vec(instruction(), 1..5),
+        lookup_table in lookup_table(),
+    ) {
+        let alts: Vec<AddressLookupTableAccount> = lookup_table.into_iter().collect();
+        let message = VaultTransactionMessage::try_compile(&vault(), &instructions, &alts).unwrap();
+        let metas = message.resolve_account_metas(&alts).unwrap();
+
+        prop_assert_eq!(metas[0].pubkey, vault());
+        let flags = merged_flags(&instructions);
+        for (index, meta) in metas.iter().enumerate() {
+            if index < message.account_keys.len() {
+                prop_assert_eq!(meta.is_signer, message.is_signer_index(index));
+                prop_assert_eq!(meta.is_writable, message.is_static_writable_index(index));
+            }
+            // Program ids referenced only as such are readonly non-signers.
+            let expected = flags.get(&meta.pubkey).copied().unwrap_or((false, false));
+            prop_assert_eq!((meta.is_signer, meta.is_writable), expected);
+        }
+    }
+}
+
+#[test]
+fn readonly_and_writable_uses_merge_to_writable() {
+    let (program, account) = (key(1), key(2));
+    let instructions = [
+        Instruction::new_with_bytes(
+            program,
+            &[0],
+            vec![AccountMeta::new_readonly(account, false)],
+        ),
+        Instruction::new_with_bytes(program, &[1], vec![AccountMeta::new(account, false)]),
+    ];
+
+    let message = VaultTransactionMessage::try_compile(&vault(), &instructions, &[]).unwrap();
+    let decompiled = message.decompile(&[]).unwrap();
+
+    assert_eq!(
+        decompiled[0].accounts,
+        vec![AccountMeta::new(account, false)]
+    );
+    assert_eq!(
+        decompiled[1].accounts,
+        vec![AccountMeta::new(account, false)]
+    );
+}
+
+#[test]
+fn decompile_rejects_missing_lookup_table() {
+    let alt = AddressLookupTableAccount {
+        key: Pubkey::new_unique(),
+        addresses: vec![key(2)],
+    };
+    let instructions = [Instruction::new_with_bytes(
+        key(1),
+        &[],
+        vec![AccountMeta::new(key(2), false)],
+    )];
+
+    let message = VaultTransactionMessage::try_compile(&vault(), &instructions, &[alt]).unwrap();
+
+    assert_eq!(message.address_table_lookups.len(), 1);
+    assert!(message.decompile(&[]).is_err());
+}
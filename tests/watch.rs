@@ -0,0 +1,620 @@
+//! `ProposalTracker` diffing, and `watch_proposals` against local RPC and websocket stand-ins,
+//! including dropped and refused connections.
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use async_trait::async_trait;
+use base64::Engine;
+use fortis_sdk::error::ClientError;
+use fortis_sdk::pda::{get_multisig_pda, get_proposal_pda, FORTIS_PROGRAM_ID};
+use fortis_sdk::solana_client::client_error::Result as RpcResult;
+use fortis_sdk::solana_client::nonblocking::rpc_client::RpcClient;
+use fortis_sdk::solana_client::rpc_client::RpcClientConfig;
+use fortis_sdk::solana_client::rpc_request::RpcRequest;
+use fortis_sdk::solana_client::rpc_sender::{RpcSender, RpcTransportStats};
+use fortis_sdk::state::{Multisig, Proposal, ProposalStatus};
+use fortis_sdk::watch::{watch_proposals, ProposalEvent, ProposalTracker, WatchConfig};
+use futures::{SinkExt, StreamExt};
+use serde_json::{json, Value};
+use solana_sdk::pubkey::Pubkey;
+use tokio::net::TcpListener;
+use tokio::sync::mpsc::{unbounded_channel, UnboundedReceiver, UnboundedSender};
+use tokio::time::Instant;
+use tokio_tungstenite::tungstenite::Message;
+
+const TIMEOUT: Duration = Duration::from_secs(5);
+
+struct Fixture {
+    multisig_key: Pubkey,
+    multisig: Multisig,
+    member: Pubkey,
+}
+
+impl Fixture {
+    fn new() -> Self {
+        let create_key = Pubkey::new_unique();
+        let (multisig_key, bump) = get_multisig_pda(&create_key, None);
+        let member = Pubkey::new_unique();
+        Self {
+            multisig_key,
+            multisig: Multisig {
+                create_key,
+                rent_collector: Pubkey::new_unique(),
+                transaction_index: 0,
+                threshold: 1,
+                bump,
+                members: vec![member],
+            },
+            member,
+        }
+    }
+
+    fn multisig_at(&self, transaction_index: u64) -> Multisig {
+        Multisig {
+            transaction_index,
+            ..self.multisig.clone()
+        }
+    }
+
+    fn proposal_key(&self, transaction_index: u64) -> Pubkey {
+        get_proposal_pda(&self.multisig_key, transaction_index, None).0
+    }
+
+    fn proposal(
+        &self,
+        transaction_index: u64,
+        status: ProposalStatus,
+        approved: Vec<Pubkey>,
+    ) -> Proposal {
+        Proposal {
+            multisig: self.multisig_key,
+            transaction_index,
+            timestamp: 0,
+            deadline: i64::MAX,
+            status,
+            bump: get_proposal_pda(&self.multisig_key, transaction_index, None).1,
+            approved,
+        }
+    }
+}
+
+#[test]
+fn tracker_reports_lifecycle_once() {
+    let fixture = Fixture::new();
+    let mut tracker = ProposalTracker::new(fixture.multisig_key, None);
+    tracker.seed(fixture.multisig.clone(), [], 0);
+
+    assert!(tracker
+        .update_multisig(fixture.multisig_at(1), 0)
+        .is_empty());
+    assert_eq!(tracker.watched_indexes(), vec![1]);
+
+    let created = fixture.proposal(1, ProposalStatus::Active, vec![]);
+    assert_eq!(
+        tracker.update_proposal(1, Some(created.clone()), 0),
+        vec![ProposalEvent::ProposalCreated {
+            transaction_index: 1,
+            proposal: created.clone(),
+        }]
+    );
+    assert!(tracker.update_proposal(1, Some(created), 0).is_empty());
+
+    let approved = fixture.proposal(1, ProposalStatus::Approved, vec![fixture.member]);
+    assert_eq!(
+        tracker.update_proposal(1, Some(approved), 0),
+        vec![
+            ProposalEvent::Approved {
+                transaction_index: 1,
+                member: fixture.member,
+            },
+            ProposalEvent::ThresholdReached {
+                transaction_index: 1
+            },
+        ]
+    );
+
+    let executed = fixture.proposal(1, ProposalStatus::Executed, vec![fixture.member]);
+    assert_eq!(
+        tracker.update_proposal(1, Some(executed), 0),
+        vec![ProposalEvent::Executed {
+            transaction_index: 1
+        }]
+    );
+    assert_eq!(
+        tracker.update_proposal(1, None, 0),
+        vec![ProposalEvent::Closed {
+            transaction_index: 1
+        }]
+    );
+    assert!(tracker.proposal(1).is_none());
+}
+
+#[test]
+fn tracker_stops_watching_finalized_proposals() {
+    let fixture = Fixture::new();
+    let mut tracker = ProposalTracker::new(fixture.multisig_key, None);
+    let expiring = Proposal {
+        deadline: 100,
+        ..fixture.proposal(2, ProposalStatus::Active, vec![])
+    };
+    tracker.seed(
+        fixture.multisig_at(3),
+        [
+            (
+                1,
+                Some(fixture.proposal(1, ProposalStatus::Executed, vec![fixture.member])),
+            ),
+            (2, Some(expiring)),
+            (3, Some(fixture.proposal(3, ProposalStatus::Active, vec![]))),
+        ],
+        0,
+    );
+    assert_eq!(tracker.watched_indexes(), vec![2, 3]);
+
+    assert_eq!(
+        tracker.check_deadlines(101),
+        vec![ProposalEvent::Expired {
+            transaction_index: 2
+        }]
+    );
+    assert_eq!(tracker.watched_indexes(), vec![3]);
+    // Still tracked, so closing them is reported.
+    assert_eq!(
+        tracker.update_proposal(1, None, 101),
+        vec![ProposalEvent::Closed {
+            transaction_index: 1
+        }]
+    );
+}
+
+/// Accounts served over RPC, recording every request it answers.
+#[derive(Clone, Default)]
+struct Chain {
+    accounts: Arc<Mutex<HashMap<Pubkey, Vec<u8>>>>,
+    requests: Option<UnboundedSender<RpcRequest>>,
+}
+
+impl Chain {
+    fn set(&self, address: Pubkey, account: &impl borsh::BorshSerialize) {
+        self.accounts
+            .lock()
+            .unwrap()
+            .insert(address, borsh::to_vec(account).unwrap());
+    }
+
+    fn ui_account(&self, address: &Pubkey) -> Value {
+        match self.accounts.lock().unwrap().get(address) {
+            Some(data) => ui_account(data),
+            None => Value::Null,
+        }
+    }
+}
+
+fn ui_account(data: &[u8]) -> Value {
+    json!({
+        "lamports": 1_000_000,
+        "data": [base64::engine::general_purpose::STANDARD.encode(data), "base64"],
+        "owner": FORTIS_PROGRAM_ID.to_string(),
+        "executable": false,
+        "rentEpoch": 0,
+        "space": data.len(),
+    })
+}
+
+fn address(value: &Value) -> Pubkey {
+    value.as_str().unwrap().parse().unwrap()
+}
+
+#[async_trait]
+impl RpcSender for Chain {
+    async fn send(&self, request: RpcRequest, params: Value) -> RpcResult<Value> {
+        let value = match request {
+            RpcRequest::GetAccountInfo => self.ui_account(&address(&params[0])),
+            RpcRequest::GetMultipleAccounts => params[0]
+                .as_array()
+                .unwrap()
+                .iter()
+                .map(|key| self.ui_account(&address(key)))
+                .collect(),
+            other => panic!("unexpected request {other}"),
+        };
+        // Recorded once the accounts are read, so they can be changed right after.
+        if let Some(requests) = &self.requests {
+            let _ = requests.send(request);
+        }
+        Ok(json!({ "context": { "slot": 1 }, "value": value }))
+    }
+
+    fn get_transport_stats(&self) -> RpcTransportStats {
+        RpcTransportStats::default()
+    }
+
+    fn url(&self) -> String {
+        "stand-in".to_string()
+    }
+}
+
+/// A websocket endpoint handling account subscriptions, one connection at a time.
+struct Websocket {
+    url: String,
+    /// Subscribed address with its subscription id.
+    subscribed: UnboundedReceiver<(Pubkey, u64)>,
+    /// Ids of the ended subscriptions.
+    unsubscribed: UnboundedReceiver<u64>,
+    notify: UnboundedSender<(u64, Vec<u8>)>,
+    /// When each connection was accepted, refused ones included.
+    connections: UnboundedReceiver<Instant>,
+    /// Number of the next connections to refuse, closed before the websocket handshake.
+    refuse: Arc<AtomicUsize>,
+    /// Closes the current connection.
+    close: UnboundedSender<()>,
+}
+
+impl Websocket {
+    async fn start() -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("ws://{}", listener.local_addr().unwrap());
+        let (subscribed_sender, subscribed) = unbounded_channel();
+        let (unsubscribed_sender, unsubscribed) = unbounded_channel();
+        let (notify, mut notifications) = unbounded_channel::<(u64, Vec<u8>)>();
+        let (connections_sender, connections) = unbounded_channel();
+        let refuse = Arc::new(AtomicUsize::new(0));
+        let (close, mut close_requests) = unbounded_channel();
+
+        let refused = refuse.clone();
+        tokio::spawn(async move {
+            let mut next_id = 0u64;
+            loop {
+                let (stream, _) = listener.accept().await.unwrap();
+                let _ = connections_sender.send(Instant::now());
+                if refused
+                    .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |n| n.checked_sub(1))
+                    .is_ok()
+                {
+                    continue;
+                }
+                let mut ws = tokio_tungstenite::accept_async(stream).await.unwrap();
+                loop {
+                    tokio::select! {
+                        message = ws.next() => {
+                            let Some(Ok(Message::Text(text))) = message else {
+                                break;
+                            };
+                            let request: Value = serde_json::from_str(&text).unwrap();
+                            let result = match request["method"].as_str().unwrap() {
+                                "accountSubscribe" => {
+                                    next_id += 1;
+                                    let _ = subscribed_sender.send((address(&request["params"][0]), next_id));
+                                    json!(next_id)
+                                }
+                                "accountUnsubscribe" => {
+                                    let _ = unsubscribed_sender.send(request["params"][0].as_u64().unwrap());
+                                    json!(true)
+                                }
+                                other => panic!("unexpected method {other}"),
+                            };
+                            let response = json!({ "jsonrpc": "2.0", "result": result, "id": request["id"] });
+                            ws.send(Message::Text(response.to_string().into())).await.unwrap();
+                        }
+                        Some((subscription, data)) = notifications.recv() => {
+                            let notification = json!({
+                                "jsonrpc": "2.0",
+                                "method": "accountNotification",
+                                "params": {
+                                    "result": { "context": { "slot": 2 }, "value": ui_account(&data) },
+                                    "subscription": subscription,
+                                },
+                            });
+                            ws.send(Message::Text(notification.to_string().into())).await.unwrap();
+                        }
+                        Some(()) = close_requests.recv() => {
+                            let _ = ws.close(None).await;
+                            break;
+                        }
+                    }
+                }
+            }
+        });
+
+        Self {
+            url,
+            subscribed,
+            unsubscribed,
+            notify,
+            connections,
+            refuse,
+            close,
+        }
+    }
+
+    async fn next_connection(&mut self) -> Instant {
+        tokio::time::timeout(TIMEOUT, self.connections.recv())
+            .await
+            .unwrap()
+            .unwrap()
+    }
+
+    async fn next_subscription(&mut self) -> (Pubkey, u64) {
+        tokio::time::timeout(TIMEOUT, self.subscribed.recv())
+            .await
+            .unwrap()
+            .unwrap()
+    }
+
+    fn send(&self, subscription: u64, account: &impl borsh::BorshSerialize) {
+        self.notify
+            .send((subscription, borsh::to_vec(account).unwrap()))
+            .unwrap();
+    }
+}
+
+async fn next_item(
+    events: &mut (impl futures::Stream<Item = fortis_sdk::ClientResult<ProposalEvent>> + Unpin),
+) -> fortis_sdk::ClientResult<ProposalEvent> {
+    tokio::time::timeout(TIMEOUT, events.next())
+        .await
+        .expect("no event before the timeout")
+        .unwrap()
+}
+
+async fn next_events(
+    events: &mut (impl futures::Stream<Item = fortis_sdk::ClientResult<ProposalEvent>> + Unpin),
+    count: usize,
+) -> Vec<ProposalEvent> {
+    let mut received = Vec::new();
+    while received.len() < count {
+        let event = tokio::time::timeout(TIMEOUT, events.next())
+            .await
+            .expect("no event before the timeout")
+            .unwrap();
+        received.push(event.unwrap());
+    }
+    received
+}
+
+#[tokio::test]
+async fn watch_reports_new_proposal_without_waiting_for_poll() {
+    let fixture = Fixture::new();
+    let (requests_sender, mut requests) = unbounded_channel();
+    let chain = Chain {
+        requests: Some(requests_sender),
+        ..Chain::default()
+    };
+    chain.set(fixture.multisig_key, &fixture.multisig);
+    let mut websocket = Websocket::start().await;
+
+    let rpc_client = Arc::new(RpcClient::new_sender(
+        chain.clone(),
+        RpcClientConfig::default(),
+    ));
+    let mut config = WatchConfig::new(websocket.url.clone());
+    // Only the subscriptions can report anything within the test.
+    config.poll_interval = Duration::from_secs(3600);
+    let events = watch_proposals(rpc_client, fixture.multisig_key, config, None);
+    futures::pin_mut!(events);
+
+    let (address, multisig_subscription) = websocket.next_subscription().await;
+    assert_eq!(address, fixture.multisig_key);
+    // Seeding and the poll following the connection.
+    for _ in 0..2 {
+        let request = tokio::time::timeout(TIMEOUT, requests.recv())
+            .await
+            .unwrap();
+        assert_eq!(request, Some(RpcRequest::GetAccountInfo));
+    }
+
+    // The proposal is created by the transaction that increments the multisig index.
+    let created = fixture.proposal(1, ProposalStatus::Active, vec![]);
+    chain.set(fixture.proposal_key(1), &created);
+    chain.set(fixture.multisig_key, &fixture.multisig_at(1));
+    websocket.send(multisig_subscription, &fixture.multisig_at(1));
+
+    let (address, proposal_subscription) = websocket.next_subscription().await;
+    assert_eq!(address, fixture.proposal_key(1));
+    assert_eq!(
+        next_events(&mut events, 1).await,
+        vec![ProposalEvent::ProposalCreated {
+            transaction_index: 1,
+            proposal: created,
+        }]
+    );
+
+    let executed = fixture.proposal(1, ProposalStatus::Executed, vec![fixture.member]);
+    websocket.send(proposal_subscription, &executed);
+    assert_eq!(
+        next_events(&mut events, 3).await,
+        vec![
+            ProposalEvent::Approved {
+                transaction_index: 1,
+                member: fixture.member,
+            },
+            ProposalEvent::ThresholdReached {
+                transaction_index: 1
+            },
+            ProposalEvent::Executed {
+                transaction_index: 1
+            },
+        ]
+    );
+
+    // Executed proposals are not watched anymore.
+    let unsubscribed = tokio::time::timeout(TIMEOUT, websocket.unsubscribed.recv())
+        .await
+        .unwrap();
+    assert_eq!(unsubscribed, Some(proposal_subscription));
+}
+
+#[tokio::test]
+async fn watch_catches_up_after_the_subscription_drops() {
+    let fixture = Fixture::new();
+    let (requests_sender, mut requests) = unbounded_channel();
+    let chain = Chain {
+        requests: Some(requests_sender),
+        ..Chain::default()
+    };
+    chain.set(fixture.multisig_key, &fixture.multisig_at(1));
+    chain.set(
+        fixture.proposal_key(1),
+        &fixture.proposal(1, ProposalStatus::Active, vec![]),
+    );
+    let mut websocket = Websocket::start().await;
+
+    let rpc_client = Arc::new(RpcClient::new_sender(
+        chain.clone(),
+        RpcClientConfig::default(),
+    ));
+    let mut config = WatchConfig::new(websocket.url.clone());
+    config.poll_interval = Duration::from_secs(3600);
+    config.reconnect_delay = Duration::from_millis(10);
+    let events = watch_proposals(rpc_client, fixture.multisig_key, config, None);
+    futures::pin_mut!(events);
+
+    assert_eq!(websocket.next_subscription().await.0, fixture.multisig_key);
+    assert_eq!(
+        websocket.next_subscription().await.0,
+        fixture.proposal_key(1)
+    );
+    // Seeding, the poll following the connection and the fetch of the subscribed proposal.
+    for _ in 0..5 {
+        tokio::time::timeout(TIMEOUT, requests.recv())
+            .await
+            .unwrap()
+            .unwrap();
+    }
+
+    // Changes the websocket never notifies: the first proposal is executed
+    // and a second one is created.
+    chain.set(
+        fixture.proposal_key(1),
+        &fixture.proposal(1, ProposalStatus::Executed, vec![fixture.member]),
+    );
+    let created = fixture.proposal(2, ProposalStatus::Active, vec![]);
+    chain.set(fixture.proposal_key(2), &created);
+    chain.set(fixture.multisig_key, &fixture.multisig_at(2));
+    websocket.close.send(()).unwrap();
+
+    assert!(matches!(
+        next_item(&mut events).await,
+        Err(ClientError::SubscriptionClosed)
+    ));
+    // The poll following the reconnection reports them.
+    assert_eq!(
+        next_events(&mut events, 4).await,
+        vec![
+            ProposalEvent::Approved {
+                transaction_index: 1,
+                member: fixture.member,
+            },
+            ProposalEvent::ThresholdReached {
+                transaction_index: 1
+            },
+            ProposalEvent::Executed {
+                transaction_index: 1
+            },
+            ProposalEvent::ProposalCreated {
+                transaction_index: 2,
+                proposal: created,
+            },
+        ]
+    );
+    // Only the proposal still open is subscribed to again.
+    assert_eq!(websocket.next_subscription().await.0, fixture.multisig_key);
+    assert_eq!(
+        websocket.next_subscription().await.0,
+        fixture.proposal_key(2)
+    );
+}
+
+#[tokio::test]
+async fn watch_backs_off_between_failed_reconnections() {
+    const DELAY: Duration = Duration::from_millis(100);
+    const MAX_DELAY: Duration = Duration::from_millis(250);
+
+    let fixture = Fixture::new();
+    let chain = Chain::default();
+    chain.set(fixture.multisig_key, &fixture.multisig);
+    let mut websocket = Websocket::start().await;
+    websocket.refuse.store(3, Ordering::SeqCst);
+
+    let rpc_client = Arc::new(RpcClient::new_sender(chain, RpcClientConfig::default()));
+    let mut config = WatchConfig::new(websocket.url.clone());
+    config.poll_interval = Duration::from_secs(3600);
+    config.reconnect_delay = DELAY;
+    config.max_reconnect_delay = MAX_DELAY;
+    let events = watch_proposals(rpc_client, fixture.multisig_key, config, None);
+    futures::pin_mut!(events);
+
+    let mut connected = Vec::new();
+    for _ in 0..4 {
+        connected.push(websocket.next_connection().await);
+    }
+    for _ in 0..3 {
+        assert!(next_item(&mut events).await.is_err());
+    }
+    let waits: Vec<Duration> = connected.windows(2).map(|pair| pair[1] - pair[0]).collect();
+    assert!(waits[0] >= DELAY, "{waits:?}");
+    assert!(waits[1] >= 2 * DELAY, "{waits:?}");
+    // Capped instead of doubled to 400ms.
+    assert!(waits[2] >= MAX_DELAY && waits[2] < 4 * DELAY, "{waits:?}");
+
+    // A successful connection resets the delay.
+    assert_eq!(websocket.next_subscription().await.0, fixture.multisig_key);
+    let closed = Instant::now();
+    websocket.close.send(()).unwrap();
+    assert!(matches!(
+        next_item(&mut events).await,
+        Err(ClientError::SubscriptionClosed)
+    ));
+    let wait = websocket.next_connection().await - closed;
+    assert!(wait >= DELAY && wait < MAX_DELAY, "{wait:?}");
+    assert_eq!(websocket.next_subscription().await.0, fixture.multisig_key);
+}
+
+#[tokio::test]
+async fn watch_unsubscribes_from_expired_proposals() {
+    let fixture = Fixture::new();
+    let now = || {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs() as i64
+    };
+    // A second of slack, so it is not already expired when the watch starts.
+    let deadline = now() + 1;
+    let expiring = Proposal {
+        deadline,
+        ..fixture.proposal(1, ProposalStatus::Active, vec![])
+    };
+    let chain = Chain::default();
+    chain.set(fixture.multisig_key, &fixture.multisig_at(1));
+    chain.set(fixture.proposal_key(1), &expiring);
+    let mut websocket = Websocket::start().await;
+
+    let rpc_client = Arc::new(RpcClient::new_sender(chain, RpcClientConfig::default()));
+    let mut config = WatchConfig::new(websocket.url.clone());
+    config.poll_interval = Duration::from_secs(3600);
+    let events = watch_proposals(rpc_client, fixture.multisig_key, config, None);
+    futures::pin_mut!(events);
+
+    let (_, multisig_subscription) = websocket.next_subscription().await;
+    let (address, proposal_subscription) = websocket.next_subscription().await;
+    assert_eq!(address, fixture.proposal_key(1));
+
+    // Deadlines are checked on every update.
+    while now() <= deadline {
+        tokio::time::sleep(Duration::from_millis(50)).await;
+    }
+    websocket.send(multisig_subscription, &fixture.multisig_at(1));
+    assert_eq!(
+        next_events(&mut events, 1).await,
+        vec![ProposalEvent::Expired {
+            transaction_index: 1
+        }]
+    );
+    let unsubscribed = tokio::time::timeout(TIMEOUT, websocket.unsubscribed.recv())
+        .await
+        .unwrap();
+    assert_eq!(unsubscribed, Some(proposal_subscription));
+}